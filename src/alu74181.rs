@@ -0,0 +1,106 @@
+//! 74181 4 bit ALU slice
+
+use arbitrary_int::u4;
+
+/// ### 74181 Arithmetic Logic Unit
+///
+/// A 4 bit slice which can perform 16 logic functions and 16 arithmetic functions. Several slices can be chained through the carry to build wider bit-slice machines.
+///
+/// The function is chosen by `select` (S3 S2 S1 S0) and `mode` (M). This follows the active high data table of the datasheet.
+///
+/// Unlike the real chip, where the carry pins are active low, `carry_in` and the returned carry are positive. `true` means a carry happened.
+///
+/// `select` | Logic (`mode` = true) | Arithmetic (`mode` = false)
+/// ---------|-----------------------|----------------------------
+/// 0000     | !A                    | A
+/// 0001     | !(A \| B)             | A \| B
+/// 0010     | !A & B                | A \| !B
+/// 0011     | 0                     | minus 1
+/// 0100     | !(A & B)              | A plus (A & !B)
+/// 0101     | !B                    | (A \| B) plus (A & !B)
+/// 0110     | A ^ B                 | A minus B minus 1
+/// 0111     | A & !B                | (A & !B) minus 1
+/// 1000     | !A \| B               | A plus (A & B)
+/// 1001     | !(A ^ B)              | A plus B
+/// 1010     | B                     | (A \| !B) plus (A & B)
+/// 1011     | A & B                 | (A & B) minus 1
+/// 1100     | 1111                  | A plus A
+/// 1101     | A \| !B               | (A \| B) plus A
+/// 1110     | A \| B                | (A \| !B) plus A
+/// 1111     | A                     | A minus 1
+///
+/// Arithmetic functions have 1 added to them when `carry_in` is set. Logic functions ignore the carry, and never carry out.
+///
+/// ### Example
+/// ```
+/// use arbitrary_int::u4;
+/// use chips::alu74181::S74X181;
+///
+/// //A plus B
+/// assert_eq!(S74X181::operate(u4::new(5), u4::new(6), u4::new(0b1001), false, false), (u4::new(11), false));
+/// assert_eq!(S74X181::operate(u4::new(9), u4::new(8), u4::new(0b1001), false, true), (u4::new(2), true));
+///
+/// //A minus B, done as A minus B minus 1 with the carry set. Carry out means no borrow.
+/// assert_eq!(S74X181::operate(u4::new(9), u4::new(3), u4::new(0b0110), false, true), (u4::new(6), true));
+/// assert_eq!(S74X181::operate(u4::new(3), u4::new(9), u4::new(0b0110), false, true), (u4::new(0xA), false));
+///
+/// //A AND B
+/// assert_eq!(S74X181::operate(u4::new(0b1011), u4::new(0b1101), u4::new(0b1011), true, false), (u4::new(0b1001), false));
+///
+/// //A OR B
+/// assert_eq!(S74X181::operate(u4::new(0b1011), u4::new(0b1101), u4::new(0b1110), true, false), (u4::new(0b1111), false));
+/// ```
+pub struct S74X181;
+
+impl S74X181 {
+  /// Run one of the 32 functions on A and B.
+  ///
+  /// Returns (result, carry)
+  pub fn operate(a: u4, b: u4, select: u4, mode: bool, carry_in: bool) -> (u4, bool) {
+    let (a, b) = (a.value(), b.value());
+    let (not_a, not_b) = (!a & 0xF, !b & 0xF);
+    if mode {
+      let result = match select.value() {
+        0b0000 => not_a,
+        0b0001 => !(a | b),
+        0b0010 => not_a & b,
+        0b0011 => 0,
+        0b0100 => !(a & b),
+        0b0101 => not_b,
+        0b0110 => a ^ b,
+        0b0111 => a & not_b,
+        0b1000 => not_a | b,
+        0b1001 => !(a ^ b),
+        0b1010 => b,
+        0b1011 => a & b,
+        0b1100 => 0xF,
+        0b1101 => a | not_b,
+        0b1110 => a | b,
+        _ => a, //0b1111
+      };
+      (u4::new(result & 0xF), false)
+    } else {
+      //Every arithmetic function is the sum of two terms. "minus 1" is the same as adding 0xF.
+      let (x, y) = match select.value() {
+        0b0000 => (a, 0),
+        0b0001 => (a | b, 0),
+        0b0010 => (a | not_b, 0),
+        0b0011 => (0, 0xF),
+        0b0100 => (a, a & not_b),
+        0b0101 => (a | b, a & not_b),
+        0b0110 => (a, not_b),
+        0b0111 => (a & not_b, 0xF),
+        0b1000 => (a, a & b),
+        0b1001 => (a, b),
+        0b1010 => (a | not_b, a & b),
+        0b1011 => (a & b, 0xF),
+        0b1100 => (a, a),
+        0b1101 => (a | b, a),
+        0b1110 => (a | not_b, a),
+        _ => (a, 0xF), //0b1111
+      };
+      let sum = x + y + carry_in as u8;
+      (u4::new(sum & 0xF), sum > 0xF)
+    }
+  }
+}
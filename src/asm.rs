@@ -0,0 +1,223 @@
+//! Assembler for small Intel 8080 programs, such as test programs, so they don't need to be encoded by hand
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// Reasons a program could not be assembled. Lines are counted from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsmError {
+  /// The mnemonic is not an 8080 instruction
+  UnknownMnemonic {
+    /// Line of the instruction
+    line: usize,
+  },
+  /// Wrong number of operands, an unknown register, or a number which doesn't fit
+  InvalidOperand {
+    /// Line of the instruction
+    line: usize,
+  },
+  /// A label was used but never defined
+  UndefinedLabel {
+    /// Line using the label
+    line: usize,
+  },
+  /// The same label was defined twice
+  DuplicateLabel {
+    /// Line of the second definition
+    line: usize,
+  },
+  /// A label is past the 64 KB the 8080 can address
+  AddressOverflow {
+    /// Line of the label
+    line: usize,
+  },
+}
+
+const REGS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "M", "A"];
+const PAIRS: [&str; 4] = ["B", "D", "H", "SP"];
+const PAIRS_PUSH_POP: [&str; 4] = ["B", "D", "H", "PSW"];
+const CONDITIONS: [&str; 8] = ["NZ", "Z", "NC", "C", "PO", "PE", "P", "M"];
+const ALU: [&str; 8] = ["ADD", "ADC", "SUB", "SBB", "ANA", "XRA", "ORA", "CMP"];
+const ALU_IMMEDIATE: [&str; 8] = ["ADI", "ACI", "SUI", "SBI", "ANI", "XRI", "ORI", "CPI"];
+
+/// Instructions without operands
+const IMPLIED: [(&str, u8); 17] = [
+  ("NOP", 0x00), ("RLC", 0x07), ("RRC", 0x0F), ("RAL", 0x17), ("RAR", 0x1F), ("DAA", 0x27), ("CMA", 0x2F), ("STC", 0x37),
+  ("CMC", 0x3F), ("HLT", 0x76), ("RET", 0xC9), ("XTHL", 0xE3), ("PCHL", 0xE9), ("XCHG", 0xEB), ("DI", 0xF3), ("SPHL", 0xF9),
+  ("EI", 0xFB),
+];
+
+/// Instructions taking a 16 bit address or label
+const ADDRESSED: [(&str, u8); 6] = [
+  ("SHLD", 0x22), ("LHLD", 0x2A), ("STA", 0x32), ("LDA", 0x3A), ("JMP", 0xC3), ("CALL", 0xCD),
+];
+
+/// A 16 bit operand whose label is filled in once all labels are known
+struct Fixup {
+  /// Where in the output the word goes
+  at: usize,
+  label: String,
+  line: usize,
+}
+
+/// ### Assemble 8080 Code
+///
+/// Turns Intel style assembly into machine code, starting at address 0.
+///
+/// Each line holds an optional `label:`, an optional instruction, and an optional `;` comment. Mnemonics, registers and labels are not case sensitive.
+///
+/// Numbers can be decimal `42`, hex `0x2A`, or Intel hex `2AH`. Any 16 bit operand can be a label instead. `DB` places raw bytes.
+///
+/// ### Example
+/// ```
+/// use chips::asm::assemble;
+///
+/// let program = assemble("
+///   start: MVI A, 5  ; count down from 5
+///   loop:  DCR A
+///          JNZ loop
+///          JMP start
+/// ").unwrap();
+/// assert_eq!(program, [0x3E, 0x05, 0x3D, 0xC2, 0x02, 0x00, 0xC3, 0x00, 0x00]);
+/// ```
+pub fn assemble(source: &str) -> Result<Vec<u8>, AsmError> {
+  let mut bytes = Vec::new();
+  let mut labels: Vec<(String, u16)> = Vec::new();
+  let mut fixups = Vec::new();
+
+  for (index, text) in source.lines().enumerate() {
+    let line = index + 1;
+    let mut text = text.split(';').next().unwrap_or("").trim();
+    if let Some((label, rest)) = text.split_once(':') {
+      let label = label.trim().to_ascii_uppercase();
+      if labels.iter().any(|(name, _)| *name == label) {
+        return Err(AsmError::DuplicateLabel { line });
+      }
+      let address = u16::try_from(bytes.len()).map_err(|_| AsmError::AddressOverflow { line })?;
+      labels.push((label, address));
+      text = rest.trim();
+    }
+    if text.is_empty() {
+      continue;
+    }
+    let (mnemonic, operands) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let mnemonic = mnemonic.to_ascii_uppercase();
+    let operands: Vec<String> = operands.split(',').map(|operand| operand.trim().to_ascii_uppercase()).filter(|operand| !operand.is_empty()).collect();
+    assemble_line(&mnemonic, &operands, line, &mut bytes, &mut fixups)?;
+  }
+
+  for fixup in fixups {
+    let Some(&(_, address)) = labels.iter().find(|(name, _)| *name == fixup.label) else {
+      return Err(AsmError::UndefinedLabel { line: fixup.line });
+    };
+    bytes[fixup.at..fixup.at + 2].copy_from_slice(&address.to_le_bytes());
+  }
+  Ok(bytes)
+}
+
+fn assemble_line(mnemonic: &str, operands: &[String], line: usize, bytes: &mut Vec<u8>, fixups: &mut Vec<Fixup>) -> Result<(), AsmError> {
+  let invalid = AsmError::InvalidOperand { line };
+  let count = |expected: usize| if operands.len() == expected { Ok(()) } else { Err(invalid) };
+  let lookup = |table: &[&str], operand: &str| table.iter().position(|&name| name == operand).map(|index| index as u8).ok_or(invalid);
+  let byte = |operand: &str| parse_number(operand).filter(|&value| value <= 0xFF).map(|value| value as u8).ok_or(invalid);
+
+  //Pushes a 16 bit operand, which could be a label
+  let mut word = |bytes: &mut Vec<u8>, operand: &str| -> Result<(), AsmError> {
+    match parse_number(operand) {
+      Some(value) if value <= 0xFFFF => bytes.extend_from_slice(&(value as u16).to_le_bytes()),
+      Some(_) => return Err(invalid),
+      None if is_label(operand) => {
+        fixups.push(Fixup { at: bytes.len(), label: operand.to_string(), line });
+        bytes.extend_from_slice(&[0, 0]);
+      },
+      None => return Err(invalid),
+    }
+    Ok(())
+  };
+
+  if let Some(&(_, opcode)) = IMPLIED.iter().find(|(name, _)| *name == mnemonic) {
+    count(0)?;
+    bytes.push(opcode);
+  } else if let Some(&(_, opcode)) = ADDRESSED.iter().find(|(name, _)| *name == mnemonic) {
+    count(1)?;
+    bytes.push(opcode);
+    word(bytes, &operands[0])?;
+  } else if let Some(op) = ALU.iter().position(|&name| name == mnemonic) {
+    count(1)?;
+    bytes.push(0x80 | (op as u8) << 3 | lookup(&REGS, &operands[0])?);
+  } else if let Some(op) = ALU_IMMEDIATE.iter().position(|&name| name == mnemonic) {
+    count(1)?;
+    bytes.extend_from_slice(&[0xC6 | (op as u8) << 3, byte(&operands[0])?]);
+  } else {
+    match mnemonic {
+      "MOV" => {
+        count(2)?;
+        let (dest, source) = (lookup(&REGS, &operands[0])?, lookup(&REGS, &operands[1])?);
+        if dest == 6 && source == 6 {
+          return Err(invalid);  //That would be HLT
+        }
+        bytes.push(0x40 | dest << 3 | source);
+      },
+      "MVI" => {
+        count(2)?;
+        bytes.extend_from_slice(&[0x06 | lookup(&REGS, &operands[0])? << 3, byte(&operands[1])?]);
+      },
+      "INR" => { count(1)?; bytes.push(0x04 | lookup(&REGS, &operands[0])? << 3); },
+      "DCR" => { count(1)?; bytes.push(0x05 | lookup(&REGS, &operands[0])? << 3); },
+      "LXI" => {
+        count(2)?;
+        bytes.push(0x01 | lookup(&PAIRS, &operands[0])? << 4);
+        word(bytes, &operands[1])?;
+      },
+      "DAD" => { count(1)?; bytes.push(0x09 | lookup(&PAIRS, &operands[0])? << 4); },
+      "INX" => { count(1)?; bytes.push(0x03 | lookup(&PAIRS, &operands[0])? << 4); },
+      "DCX" => { count(1)?; bytes.push(0x0B | lookup(&PAIRS, &operands[0])? << 4); },
+      "STAX" => { count(1)?; bytes.push(0x02 | lookup(&PAIRS[..2], &operands[0])? << 4); },
+      "LDAX" => { count(1)?; bytes.push(0x0A | lookup(&PAIRS[..2], &operands[0])? << 4); },
+      "PUSH" => { count(1)?; bytes.push(0xC5 | lookup(&PAIRS_PUSH_POP, &operands[0])? << 4); },
+      "POP" => { count(1)?; bytes.push(0xC1 | lookup(&PAIRS_PUSH_POP, &operands[0])? << 4); },
+      "IN" => { count(1)?; bytes.extend_from_slice(&[0xDB, byte(&operands[0])?]); },
+      "OUT" => { count(1)?; bytes.extend_from_slice(&[0xD3, byte(&operands[0])?]); },
+      "RST" => {
+        count(1)?;
+        let index = parse_number(&operands[0]).filter(|&index| index < 8).ok_or(invalid)?;
+        bytes.push(0xC7 | (index as u8) << 3);
+      },
+      "DB" => {
+        if operands.is_empty() {
+          return Err(invalid);
+        }
+        for operand in operands {
+          bytes.push(byte(operand)?);
+        }
+      },
+      _ => {
+        //Conditional jumps, calls and returns, such as JNZ, CPE or RC
+        let (kind, condition) = mnemonic.split_at_checked(1).ok_or(AsmError::UnknownMnemonic { line })?;
+        let condition = CONDITIONS.iter().position(|&name| name == condition).ok_or(AsmError::UnknownMnemonic { line })? as u8;
+        match kind {
+          "R" => { count(0)?; bytes.push(0xC0 | condition << 3); },
+          "J" => { count(1)?; bytes.push(0xC2 | condition << 3); word(bytes, &operands[0])?; },
+          "C" => { count(1)?; bytes.push(0xC4 | condition << 3); word(bytes, &operands[0])?; },
+          _ => return Err(AsmError::UnknownMnemonic { line }),
+        }
+      },
+    }
+  }
+  Ok(())
+}
+
+/// Decimal, `0x` hex, or `H` suffixed hex
+fn parse_number(operand: &str) -> Option<u32> {
+  if let Some(hex) = operand.strip_prefix("0X") {
+    u32::from_str_radix(hex, 16).ok()
+  } else if let Some(hex) = operand.strip_suffix('H').filter(|hex| hex.starts_with(|c: char| c.is_ascii_digit())) {
+    u32::from_str_radix(hex, 16).ok()
+  } else {
+    operand.parse().ok()
+  }
+}
+
+fn is_label(operand: &str) -> bool {
+  operand.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') && operand.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,131 @@
+//! Serial BCD registers, as used by calculator chips
+
+use arbitrary_int::u4;
+use alloc::string::String;
+
+/// ### Digit Serial BCD Register
+///
+/// Calculator chips keep numbers as a row of 4 bit decimal digits, and do their math one digit at a time, starting from the least significant digit.
+///
+/// From 1 up to 16 digits fit. Other sizes fail to compile. Digit 0 is the least significant.
+///
+/// ### Example
+/// ```
+/// use chips::BcdRegister;
+///
+/// let mut register: BcdRegister<6> = BcdRegister::new(0x001999);
+/// let carry = register.add(&BcdRegister::new(0x000001), false);
+/// assert!(!carry);
+/// assert_eq!(register.to_decimal_string(), "002000");
+/// ```
+///
+/// 17 digits don't fit in a u64:
+/// ```compile_fail
+/// use chips::BcdRegister;
+///
+/// let register: BcdRegister<17> = BcdRegister::new(0);
+/// ```
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BcdRegister<const DIGITS: usize> {
+  /// The register's "memory", one digit per nibble
+  data: u64,
+}
+
+impl<const DIGITS: usize> BcdRegister<DIGITS> {
+  const MASK: u64 = u64::MAX >> (u64::BITS as usize - DIGITS * 4);
+
+  /// Create a new register. Each nibble of `data` is one digit, so `0x1234` holds the number 1234.
+  #[inline]
+  pub fn new(data: u64) -> Self {
+    const { assert!(DIGITS >= 1 && DIGITS <= 16, "A register holds 1 to 16 digits, one per nibble of a u64") };
+    Self {
+      data: data & Self::MASK,
+    }
+  }
+
+  /// Read all digits at once
+  #[inline]
+  pub fn read_parallel(&self) -> u64 {
+    self.data
+  }
+
+  /// Read a single digit
+  #[inline]
+  pub fn digit(&self, index: usize) -> u4 {
+    u4::new(((self.data >> (index * 4)) & 0xF) as u8)
+  }
+
+  /// Write a single digit
+  #[inline]
+  pub fn set_digit(&mut self, index: usize, digit: u4) {
+    let shift = index * 4;
+    self.data = ((self.data & !(0xF << shift)) | ((digit.value() as u64) << shift)) & Self::MASK;
+  }
+
+  /// ### BCD Add
+  ///
+  /// Adds `other` digit by digit, starting with `carry_in`. Returns the carry out of the most significant digit.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::BcdRegister;
+  ///
+  /// let mut register: BcdRegister<4> = BcdRegister::new(0x0095);
+  /// assert!(!register.add(&BcdRegister::new(0x0007), true));
+  /// assert_eq!(register.read_parallel(), 0x0103);
+  ///
+  /// //Carry ripples out of the top digit
+  /// let mut register: BcdRegister<4> = BcdRegister::new(0x9999);
+  /// assert!(register.add(&BcdRegister::new(0x0000), true));
+  /// assert_eq!(register.read_parallel(), 0x0000);
+  /// ```
+  pub fn add(&mut self, other: &Self, carry_in: bool) -> bool {
+    let mut carry = carry_in;
+    for index in 0..DIGITS {
+      let mut sum = self.digit(index).value() + other.digit(index).value() + carry as u8;
+      carry = sum >= 10;
+      if carry {
+        sum -= 10;
+      }
+      self.set_digit(index, u4::new(sum & 0xF));
+    }
+    carry
+  }
+
+  /// ### BCD Subtract
+  ///
+  /// Subtracts `other` digit by digit, starting with `borrow_in`. Returns the borrow out of the most significant digit.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::BcdRegister;
+  ///
+  /// let mut register: BcdRegister<4> = BcdRegister::new(0x0103);
+  /// assert!(!register.sub(&BcdRegister::new(0x0007), true));
+  /// assert_eq!(register.read_parallel(), 0x0095);
+  ///
+  /// //Borrowing past the top digit wraps around to 9s complement
+  /// let mut register: BcdRegister<4> = BcdRegister::new(0x0000);
+  /// assert!(register.sub(&BcdRegister::new(0x0001), false));
+  /// assert_eq!(register.read_parallel(), 0x9999);
+  /// ```
+  pub fn sub(&mut self, other: &Self, borrow_in: bool) -> bool {
+    let mut borrow = borrow_in;
+    for index in 0..DIGITS {
+      let mut difference = self.digit(index).value() as i8 - other.digit(index).value() as i8 - borrow as i8;
+      borrow = difference < 0;
+      if borrow {
+        difference += 10;
+      }
+      self.set_digit(index, u4::new(difference as u8 & 0xF));
+    }
+    borrow
+  }
+
+  /// All digits, most significant first. Nibbles which aren't decimal show up as hex.
+  pub fn to_decimal_string(&self) -> String {
+    (0..DIGITS).rev()
+      .map(|index| char::from_digit(self.digit(index).value() as u32, 16).unwrap_or('?').to_ascii_uppercase())
+      .collect()
+  }
+}
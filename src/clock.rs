@@ -0,0 +1,60 @@
+//! Clock source chips
+
+/// ### 555 style Clock
+///
+/// Abstract clock source, which divides down a faster tick. Every `period` ticks, it outputs a rising edge.
+///
+/// Boards can use it to gate subsystems which run slower than the main `run_cycle` loop.
+///
+/// ### Example
+/// ```
+/// use chips::Clock;
+///
+/// let mut clock = Clock::new(3);
+/// assert_eq!(clock.tick(), false);
+/// assert_eq!(clock.tick(), false);
+/// assert_eq!(clock.tick(), true);
+/// assert_eq!(clock.tick(), false);
+/// assert_eq!(clock.tick(), false);
+/// assert_eq!(clock.tick(), true);
+///
+/// clock.set_period(1);
+/// assert_eq!(clock.tick(), true);
+/// assert_eq!(clock.tick(), true);
+/// ```
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct Clock {
+  /// How many ticks between each rising edge
+  period: u32,
+  /// Ticks since the last rising edge
+  counter: u32,
+}
+
+impl Clock {
+  /// Create a new clock. A period of 0 behaves like 1, firing on every tick.
+  #[inline]
+  pub fn new(period: u32) -> Self {
+    Self {
+      period,
+      counter: 0,
+    }
+  }
+
+  /// Advance by one tick. Returns true on the rising edge.
+  #[inline]
+  pub fn tick(&mut self) -> bool {
+    self.counter += 1;
+    if self.counter >= self.period {
+      self.counter = 0;
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Change the period. Ticks already counted toward the next edge are kept.
+  #[inline]
+  pub fn set_period(&mut self, period: u32) {
+    self.period = period;
+  }
+}
@@ -28,15 +28,39 @@ pub type S74X393 = Counter<u4>;
 pub struct Counter<T> {
   /// The counter's "memory"
   count: T,
+  /// Highest count before wrapping back to 0. `None` uses the whole width of `T`.
+  max: Option<T>,
 }
 
-impl<T: default::Default + Copy + wrapping::WrappingAdd + arbitrary_int::Number> Counter<T> {
+impl<T: default::Default + Copy + PartialEq + wrapping::WrappingAdd + wrapping::WrappingSub + arbitrary_int::Number> Counter<T> {
   /// Create a new Counter chip
   #[inline]
   pub fn new() -> Self {
     Default::default()
   }
 
+  /// ### Counter with a Custom Max
+  ///
+  /// Counts from 0 up to and including `max`, then wraps back to 0. Counting down from 0 wraps to `max`.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Counter;
+  ///
+  /// let mut counter: Counter<u8> = Counter::new_with_max(2);
+  /// assert_eq!(counter.increment(), 1);
+  /// assert_eq!(counter.increment(), 2);
+  /// assert_eq!(counter.increment(), 0);
+  /// assert_eq!(counter.decrement(), 2);
+  /// ```
+  #[inline]
+  pub fn new_with_max(max: T) -> Self {
+    Self {
+      count: Default::default(),
+      max: Some(max),
+    }
+  }
+
   /// Read current count without changing it
   #[inline]
   pub fn clear(&mut self) -> T {
@@ -47,7 +71,20 @@ impl<T: default::Default + Copy + wrapping::WrappingAdd + arbitrary_int::Number>
   /// Increment count
   #[inline]
   pub fn increment(&mut self) -> T {
-    self.count = self.count.wrapping_add(&T::new(1.into()));
+    self.count = match self.max {
+      Some(max) if self.count == max => Default::default(),
+      _ => self.count.wrapping_add(&T::new(1.into())),
+    };
+    self.count
+  }
+
+  /// Decrement count
+  #[inline]
+  pub fn decrement(&mut self) -> T {
+    self.count = match self.max {
+      Some(max) if self.count == Default::default() => max,
+      _ => self.count.wrapping_sub(&T::new(1.into())),
+    };
     self.count
   }
   
@@ -56,4 +93,210 @@ impl<T: default::Default + Copy + wrapping::WrappingAdd + arbitrary_int::Number>
   pub fn read(&self) -> T {
     self.count
   }
-}
\ No newline at end of file
+}
+
+/// ### Ring Counter
+///
+/// A single set bit rotates through N positions. Often used to drive scan lines or multiplexed display digits one at a time.
+///
+/// ```
+/// use chips::counter::RingCounter;
+///
+/// let mut ring: RingCounter<4> = RingCounter::new();
+/// assert_eq!(ring.read(), 0b0001);
+/// assert_eq!(ring.step(), 0b0010);
+/// assert_eq!(ring.step(), 0b0100);
+/// assert_eq!(ring.step(), 0b1000);
+/// assert_eq!(ring.step(), 0b0001);  //Back to the start after N steps
+/// ```
+#[derive(Clone, Copy, PartialEq)]
+pub struct RingCounter<const N: usize> {
+  /// The counter's "memory"
+  state: u32,
+}
+
+impl<const N: usize> Default for RingCounter<N> {
+  #[inline]
+  fn default() -> Self {
+    Self {
+      state: 1,
+    }
+  }
+}
+
+impl<const N: usize> RingCounter<N> {
+  const MASK: u32 = u32::MAX >> (u32::BITS - N as u32);
+
+  /// Create a new Ring Counter with the first bit set
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Rotate the set bit to the next position
+  #[inline]
+  pub fn step(&mut self) -> u32 {
+    self.state = ((self.state << 1) | (self.state >> (N - 1))) & Self::MASK;
+    self.state
+  }
+
+  /// Read current state without changing it
+  #[inline]
+  pub fn read(&self) -> u32 {
+    self.state
+  }
+}
+
+/// ### Johnson Counter
+///
+/// Also known as a twisted ring counter. The inverted last bit is fed back in, so it runs through 2N states.
+///
+/// ```
+/// use chips::counter::JohnsonCounter;
+///
+/// let mut johnson: JohnsonCounter<3> = JohnsonCounter::new();
+/// assert_eq!(johnson.read(), 0b000);
+/// assert_eq!(johnson.step(), 0b001);
+/// assert_eq!(johnson.step(), 0b011);
+/// assert_eq!(johnson.step(), 0b111);
+/// assert_eq!(johnson.step(), 0b110);
+/// assert_eq!(johnson.step(), 0b100);
+/// assert_eq!(johnson.step(), 0b000);  //Back to the start after 2N steps
+/// ```
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct JohnsonCounter<const N: usize> {
+  /// The counter's "memory"
+  state: u32,
+}
+
+impl<const N: usize> JohnsonCounter<N> {
+  const MASK: u32 = u32::MAX >> (u32::BITS - N as u32);
+
+  /// Create a new Johnson Counter with all bits cleared
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Shift in the inverted last bit
+  #[inline]
+  pub fn step(&mut self) -> u32 {
+    let feedback = (self.state >> (N - 1)) & 1 ^ 1;
+    self.state = ((self.state << 1) | feedback) & Self::MASK;
+    self.state
+  }
+
+  /// Read current state without changing it
+  #[inline]
+  pub fn read(&self) -> u32 {
+    self.state
+  }
+}
+
+/// ### Clock Divider
+///
+/// Divides an input clock by a fixed ratio, outputting once every DIV input ticks. Unlike `Clock`, the ratio is fixed when the board is wired.
+///
+/// ```
+/// use chips::counter::ClockDivider;
+///
+/// let mut divider: ClockDivider<3> = ClockDivider::new();
+/// let outputs: Vec<bool> = (0..9).map(|_| divider.tick()).collect();
+/// assert_eq!(outputs, [false, false, true, false, false, true, false, false, true]);
+///
+/// //Exactly once every DIV ticks
+/// let mut divider: ClockDivider<1000> = ClockDivider::new();
+/// assert_eq!((0..1_000_000).filter(|_| divider.tick()).count(), 1000);
+/// ```
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct ClockDivider<const DIV: u32> {
+  /// Input ticks since the last output
+  count: u32,
+}
+
+impl<const DIV: u32> ClockDivider<DIV> {
+  /// Create a new Clock Divider
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Advance by one input tick. Returns true on every DIV'th tick.
+  #[inline]
+  pub fn tick(&mut self) -> bool {
+    self.count += 1;
+    if self.count >= DIV {
+      self.count = 0;
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Look up how many input ticks it takes for each output.
+  pub const DIV: u32 = DIV;
+}
+
+/// ### BCD Digit Counter
+///
+/// Counts a single decimal digit up or down, wrapping 9 to 0 and 0 to 9 rather than through all 16 nibble values. The wrap is reported as a carry or borrow, which can step the next digit.
+///
+/// ```
+/// use arbitrary_int::u4;
+/// use chips::counter::BcdDigitCounter;
+///
+/// let mut digit = BcdDigitCounter::new();
+/// assert!(digit.down());  //Borrow out of 0
+/// assert_eq!(digit.read(), u4::new(9));
+/// assert!(digit.up());  //Carry out of 9
+/// assert_eq!(digit.read(), u4::new(0));
+/// ```
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct BcdDigitCounter {
+  /// The counter's "memory", always 0 to 9
+  digit: u4,
+}
+
+impl BcdDigitCounter {
+  /// Create a new BCD Digit Counter at 0
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Count up. Returns true on the carry from 9 to 0.
+  #[inline]
+  pub fn up(&mut self) -> bool {
+    if self.digit.value() >= 9 {
+      self.digit = u4::new(0);
+      true
+    } else {
+      self.digit += u4::new(1);
+      false
+    }
+  }
+
+  /// Count down. Returns true on the borrow from 0 to 9.
+  #[inline]
+  pub fn down(&mut self) -> bool {
+    if self.digit.value() == 0 {
+      self.digit = u4::new(9);
+      true
+    } else {
+      self.digit -= u4::new(1);
+      false
+    }
+  }
+
+  /// Reset to 0
+  #[inline]
+  pub fn clear(&mut self) {
+    self.digit = u4::new(0);
+  }
+
+  /// Read current digit without changing it
+  #[inline]
+  pub fn read(&self) -> u4 {
+    self.digit
+  }
+}
@@ -9,6 +9,7 @@ pub mod hp_cnt; pub use hp_cnt::HP_CnT;
 pub mod hp_anr; pub use hp_anr::HP_AnR;
 pub mod tms0800; pub use tms0800::TMS0800;
 pub mod tms_alu;*/
+pub mod i8080; pub use i8080::I8080;
 
 /// The data is being requested from RAM and ROM chips with methods in this trait
 pub trait MemoryIO<ADDRESS> {
@@ -18,6 +19,211 @@ pub trait MemoryIO<ADDRESS> {
   fn write_mem<T: crate::WriteArr>(&mut self, address: ADDRESS, value: T);
 }
 
+/// Direction of a memory access
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+  /// The CPU read from memory
+  Read,
+  /// The CPU wrote to memory
+  Write,
+}
+
+/// ### Memory Access Tracer
+///
+/// Wraps any memory, reporting every byte read or written to a closure, before passing the access on.
+///
+/// Wider accesses are split into single byte accesses, lowest address first.
+///
+/// ### Example
+/// ```
+/// use chips::cpu::{Access, MemoryIO, NullIo, TracingMemory};
+///
+/// let mut accesses = vec![];
+/// let mut memory = TracingMemory::new(NullIo::new(&[0x12, 0x34]), |access, address, value| accesses.push((access, address, value)));
+/// let _: u16 = memory.read_mem(0);
+/// memory.write_mem(5, 0x56_u8);
+/// drop(memory);
+/// assert_eq!(accesses, [(Access::Read, 0, 0x12), (Access::Read, 1, 0x34), (Access::Write, 5, 0x56)]);
+/// ```
+pub struct TracingMemory<M, F: FnMut(Access, u16, u8)> {
+  /// The wrapped memory
+  pub inner: M,
+  trace: core::cell::RefCell<F>,
+}
+
+impl<M, F: FnMut(Access, u16, u8)> TracingMemory<M, F> {
+  /// Wrap `inner`, calling `trace` with (access, address, value) on every byte
+  #[inline]
+  pub fn new(inner: M, trace: F) -> Self {
+    Self {
+      inner,
+      trace: core::cell::RefCell::new(trace),
+    }
+  }
+}
+
+impl<M: MemoryIO<u16>, F: FnMut(Access, u16, u8)> MemoryIO<u16> for TracingMemory<M, F> {
+  fn read_mem<T: crate::ReadArr>(&self, address: u16) -> T {
+    let mut bytes = [0; 8];
+    for (index, byte) in bytes.iter_mut().take(core::mem::size_of::<T>()).enumerate() {
+      let address = address.wrapping_add(index as u16);
+      *byte = self.inner.read_mem(address);
+      (self.trace.borrow_mut())(Access::Read, address, *byte);
+    }
+    T::read(&bytes)
+  }
+
+  fn write_mem<T: crate::WriteArr>(&mut self, address: u16, value: T) {
+    let mut bytes = [0; 8];
+    T::write(&mut bytes, value);
+    for (index, &byte) in bytes.iter().take(core::mem::size_of::<T>()).enumerate() {
+      let address = address.wrapping_add(index as u16);
+      (self.trace.get_mut())(Access::Write, address, byte);
+      self.inner.write_mem(address, byte);
+    }
+  }
+}
+
+/// Common interface of the CPU cores, so that run loops can be written once for all of them.
+///
+/// `IO` is whatever the core needs to talk to its board.
+pub trait Chip<IO> {
+  /// Run one full instruction. Returns how many clock cycles it took.
+  fn run_instruction(&mut self, io: &mut IO) -> u32;
+  /// Address of the next instruction. Some cores keep it outside the CPU, so `io` is passed in too.
+  fn pc(&self, io: &IO) -> u16;
+  /// Put the chip back to its power on state, as the reset line would.
+  fn reset(&mut self);
+  /// Human readable name of the chip, such as "Intel 8080".
+  fn name(&self) -> &'static str;
+}
+
+/// What a single `step` ran, for single step debuggers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepInfo {
+  /// Address of the instruction
+  pub pc_before: u16,
+  /// First byte of the instruction
+  pub opcode: u8,
+  /// Clock cycles it took
+  pub cycles: u32,
+}
+
+/// Run a fixed number of instructions. Returns the total clock cycles taken.
+///
+/// ### Example
+/// ```
+/// use chips::cpu::{self, I8080, NullIo};
+///
+/// let mut io = NullIo::new(&[]); //Reads as all 0, a sled of NOPs
+/// let mut cpu = I8080::new();
+/// assert_eq!(cpu::run_for(&mut cpu, &mut io, 10), 10 * 4);
+/// assert_eq!(cpu.state().pc, 10);
+/// ```
+pub fn run_for<IO>(chip: &mut impl Chip<IO>, io: &mut IO, instructions: usize) -> u64 {
+  (0..instructions).map(|_| chip.run_instruction(io) as u64).sum()
+}
+
+/// ### Run Until PC
+///
+/// Run instructions until the program counter reaches `target`, such as a success or failure address in a test program. Returns false if `max_cycles` clock cycles went by first.
+///
+/// The PC is checked before every instruction, so a chip already at `target` runs nothing.
+///
+/// ### Example
+/// ```
+/// use chips::cpu::{self, I8080, NullIo};
+///
+/// let program = [
+///   0x3E, 0x03, //MVI A, 3
+///   0x3D,       //DCR A
+///   0xC2, 0x02, 0x00, //JNZ 0x0002
+///   0x76,       //HLT
+/// ];
+/// let mut io = NullIo::new(&program);
+/// let mut cpu = I8080::new();
+/// assert!(cpu::run_until_pc(&mut cpu, &mut io, 0x0006, 1000));
+/// assert_eq!(cpu.state().a, 0);
+///
+/// //A NOP sled never comes back to 0
+/// assert!(!cpu::run_until_pc(&mut cpu, &mut io, 0x0000, 1000));
+/// ```
+pub fn run_until_pc<IO>(chip: &mut impl Chip<IO>, io: &mut IO, target: u16, max_cycles: u64) -> bool {
+  let mut cycles = 0;
+  while chip.pc(io) != target {
+    if cycles >= max_cycles {
+      return false;
+    }
+    cycles += chip.run_instruction(io) as u64;
+  }
+  true
+}
+
+/// What a CPU core does when it reads an opcode which isn't in its instruction set, such as from a bad ROM dump
+#[derive(Debug, Default, Clone, Copy)]
+pub enum InvalidPolicy {
+  /// Crash, so the bug gets noticed. Best for tests.
+  #[default]
+  Panic,
+  /// Skip over it as if it were a NOP
+  Nop,
+  /// Pass the opcode to a function, such as a logger, then skip over it as if it were a NOP
+  Callback(fn(u8)),
+}
+
+impl InvalidPolicy {
+  /// Apply the policy to `opcode`
+  pub fn handle(self, opcode: u8) {
+    match self {
+      InvalidPolicy::Panic => panic!("Unknown opcode: 0x{:02X}", opcode),
+      InvalidPolicy::Nop => (),
+      InvalidPolicy::Callback(callback) => callback(opcode),
+    }
+  }
+}
+
+/// ### Inert IO
+///
+/// Memory reads come from a fixed byte slice, and read as 0 past its end. Memory writes and port IO do nothing.
+///
+/// Useful for running a CPU in isolation, without building a board.
+///
+/// ### Example
+/// ```
+/// use chips::cpu::{I8080, NullIo};
+///
+/// let mut io = NullIo::new(&[0x00]); //NOP
+/// let mut cpu = I8080::new();
+/// cpu.run_cycle(&mut io);
+/// assert_eq!(cpu.state().pc, 1);
+/// ```
+pub struct NullIo<'a> {
+  /// Memory contents, starting at address 0
+  memory: &'a [u8],
+}
+
+impl<'a> NullIo<'a> {
+  /// Create IO which reads from `memory`
+  #[inline]
+  pub fn new(memory: &'a [u8]) -> Self {
+    Self {
+      memory,
+    }
+  }
+}
+
+impl MemoryIO<u16> for NullIo<'_> {
+  fn read_mem<T: crate::ReadArr>(&self, address: u16) -> T {
+    let mut bytes = [0; 8];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+      *byte = self.memory.get(address.wrapping_add(index as u16) as usize).copied().unwrap_or(0);
+    }
+    T::read(&bytes)
+  }
+  #[inline]
+  fn write_mem<T: crate::WriteArr>(&mut self, _address: u16, _value: T) {}
+}
+
 /// Generic CPU chip
 ///
 /// Keeps track of current instruction position and stack position
@@ -44,36 +250,213 @@ impl<ADDRESS: core::default::Default + core::ops::AddAssign<ADDRESS> + core::ops
     ret
   }
   
+  /// ### Read with Wait States
+  ///
+  /// Same as `next_code_byte`, for memory too slow to answer within one clock cycle. `T` is the timing of the memory, and `clock_ns` the nanoseconds per clock cycle. Returns the byte, and the wait cycles the CPU was held for, to add to the instruction's total.
+  ///
+  /// Panics if `clock_ns` is 0.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::cpu::{CPU, NullIo};
+  /// use chips::ram::MK4027Timing;
+  ///
+  /// let io = NullIo::new(&[0x42, 0x43]);
+  /// let mut cpu: CPU<u16> = CPU::new();
+  /// //At 2 MHz there are 500ns per clock cycle, plenty for the MK4027
+  /// assert_eq!(cpu.next_code_byte_wait::<MK4027Timing>(&io, 500), (0x42, 0));
+  /// //At 10 MHz it needs another cycle
+  /// assert_eq!(cpu.next_code_byte_wait::<MK4027Timing>(&io, 100), (0x43, 1));
+  /// assert_eq!(cpu.pc, 2);
+  /// ```
+  #[inline]
+  pub fn next_code_byte_wait<T: crate::ram::AccessTiming>(&mut self, io: &impl MemoryIO<ADDRESS>, clock_ns: u32) -> (u8, u32) {
+    (self.next_code_byte(io), T::wait_cycles(clock_ns))
+  }
+
   /// Read next ROM word and move over program counter
   #[inline]
   pub fn next_code_word(&mut self, io: &impl MemoryIO<ADDRESS>) -> u16 {
-    let ret = io.read_mem(self.pc);
-    self.pc += ADDRESS::from(2);
-    ret
+    //Read byte by byte, so that memories which only handle byte reads work too.
+    let low = self.next_code_byte(io);
+    let high = self.next_code_byte(io);
+    u16::from_le_bytes([low, high])
   }
   
-  /// Push data to stack and update stack pointer
+  /// Push data to stack and update stack pointer. The stack pointer moves by the size of the data.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::cpu::{CPU, NullIo};
+  ///
+  /// let mut io = NullIo::new(&[]);
+  /// let mut cpu: CPU<u16> = CPU::new();
+  /// cpu.sp = 0x100;
+  /// cpu.push(&mut io, 0x12_u8);
+  /// assert_eq!(cpu.sp, 0xFF);
+  /// cpu.push(&mut io, 0x1234_u16);
+  /// assert_eq!(cpu.sp, 0xFD);
+  /// let _: u16 = cpu.pop(&io);
+  /// assert_eq!(cpu.sp, 0xFF);
+  /// let _: u8 = cpu.pop(&io);
+  /// assert_eq!(cpu.sp, 0x100);
+  /// ```
   #[inline]
   pub fn push<T: crate::WriteArr>(&mut self, io: &mut impl MemoryIO<ADDRESS>, value: T) {
-    self.sp -= ADDRESS::from(2);
+    self.sp -= ADDRESS::from(core::mem::size_of::<T>() as u8);
     io.write_mem(self.sp, value);
   }
   
-  /// Pop data from stack and update stack pointer
+  /// Pop data from stack and update stack pointer. The stack pointer moves by the size of the data.
   #[inline]
   pub fn pop<T: crate::ReadArr>(&mut self, io: &impl MemoryIO<ADDRESS>) -> T {
     let value = io.read_mem(self.sp);
-    self.sp += ADDRESS::from(2);
+    self.sp += ADDRESS::from(core::mem::size_of::<T>() as u8);
     value
   }
   
 }
 
 
+/// ### Circular Hardware Stack
+///
+/// Early CPUs like the 4004 keep return addresses in a handful of on-chip registers instead of RAM. Pushing past `DEPTH` wraps around and overwrites the oldest entry.
+///
+/// Returning without a matching call is ignored. This is the 4004's undocumented behavior, tracked by a push count. Returning more times than `DEPTH` after an overflow keeps looping around the same entries.
+///
+/// ### Example
+/// ```
+/// use chips::cpu::HardwareStack;
+///
+/// let mut stack: HardwareStack<u16, 2> = HardwareStack::new();
+/// assert_eq!(stack.pop(), None); //Nothing was pushed
+/// stack.push(0x10);
+/// stack.push(0x20);
+/// stack.push(0x30); //Overwrites 0x10
+/// assert_eq!(stack.pop(), Some(0x30));
+/// assert_eq!(stack.pop(), Some(0x20));
+/// assert_eq!(stack.pop(), Some(0x30)); //Looped around
+/// assert_eq!(stack.pop(), None);
+/// ```
+#[derive(Clone, Copy, PartialEq)]
+pub struct HardwareStack<T, const DEPTH: usize> {
+  /// Stack registers
+  pub(crate) entries: [T; DEPTH],
+  /// Which entry the next push writes to
+  pub(crate) index: usize,
+  /// Pushes not yet popped. Undocumented feature
+  pub(crate) push_count: u8,
+}
+
+impl<T: Default + Copy, const DEPTH: usize> Default for HardwareStack<T, DEPTH> {
+  #[inline]
+  fn default() -> Self {
+    Self {
+      entries: [T::default(); DEPTH],
+      index: 0,
+      push_count: 0,
+    }
+  }
+}
+
+impl<T: Default + Copy, const DEPTH: usize> HardwareStack<T, DEPTH> {
+  /// Create an empty stack
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Push a value, overwriting the oldest entry when full
+  #[inline]
+  pub fn push(&mut self, value: T) {
+    self.entries[self.index] = value;
+    self.index = (self.index + 1) % DEPTH;  //Loops around
+    self.push_count = self.push_count.saturating_add(1);
+  }
+
+  /// Pop the last pushed value. Returns None if there were no pushes to return from.
+  #[inline]
+  pub fn pop(&mut self) -> Option<T> {
+    if self.push_count > 0 {
+      self.push_count -= 1;
+      self.index = (self.index + DEPTH - 1) % DEPTH;  //Loops back around
+      Some(self.entries[self.index])
+    } else {
+      None
+    }
+  }
+
+  /// Look up how many entries is the generic const DEPTH set to.
+  pub const DEPTH: usize = DEPTH;
+}
+
+
+/// Split a word into bytes in big endian order, as the F8 keeps its 16 bit registers
+///
+/// Returns (upper, lower)
+///
+/// ### Example
+/// ```
+/// use chips::cpu;
+/// assert_eq!(cpu::split_u16_be(0x1234), (0x12, 0x34));
+/// assert_eq!(cpu::join_u16_be(0x12, 0x34), 0x1234);
+/// ```
+#[inline]
+pub fn split_u16_be(value: u16) -> (u8, u8) {
+  let [upper, lower] = value.to_be_bytes();
+  (upper, lower)
+}
+
+/// Join big endian bytes, (upper, lower), back into a word
+#[inline]
+pub fn join_u16_be(upper: u8, lower: u8) -> u16 {
+  u16::from_be_bytes([upper, lower])
+}
+
+/// Split a word into bytes in little endian order, as the 8080 keeps words in memory
+///
+/// Returns (lower, upper)
+///
+/// ### Example
+/// ```
+/// use chips::cpu;
+/// assert_eq!(cpu::split_u16_le(0x1234), (0x34, 0x12));
+/// assert_eq!(cpu::join_u16_le(0x34, 0x12), 0x1234);
+/// ```
+#[inline]
+pub fn split_u16_le(value: u16) -> (u8, u8) {
+  let [lower, upper] = value.to_le_bytes();
+  (lower, upper)
+}
+
+/// Join little endian bytes, (lower, upper), back into a word
+#[inline]
+pub fn join_u16_le(lower: u8, upper: u8) -> u16 {
+  u16::from_le_bytes([lower, upper])
+}
+
+/// Sign, zero and parity flags of a result, as used by the 8080 family
+///
+/// Returns (sign, zero, parity even)
+///
+/// ### Example
+/// ```
+/// use chips::cpu;
+/// assert_eq!(cpu::result_flags(0x00), (false, true, true));
+/// assert_eq!(cpu::result_flags(0x80), (true, false, false));
+/// assert_eq!(cpu::result_flags(0x03), (false, false, true));
+/// ```
+#[inline]
+pub fn result_flags(byte: u8) -> (bool, bool, bool) {
+  (byte & 0b1000_0000 == 0b1000_0000, byte == 0, byte.count_ones() % 2 == 0)
+}
+
 /// Add two bytes
 ///
 /// Returns (result, carry, nibble carry)
 ///
+/// Nibble carry is the carry out of bit 3.
+///
 /// ### Example
 /// ```
 /// use chips::cpu;
@@ -84,7 +467,7 @@ impl<ADDRESS: core::default::Default + core::ops::AddAssign<ADDRESS> + core::ops
 /// ```
 pub fn execute_add(byte1: u8, byte2: u8) -> (u8, bool, bool) {
   let (result, carry) = byte1.overflowing_add(byte2);
-  let nibble_carry = (byte1 & 0xF) + (byte2 & 0xF) > 9;
+  let nibble_carry = (byte1 & 0xF) + (byte2 & 0xF) > 0xF;
   (result, carry, nibble_carry)
 }
 
@@ -100,41 +483,120 @@ pub fn execute_add(byte1: u8, byte2: u8) -> (u8, bool, bool) {
 pub fn execute_add_carry(byte1: u8, byte2: u8, carry: bool) -> (u8, bool, bool) {
   let (result, carry1) = byte1.overflowing_add(carry as u8);
   let (result, carry2) = result.overflowing_add(byte2);
-  let nibble_carry = (byte1 & 0xF) + (byte2 & 0xF) + ((carry as u8) & 0xF) > 9;
+  let nibble_carry = (byte1 & 0xF) + (byte2 & 0xF) + (carry as u8) > 0xF;
   (result, carry1 | carry2, nibble_carry)
 }
 
 
+/// Add two bytes + carry, also tracking signed overflow, as the 6502 and 6800 do
+///
+/// Returns (result, carry, nibble carry, overflow)
+///
+/// Overflow is set when both bytes have the same sign, but the result has a different sign.
+///
+/// ### Example
+/// ```
+/// use chips::cpu;
+/// assert_eq!(cpu::execute_add_v(0x50, 0x50, false), (0xA0, false, false, true));  //80+80 is not -96
+/// assert_eq!(cpu::execute_add_v(0xD0, 0x90, false), (0x60, true, false, true));   //-48+-112 is not 96
+/// assert_eq!(cpu::execute_add_v(0x50, 0xF0, false), (0x40, true, false, false));  //80+-16=64
+/// assert_eq!(cpu::execute_add_v(0x0F, 0x00, true), (0x10, false, true, false));
+/// ```
+pub fn execute_add_v(byte1: u8, byte2: u8, carry: bool) -> (u8, bool, bool, bool) {
+  let (result, carry, nibble_carry) = execute_add_carry(byte1, byte2, carry);
+  let overflow = (byte1 ^ result) & (byte2 ^ result) & 0x80 != 0;
+  (result, carry, nibble_carry, overflow)
+}
+
 /// Subtract two bytes
 ///
+/// Returns (result, borrow, nibble carry)
+///
+/// The subtraction is done like the 8080 does it, by adding the two's complement. So nibble carry is set when the low nibble did *not* need to borrow.
+///
 /// ### Example
 /// ```
 /// use chips::cpu;
-/// assert_eq!(cpu::execute_sub(0x5, 0x4), (0x1, false, false));
+/// assert_eq!(cpu::execute_sub(0x5, 0x4), (0x1, false, true));
+/// assert_eq!(cpu::execute_sub(0x10, 0x1), (0xF, false, false)); //borrowed from the high nibble
+/// assert_eq!(cpu::execute_sub(0x1, 0x2), (0xFF, true, false));
 /// ```
 pub fn execute_sub(byte1: u8, byte2: u8) -> (u8, bool, bool) {
   let (result, carry) = byte1.overflowing_sub(byte2);
-  let nibble_carry = (result & 0xF) + (byte2 & 0xF) > 9;
+  let nibble_carry = (byte1 & 0xF) >= (byte2 & 0xF);
   (result, carry, nibble_carry)
 }
 
 /// Subtract two bytes - carry
 ///
-/// Returns (result, carry, nibble carry)
+/// Returns (result, borrow, nibble carry)
+///
+/// As with `execute_sub`, nibble carry is set when the low nibble did not need to borrow.
 ///
 /// ### Example
 /// ```
 /// use chips::cpu;
-/// assert_eq!(cpu::execute_sub_carry(0x5, 0x4, true), (0x0, false, false));
+/// assert_eq!(cpu::execute_sub_carry(0x5, 0x4, true), (0x0, false, true));
+/// assert_eq!(cpu::execute_sub_carry(0x5, 0x5, true), (0xFF, true, false));
 /// ```
 pub fn execute_sub_carry(byte1: u8, byte2: u8, carry: bool) -> (u8, bool, bool) {
   let (result, carry1) = byte1.overflowing_sub(carry as u8);
   let (result, carry2) = result.overflowing_sub(byte2);
-  let nibble_carry = (result & 0xF) + (byte2 & 0xF) + ((carry as u8) & 0xF) > 9;
+  let nibble_carry = (byte1 & 0xF) >= (byte2 & 0xF) + (carry as u8);
   (result, carry1 | carry2, nibble_carry)
 }
 
 
+/// Add two packed BCD bytes (2 decimal digits each) + carry
+///
+/// Returns (result, carry)
+///
+/// ### Example
+/// ```
+/// use chips::cpu;
+/// assert_eq!(cpu::bcd_add(0x38, 0x45, false), (0x83, false));
+/// assert_eq!(cpu::bcd_add(0x38, 0x41, false), (0x79, false));
+/// assert_eq!(cpu::bcd_add(0x83, 0x54, false), (0x37, true));
+/// assert_eq!(cpu::bcd_add(0x09, 0x08, false), (0x17, false));
+/// assert_eq!(cpu::bcd_add(0x99, 0x00, true), (0x00, true));
+///
+/// //Same as a binary add followed by a decimal adjust
+/// let (num, carry, nibble_carry) = cpu::execute_add(0x38, 0x45);
+/// let (daa, daa_carry, _) = cpu::execute_daa(num, carry, nibble_carry);
+/// assert_eq!(cpu::bcd_add(0x38, 0x45, false), (daa, daa_carry));
+/// ```
+pub fn bcd_add(byte1: u8, byte2: u8, carry: bool) -> (u8, bool) {
+  let add_digit = |digit1: u8, digit2: u8, carry: bool| {
+    let sum = digit1 + digit2 + carry as u8;
+    if sum >= 10 { (sum - 10, true) } else { (sum, false) }
+  };
+  let (low, carry) = add_digit(byte1 & 0xF, byte2 & 0xF, carry);
+  let (high, carry) = add_digit(byte1 >> 4, byte2 >> 4, carry);
+  (((high & 0xF) << 4) | (low & 0xF), carry)
+}
+
+/// Subtract two packed BCD bytes (2 decimal digits each) - borrow
+///
+/// Returns (result, borrow)
+///
+/// ### Example
+/// ```
+/// use chips::cpu;
+/// assert_eq!(cpu::bcd_sub(0x83, 0x45, false), (0x38, false));
+/// assert_eq!(cpu::bcd_sub(0x37, 0x54, false), (0x83, true));  //137-54=83
+/// assert_eq!(cpu::bcd_sub(0x17, 0x08, false), (0x09, false));
+/// assert_eq!(cpu::bcd_sub(0x00, 0x00, true), (0x99, true));
+/// ```
+pub fn bcd_sub(byte1: u8, byte2: u8, borrow: bool) -> (u8, bool) {
+  let sub_digit = |digit1: u8, digit2: u8, borrow: bool| {
+    let difference = digit1 as i8 - digit2 as i8 - borrow as i8;
+    if difference < 0 { ((difference + 10) as u8, true) } else { (difference as u8, false) }
+  };
+  let (low, borrow) = sub_digit(byte1 & 0xF, byte2 & 0xF, borrow);
+  let (high, borrow) = sub_digit(byte1 >> 4, byte2 >> 4, borrow);
+  (((high & 0xF) << 4) | (low & 0xF), borrow)
+}
+
 /// Decimal Adjust after Addition adjusts numbers to look decimal in hexadecimal.
 ///
 /// ### Example
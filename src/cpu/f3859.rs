@@ -32,16 +32,6 @@ struct F3850IO<'a> {
   rom: &'a mut rom::F3851,
 }
 
-/// Outputs (upper, lower)
-fn u16_to_u8(source: u16) -> (u8, u8) {
-  let bytes = source.to_be_bytes();
-  (bytes[0], bytes[1])
-}
-
-fn u8_to_u16(upper: u8, lower: u8) -> u16 {
-  u16::from_be_bytes([upper, lower])
-}
-
 
 impl cpu::f3850::IO for F3850IO<'_> {
   fn output(&mut self, _port: u8, _value: u8) {
@@ -84,7 +74,7 @@ impl cpu::f3850::IO for F3850IO<'_> {
 
   /// Jump to direct address. push_pc will back up the current position, so you can return to it later. (Call vs Jump)
   fn jump(&mut self, upper: u8, lower: u8, push_pc: bool) {
-    self.rom.jump(u8_to_u16(upper, lower), push_pc);
+    self.rom.jump(cpu::join_u16_be(upper, lower), push_pc);
   }
   /// Jump to relative address.
   fn jump_relative(&mut self, relative_addr: i8) {
@@ -101,11 +91,11 @@ impl cpu::f3850::IO for F3850IO<'_> {
   }
   /// Get dc0 pointer, returns upper, lower
   fn get_dc0(&mut self) -> (u8, u8) {
-    u16_to_u8(self.rom.dc0)
+    cpu::split_u16_be(self.rom.dc0)
   }
   /// Set dc0 pointer
   fn set_dc0(&mut self, upper: u8, lower: u8) {
-    self.rom.dc0 = u8_to_u16(upper, lower);
+    self.rom.dc0 = cpu::join_u16_be(upper, lower);
   }
   /// Swap DC pointers
   fn swap_dc(&mut self) {
@@ -114,11 +104,11 @@ impl cpu::f3850::IO for F3850IO<'_> {
   
   /// Get pc1 pointer, returns upper, lower
   fn get_pc1(&mut self) -> (u8, u8) {
-    u16_to_u8(self.rom.pc1)
+    cpu::split_u16_be(self.rom.pc1)
   }
   /// Set pc1 pointer
   fn set_pc1(&mut self, upper: u8, lower: u8) {
-    self.rom.pc1 = u8_to_u16(upper, lower);
+    self.rom.pc1 = cpu::join_u16_be(upper, lower);
   }
   
 }
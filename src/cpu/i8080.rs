@@ -10,6 +10,8 @@ use bitbybit::bitfield;
 use log::{trace,debug};
 use arbitrary_int::{u2,u3};
 use crate::cpu;
+use crate::RAM;
+use alloc::{format, string::{String, ToString}};
 
 /// Used to communicate with board
 pub trait IO: crate::cpu::MemoryIO<u16> {
@@ -19,6 +21,26 @@ pub trait IO: crate::cpu::MemoryIO<u16> {
   fn input(&mut self, port: u8) -> u8;
 }
 
+impl<M: IO, F: FnMut(cpu::Access, u16, u8)> IO for cpu::TracingMemory<M, F> {
+  #[inline]
+  fn output(&mut self, port: u8, value: u8) {
+    self.inner.output(port, value);
+  }
+  #[inline]
+  fn input(&mut self, port: u8) -> u8 {
+    self.inner.input(port)
+  }
+}
+
+impl IO for cpu::NullIo<'_> {
+  #[inline]
+  fn output(&mut self, _port: u8, _value: u8) {}
+  #[inline]
+  fn input(&mut self, _port: u8) -> u8 {
+    0
+  }
+}
+
 /// Program Status Word
 #[bitfield(u16, default: 0)]
 struct PSW {
@@ -69,6 +91,399 @@ struct Registers {
   hl: Word,
 }
 
+/// Load a CP/M `.COM` program into memory, ready to run from address 0.
+///
+/// COM programs start at 0x100. The zero page below it is cleared, then address 0 gets a JMP to the program, and the BDOS entry at 5 gets a RET, so that BDOS calls return straight away. The rest of memory is left as it is.
+///
+/// Panics if the program is larger than 0xFF00 bytes, as it would not fit above the zero page.
+///
+/// ### Example
+/// ```
+/// use chips::{cpu::i8080, RAM};
+///
+/// let mut ram: RAM<0x1_0000> = RAM::new();
+/// ram.set_total([0xFF; 0x1_0000]);
+/// i8080::load_com(&mut ram, &[0x3E, 0x42]); //MVI A, 0x42
+/// assert_eq!(ram.read::<u8>(0), 0xC3); //JMP
+/// assert_eq!(ram.read::<u16>(1), 0x100);
+/// assert_eq!(ram.read::<u8>(5), 0xC9); //RET
+/// assert_eq!(ram.read::<u16>(6), 0); //Nothing else in the zero page
+/// assert_eq!(ram.read::<u16>(0x100), 0x423E);
+/// ```
+pub fn load_com(mem: &mut RAM<0x1_0000>, program: &[u8]) {
+  mem.data[..0x100].fill(0);
+  mem.write(0, 0xC3_u8);  //JMP 0x100
+  mem.write(1, 0x100_u16);
+  mem.write(5, 0xC9_u8);  //RET
+  mem.data[0x100..0x100 + program.len()].copy_from_slice(program);
+}
+
+/// ### CP/M BDOS Console Output
+///
+/// Wraps the board's IO, and catches calls to the BDOS entry at address 5, which CP/M programs use to print. Use it together with `load_com`.
+///
+/// Supported functions, selected by register C:
+/// * 2 - Print the character in E.
+/// * 9 - Print the string at DE, up to a `$`. Without one, at most 0x10000 bytes are printed.
+///
+/// Every printed byte is passed to the `output` callback. Other functions are ignored.
+pub struct Bdos<T: IO, F: FnMut(u8)> {
+  /// The wrapped board IO
+  pub io: T,
+  output: F,
+}
+
+impl<T: IO, F: FnMut(u8)> Bdos<T, F> {
+  /// Wrap the board's IO, sending console output to `output`
+  #[inline]
+  pub fn new(io: T, output: F) -> Self {
+    Self {
+      io,
+      output,
+    }
+  }
+
+  /// Run a cycle of the CPU. If it is about to enter the BDOS, handle the call first. Returns how many clock cycles it took.
+  pub fn run_cycle(&mut self, cpu: &mut I8080) -> u8 {
+    if cpu.cpu.pc == 0x0005 {
+      let state = cpu.state();
+      match state.c {
+        2 => (self.output)(state.e),
+        9 => {
+          //Without a `$`, stop once the whole address space has been printed
+          let start = u16::from_be_bytes([state.d, state.e]);
+          for offset in 0..=u16::MAX {
+            let byte: u8 = self.io.read_mem(start.wrapping_add(offset));
+            if byte == b'$' {
+              break;
+            }
+            (self.output)(byte);
+          }
+        },
+        _ => {},
+      }
+    }
+    cpu.run_cycle(&mut self.io)
+  }
+}
+
+/// Clock cycles of every opcode. Conditional calls and returns are listed with their cost when the condition is not met, and take 6 more when it is. Conditional jumps always take 10.
+const CYCLES: [u8; 256] = [
+//x0  x1  x2  x3  x4  x5  x6  x7  x8  x9  xA  xB  xC  xD  xE  xF
+   4, 10,  7,  5,  5,  5,  7,  4,  4, 10,  7,  5,  5,  5,  7,  4, //0x
+   4, 10,  7,  5,  5,  5,  7,  4,  4, 10,  7,  5,  5,  5,  7,  4, //1x
+   4, 10, 16,  5,  5,  5,  7,  4,  4, 10, 16,  5,  5,  5,  7,  4, //2x
+   4, 10, 13,  5, 10, 10, 10,  4,  4, 10, 13,  5,  5,  5,  7,  4, //3x
+   5,  5,  5,  5,  5,  5,  7,  5,  5,  5,  5,  5,  5,  5,  7,  5, //4x
+   5,  5,  5,  5,  5,  5,  7,  5,  5,  5,  5,  5,  5,  5,  7,  5, //5x
+   5,  5,  5,  5,  5,  5,  7,  5,  5,  5,  5,  5,  5,  5,  7,  5, //6x
+   7,  7,  7,  7,  7,  7,  7,  7,  5,  5,  5,  5,  5,  5,  7,  5, //7x
+   4,  4,  4,  4,  4,  4,  7,  4,  4,  4,  4,  4,  4,  4,  7,  4, //8x
+   4,  4,  4,  4,  4,  4,  7,  4,  4,  4,  4,  4,  4,  4,  7,  4, //9x
+   4,  4,  4,  4,  4,  4,  7,  4,  4,  4,  4,  4,  4,  4,  7,  4, //Ax
+   4,  4,  4,  4,  4,  4,  7,  4,  4,  4,  4,  4,  4,  4,  7,  4, //Bx
+   5, 10, 10, 10, 11, 11,  7, 11,  5, 10, 10, 10, 11, 17,  7, 11, //Cx
+   5, 10, 10, 10, 11, 11,  7, 11,  5, 10, 10, 10, 11, 17,  7, 11, //Dx
+   5, 10, 10, 18, 11, 11,  7, 11,  5,  5, 10,  4, 11, 17,  7, 11, //Ex
+   5, 10, 10,  4, 11, 11,  7, 11,  5,  5, 10,  4, 11, 17,  7, 11, //Fx
+];
+
+const REGS: [&str; 8] = ["B", "C", "D", "E", "H", "L", "M", "A"];
+const PAIRS: [&str; 4] = ["B", "D", "H", "SP"];
+const PAIRS_PUSH_POP: [&str; 4] = ["B", "D", "H", "PSW"];
+const CONDITIONS: [&str; 8] = ["NZ", "Z", "NC", "C", "PO", "PE", "P", "M"];
+
+/// Accumulator operations, in opcode order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AluOp {
+  /// Add
+  Add,
+  /// Add with carry
+  Adc,
+  /// Subtract
+  Sub,
+  /// Subtract with borrow
+  Sbb,
+  /// And
+  Ana,
+  /// Exclusive or
+  Xra,
+  /// Or
+  Ora,
+  /// Compare, subtracting without keeping the result
+  Cmp,
+}
+
+const ALU_OPS: [AluOp; 8] = [AluOp::Add, AluOp::Adc, AluOp::Sub, AluOp::Sbb, AluOp::Ana, AluOp::Xra, AluOp::Ora, AluOp::Cmp];
+
+/// ### Decoded Instruction
+///
+/// Registers are indexed in opcode order: B, C, D, E, H, L, M, A, where M is the byte in memory at HL.
+/// Register pairs are BC, DE, HL, SP, except for PUSH and POP, where the last is PSW.
+/// Conditions are NZ, Z, NC, C, PO, PE, P, M.
+///
+/// Undocumented opcodes decode as the instruction they run as. `Display` gives Intel mnemonics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+  /// No operation
+  Nop,
+  /// Load a pair with a word
+  Lxi(u2, u16),
+  /// Store the accumulator at the address in BC or DE
+  Stax(u2),
+  /// Store HL at an address
+  Shld(u16),
+  /// Store the accumulator at an address
+  Sta(u16),
+  /// Increment a pair
+  Inx(u2),
+  /// Increment a register
+  Inr(u3),
+  /// Decrement a register
+  Dcr(u3),
+  /// Load a register with a byte
+  Mvi(u3, u8),
+  /// Rotate the accumulator left
+  Rlc,
+  /// Rotate the accumulator right
+  Rrc,
+  /// Rotate the accumulator left through carry
+  Ral,
+  /// Rotate the accumulator right through carry
+  Rar,
+  /// Decimal adjust the accumulator
+  Daa,
+  /// Complement the accumulator
+  Cma,
+  /// Set carry
+  Stc,
+  /// Complement carry
+  Cmc,
+  /// Add a pair to HL
+  Dad(u2),
+  /// Load the accumulator from the address in BC or DE
+  Ldax(u2),
+  /// Load HL from an address
+  Lhld(u16),
+  /// Load the accumulator from an address
+  Lda(u16),
+  /// Decrement a pair
+  Dcx(u2),
+  /// Copy the second register into the first
+  Mov(u3, u3),
+  /// Halt
+  Hlt,
+  /// Accumulator operation with a register
+  Alu(AluOp, u3),
+  /// Accumulator operation with a byte
+  AluImmediate(AluOp, u8),
+  /// Return if the condition is met
+  Rcond(u3),
+  /// Return
+  Ret,
+  /// Pop a pair off the stack
+  Pop(u2),
+  /// Jump if the condition is met
+  Jcond(u3, u16),
+  /// Jump
+  Jmp(u16),
+  /// Output the accumulator to a port
+  Out(u8),
+  /// Input the accumulator from a port
+  In(u8),
+  /// Exchange HL with the top of the stack
+  Xthl,
+  /// Exchange HL with DE
+  Xchg,
+  /// Disable interrupts
+  Di,
+  /// Enable interrupts
+  Ei,
+  /// Call if the condition is met
+  Ccond(u3, u16),
+  /// Call
+  Call(u16),
+  /// Push a pair onto the stack
+  Push(u2),
+  /// Call the restart vector at index * 8
+  Rst(u3),
+  /// Jump to HL
+  Pchl,
+  /// Copy HL into SP
+  Sphl,
+}
+
+impl core::fmt::Display for Instruction {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    let reg = |index: u3| REGS[index.value() as usize];
+    let pair = |index: u2| PAIRS[index.value() as usize];
+    let condition = |index: u3| CONDITIONS[index.value() as usize];
+    match *self {
+      Instruction::Nop => write!(f, "NOP"),
+      Instruction::Lxi(p, word) => write!(f, "LXI {}, 0x{:04X}", pair(p), word),
+      Instruction::Stax(p) => write!(f, "STAX {}", pair(p)),
+      Instruction::Shld(address) => write!(f, "SHLD 0x{:04X}", address),
+      Instruction::Sta(address) => write!(f, "STA 0x{:04X}", address),
+      Instruction::Inx(p) => write!(f, "INX {}", pair(p)),
+      Instruction::Inr(r) => write!(f, "INR {}", reg(r)),
+      Instruction::Dcr(r) => write!(f, "DCR {}", reg(r)),
+      Instruction::Mvi(r, byte) => write!(f, "MVI {}, 0x{:02X}", reg(r), byte),
+      Instruction::Rlc => write!(f, "RLC"),
+      Instruction::Rrc => write!(f, "RRC"),
+      Instruction::Ral => write!(f, "RAL"),
+      Instruction::Rar => write!(f, "RAR"),
+      Instruction::Daa => write!(f, "DAA"),
+      Instruction::Cma => write!(f, "CMA"),
+      Instruction::Stc => write!(f, "STC"),
+      Instruction::Cmc => write!(f, "CMC"),
+      Instruction::Dad(p) => write!(f, "DAD {}", pair(p)),
+      Instruction::Ldax(p) => write!(f, "LDAX {}", pair(p)),
+      Instruction::Lhld(address) => write!(f, "LHLD 0x{:04X}", address),
+      Instruction::Lda(address) => write!(f, "LDA 0x{:04X}", address),
+      Instruction::Dcx(p) => write!(f, "DCX {}", pair(p)),
+      Instruction::Mov(to, from) => write!(f, "MOV {}, {}", reg(to), reg(from)),
+      Instruction::Hlt => write!(f, "HLT"),
+      Instruction::Alu(op, r) => write!(f, "{} {}", ["ADD", "ADC", "SUB", "SBB", "ANA", "XRA", "ORA", "CMP"][op as usize], reg(r)),
+      Instruction::AluImmediate(op, byte) => write!(f, "{} 0x{:02X}", ["ADI", "ACI", "SUI", "SBI", "ANI", "XRI", "ORI", "CPI"][op as usize], byte),
+      Instruction::Rcond(c) => write!(f, "R{}", condition(c)),
+      Instruction::Ret => write!(f, "RET"),
+      Instruction::Pop(p) => write!(f, "POP {}", PAIRS_PUSH_POP[p.value() as usize]),
+      Instruction::Jcond(c, address) => write!(f, "J{} 0x{:04X}", condition(c), address),
+      Instruction::Jmp(address) => write!(f, "JMP 0x{:04X}", address),
+      Instruction::Out(port) => write!(f, "OUT 0x{:02X}", port),
+      Instruction::In(port) => write!(f, "IN 0x{:02X}", port),
+      Instruction::Xthl => write!(f, "XTHL"),
+      Instruction::Xchg => write!(f, "XCHG"),
+      Instruction::Di => write!(f, "DI"),
+      Instruction::Ei => write!(f, "EI"),
+      Instruction::Ccond(c, address) => write!(f, "C{} 0x{:04X}", condition(c), address),
+      Instruction::Call(address) => write!(f, "CALL 0x{:04X}", address),
+      Instruction::Push(p) => write!(f, "PUSH {}", PAIRS_PUSH_POP[p.value() as usize]),
+      Instruction::Rst(index) => write!(f, "RST {}", index),
+      Instruction::Pchl => write!(f, "PCHL"),
+      Instruction::Sphl => write!(f, "SPHL"),
+    }
+  }
+}
+
+/// How many bytes the instruction starting with `opcode` takes, including the opcode
+pub fn instruction_length(opcode: u8) -> usize {
+  //Octal fields, as the opcode map is laid out in octal
+  let x = opcode >> 6;
+  let y = (opcode >> 3) & 0b111;
+  let z = opcode & 0b111;
+  match (x, z) {
+    (0, 6) | (3, 6) => 2,
+    (0, 1) if y & 1 == 0 => 3,
+    (0, 2) if y >= 4 => 3,
+    (3, 2) | (3, 4) => 3,
+    _ => match opcode {
+      0xC3 | 0xCB | 0xCD | 0xDD | 0xED | 0xFD => 3,
+      0xD3 | 0xDB => 2,
+      _ => 1,
+    },
+  }
+}
+
+/// ### Decode
+///
+/// Decodes the instruction at the start of `bytes`. Returns the instruction, and how many bytes it took.
+///
+/// Operand bytes past the end of `bytes` read as 0. Panics if `bytes` is empty.
+///
+/// ### Example
+/// ```
+/// use chips::cpu::i8080::{decode, AluOp, Instruction};
+/// use arbitrary_int::u3;
+///
+/// assert_eq!(decode(&[0x3E, 0x05]), (Instruction::Mvi(u3::new(7), 0x05), 2));
+/// assert_eq!(decode(&[0xFE, 0x10]), (Instruction::AluImmediate(AluOp::Cmp, 0x10), 2));
+/// assert_eq!(decode(&[0xC2, 0x34, 0x12]), (Instruction::Jcond(u3::new(0), 0x1234), 3));
+/// ```
+pub fn decode(bytes: &[u8]) -> (Instruction, usize) {
+  let opcode = bytes[0];
+  let x = opcode >> 6;
+  let y = (opcode >> 3) & 0b111;
+  let z = opcode & 0b111;
+  let byte = bytes.get(1).copied().unwrap_or(0);
+  let word = u16::from_le_bytes([byte, bytes.get(2).copied().unwrap_or(0)]);
+  let reg = u3::new(y);
+  let pair = u2::new(y >> 1);
+  let instruction = match (x, z) {
+    (0, 0) => Instruction::Nop,
+    (0, 1) if y & 1 == 0 => Instruction::Lxi(pair, word),
+    (0, 1) => Instruction::Dad(pair),
+    (0, 2) => match y {
+      0 | 2 => Instruction::Stax(pair),
+      1 | 3 => Instruction::Ldax(pair),
+      4 => Instruction::Shld(word),
+      5 => Instruction::Lhld(word),
+      6 => Instruction::Sta(word),
+      _ => Instruction::Lda(word),
+    },
+    (0, 3) if y & 1 == 0 => Instruction::Inx(pair),
+    (0, 3) => Instruction::Dcx(pair),
+    (0, 4) => Instruction::Inr(reg),
+    (0, 5) => Instruction::Dcr(reg),
+    (0, 6) => Instruction::Mvi(reg, byte),
+    (0, _) => [Instruction::Rlc, Instruction::Rrc, Instruction::Ral, Instruction::Rar,
+               Instruction::Daa, Instruction::Cma, Instruction::Stc, Instruction::Cmc][y as usize],
+    (1, _) if opcode == 0x76 => Instruction::Hlt,
+    (1, _) => Instruction::Mov(reg, u3::new(z)),
+    (2, _) => Instruction::Alu(ALU_OPS[y as usize], u3::new(z)),
+    (_, 0) => Instruction::Rcond(reg),
+    (_, 1) => match y {
+      0 | 2 | 4 | 6 => Instruction::Pop(pair),
+      5 => Instruction::Pchl,
+      7 => Instruction::Sphl,
+      _ => Instruction::Ret, //0xC9, 0xD9
+    },
+    (_, 2) => Instruction::Jcond(reg, word),
+    (_, 3) => match y {
+      2 => Instruction::Out(byte),
+      3 => Instruction::In(byte),
+      4 => Instruction::Xthl,
+      5 => Instruction::Xchg,
+      6 => Instruction::Di,
+      7 => Instruction::Ei,
+      _ => Instruction::Jmp(word), //0xC3, 0xCB
+    },
+    (_, 4) => Instruction::Ccond(reg, word),
+    (_, 5) if y & 1 == 0 => Instruction::Push(pair),
+    (_, 5) => Instruction::Call(word), //0xCD, 0xDD, 0xED, 0xFD
+    (_, 6) => Instruction::AluImmediate(ALU_OPS[y as usize], byte),
+    _ => Instruction::Rst(reg),
+  };
+  (instruction, instruction_length(opcode))
+}
+
+/// ### Disassemble
+///
+/// Decodes the instruction at the start of `bytes`, using Intel mnemonics. Returns the text, and how many bytes it took.
+///
+/// `address` is unused, as the 8080 has no relative jumps, but is kept to match the other disassemblers. Undocumented opcodes show as the instruction they run as. A truncated instruction shows up as a `DB` byte, and empty `bytes` as empty text taking 0 bytes.
+///
+/// ### Example
+/// ```
+/// use chips::cpu::i8080::disassemble;
+///
+/// assert_eq!(disassemble(&[0x3E, 0x05], 0), ("MVI A, 0x05".into(), 2));
+/// assert_eq!(disassemble(&[0xC3, 0x34, 0x12], 0), ("JMP 0x1234".into(), 3));
+/// assert_eq!(disassemble(&[0x70], 0), ("MOV M, B".into(), 1));
+/// assert_eq!(disassemble(&[0xC3, 0x34], 0), ("DB 0xC3".into(), 1));
+/// assert_eq!(disassemble(&[], 0), ("".into(), 0));
+/// ```
+pub fn disassemble(bytes: &[u8], _address: usize) -> (String, usize) {
+  let Some(&opcode) = bytes.first() else {
+    return (String::new(), 0);
+  };
+  if bytes.len() < instruction_length(opcode) {
+    return (format!("DB 0x{:02X}", opcode), 1);
+  }
+  let (instruction, length) = decode(bytes);
+  (instruction.to_string(), length)
+}
+
 /// Intel 8080 chip
 #[derive(Default)]
 pub struct I8080 {
@@ -80,6 +495,67 @@ pub struct I8080 {
   interrupts_enabled: bool,
 }
 
+/// Copy of all registers and flags of the 8080, for displaying without the `log` crate.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct I8080State {
+  /// Accumulator
+  pub a: u8,
+  /// B register
+  pub b: u8,
+  /// C register
+  pub c: u8,
+  /// D register
+  pub d: u8,
+  /// E register
+  pub e: u8,
+  /// H register
+  pub h: u8,
+  /// L register
+  pub l: u8,
+  /// Stack Pointer
+  pub sp: u16,
+  /// Program Counter
+  pub pc: u16,
+  /// Sign flag
+  pub sign: bool,
+  /// Zero flag
+  pub zero: bool,
+  /// Auxiliary Carry flag
+  pub aux: bool,
+  /// Parity flag
+  pub parity: bool,
+  /// Carry flag
+  pub carry: bool,
+  /// Interrupts Enabled
+  pub interrupts_enabled: bool,
+}
+
+impl<T: IO> cpu::Chip<T> for I8080 {
+  #[inline]
+  fn run_instruction(&mut self, io: &mut T) -> u32 {
+    self.run_cycle(io) as u32
+  }
+  #[inline]
+  fn pc(&self, _io: &T) -> u16 {
+    self.cpu.pc
+  }
+  #[inline]
+  fn reset(&mut self) {
+    I8080::reset(self);
+  }
+  #[inline]
+  fn name(&self) -> &'static str {
+    "Intel 8080"
+  }
+}
+
+impl core::fmt::Display for I8080State {
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    writeln!(f, "A: 0x{:02X} B: 0x{:02X} C: 0x{:02X} D: 0x{:02X} E: 0x{:02X} H: 0x{:02X} L: 0x{:02X} SP: 0x{:04X}", self.a, self.b, self.c, self.d, self.e, self.h, self.l, self.sp)?;
+    write!(f, "PC: 0x{:04X} Zero: {} Carry: {} Aux: {} Parity: {} Sign: {}", self.pc, self.zero, self.carry, self.aux, self.parity, self.sign)
+  }
+}
+
 impl I8080 {
   /// Create a new chip
   pub fn new() -> Self {
@@ -92,13 +568,26 @@ impl I8080 {
       interrupts_enabled: false,
     }
   }
-  
-  fn debug_reg(&self, reg_index: u3) -> &str {
-    match reg_index.value() {
-      0 => "B", 1 => "C", 2 => "D", 3 => "E",
-      4 => "H", 5 => "L", 6 => "[HL]", _ => "A",
-    }
+
+  /// Start running from `pc` instead of 0
+  #[inline]
+  pub fn with_entry(mut self, pc: u16) -> Self {
+    self.cpu.pc = pc;
+    self
+  }
+
+  /// Start with the stack pointer at `sp` instead of 0xFFFF
+  #[inline]
+  pub fn with_stack(mut self, sp: u16) -> Self {
+    self.cpu.sp = sp;
+    self
+  }
+
+  /// Reset button. Clears all registers and flags, jumps to 0, and puts the stack back at 0xFFFF.
+  pub fn reset(&mut self) {
+    *self = Self::new();
   }
+  
   fn read_reg(&self, io: &mut impl IO, reg_index: u3) -> u8 {
     match reg_index.value() {
       0 => self.regs.bc.high(), //B
@@ -124,11 +613,6 @@ impl I8080 {
     };
   }
 
-  fn debug_pair(&self, pair_index: u2) -> &str {
-    match pair_index.value() {
-      0 => "BC", 1 => "CD", 2 => "HL", _ => "SP",
-    }
-  }
   fn read_pair(&mut self, pair_index: u2) -> u16 {
     match pair_index.value() {
       0 => self.regs.bc.raw_value(),
@@ -147,11 +631,6 @@ impl I8080 {
   }
 
 
-  fn debug_pair_push_pop(&self, pair_index: u2) -> &str {
-    match pair_index.value() {
-      0 => "BC", 1 => "CD", 2 => "HL", _ => "PSW",
-    }
-  }
   fn read_pair_push(&mut self, pair_index: u2) -> u16 {
     match pair_index.value() {
       0 => self.regs.bc.raw_value(),
@@ -169,19 +648,6 @@ impl I8080 {
     };
   }
   
-  fn debug_condition(&self, condition: u3) -> &str {
-    match condition.value() {
-      0 => "NZ",  //Not Zero
-      1 => "Z",   //Zero
-      2 => "NC",  //Not Carry
-      3 => "C",   //Carry
-      4 => "PO",  //Odd
-      5 => "PE",  //Even
-      6 => "P",   //Plus
-      _ => "M",   //Minus
-    }
-  }
-  
   fn test_condition(&self, condition: u3) -> bool {
     match condition.value() {
       0 => !self.regs.psw.zero(),
@@ -201,358 +667,261 @@ impl I8080 {
     debug!("PC: 0x{:04X} Zero: {} Carry: {} Aux: {} Parity: {} Sign: {}", self.cpu.pc, self.regs.psw.zero(), self.regs.psw.carry(), self.regs.psw.aux(), self.regs.psw.parity(), self.regs.psw.sign());
   }
 
+  /// Read all registers and flags at once
+  pub fn state(&self) -> I8080State {
+    I8080State {
+      a: self.regs.psw.acc(),
+      b: self.regs.bc.high(),
+      c: self.regs.bc.low(),
+      d: self.regs.de.high(),
+      e: self.regs.de.low(),
+      h: self.regs.hl.high(),
+      l: self.regs.hl.low(),
+      sp: self.cpu.sp,
+      pc: self.cpu.pc,
+      sign: self.regs.psw.sign(),
+      zero: self.regs.psw.zero(),
+      aux: self.regs.psw.aux(),
+      parity: self.regs.psw.parity(),
+      carry: self.regs.psw.carry(),
+      interrupts_enabled: self.interrupts_enabled,
+    }
+  }
+
+  /// Set by EI, cleared by DI and by accepting an interrupt
+  #[inline]
+  pub fn interrupts_enabled(&self) -> bool {
+    self.interrupts_enabled
+  }
+
+  /// Interrupt request line. The interrupting device puts `RST index` on the data bus.
+  ///
+  /// Ignored while interrupts are disabled. Otherwise interrupts get disabled, just like the real chip, and the RST runs.
+  ///
+  /// Returns if the interrupt was accepted.
+  pub fn interrupt(&mut self, io: &mut impl IO, index: u3) -> bool {
+    if !self.interrupts_enabled {
+      return false;
+    }
+    trace!("Interrupt RST {}", index);
+    self.interrupts_enabled = false;
+    self.cpu.push(io, self.cpu.pc);
+    self.cpu.pc = (index.value() as u16) << 3;
+    true
+  }
+
   /// Executes single instruction:
   ///
   /// 1. Read the next byte from ROM
-  /// 2. Check top 2 bits to divide up decoding.
-  /// 3. Some decoders might swap the two nibbles for easier decoding.
-  /// 4. If multiple byte instruction found, read more bytes from ROM.
-  /// 5. Execute instruction.
-  pub fn run_cycle(&mut self, io: &mut impl IO) {
+  /// 2. If multiple byte instruction found, read more bytes from ROM.
+  /// 3. Decode it, the same way as `decode`.
+  /// 4. Execute instruction.
+  ///
+  /// Returns how many clock cycles it took.
+  #[inline]
+  pub fn run_cycle(&mut self, io: &mut impl IO) -> u8 {
+    self.run_opcode(io).1
+  }
+
+  /// Runs one instruction, returning the opcode fetched and how many clock cycles it took
+  fn run_opcode(&mut self, io: &mut impl IO) -> (u8, u8) {
     let opcode = self.cpu.next_code_byte(io);
-    //Looking at the opcode map, it makes sense to chop up the instruction set into 4 chunks..
-    match opcode >> 6 {
-      0 => self.decode0(io, opcode),
-      1 => {  //MOV
-        let from_index = u3::new(opcode & 0b111);
-        let to_index = u3::new((opcode >> 3) & 0b111);
-        trace!("Copy {} into {}", self.debug_reg(from_index), self.debug_reg(to_index));
-        let value = self.read_reg(io, from_index);
-        self.write_reg(io, to_index, value);
-      },
-      2 => self.decode2(io, opcode),
-      _ => self.decode3(io, opcode),
+    let mut bytes = [opcode, 0, 0];
+    match instruction_length(opcode) {
+      2 => bytes[1] = self.cpu.next_code_byte(io),
+      3 => bytes[1..].copy_from_slice(&self.cpu.next_code_word(io).to_le_bytes()),
+      _ => (),
     }
+    let (instruction, _) = decode(&bytes);
+    (opcode, CYCLES[opcode as usize] + self.execute(io, instruction))
   }
-  
-  fn decode0(&mut self, io: &mut impl IO, opcode: u8) {
-    let opcode = opcode.rotate_left(4); //Rotating makes decoding a lot easier..
-    match opcode {
-      0x00..=0x03 | 0x80..=0x83 => trace!("NOP"),
-      0x10..=0x13 => {  //LXI
-        let word = self.cpu.next_code_word(io);
-        let to_index = u2::new(opcode & 0b11);
-        trace!("Copy 0x{:04X} into {}", word, self.debug_pair(to_index));
-        self.write_pair(to_index, word);
-      },
-      0x20 => { trace!("Copy Acc into [BC]"); //STAX BC
-        let to_addr = self.regs.bc.raw_value();
-        let byte = self.regs.psw.acc();
-        io.write_mem(to_addr, byte);
-      },
-      0x21 => { trace!("Copy Acc into [DE]"); //STAX DE
-        let to_addr = self.regs.de.raw_value();
-        let byte = self.regs.psw.acc();
-        io.write_mem(to_addr, byte);
-      },
-      0x22 => { trace!("Copy HL into [word]"); //SHLD
-        let to_addr = self.cpu.next_code_word(io);
-        let value = self.regs.hl.raw_value();
-        io.write_mem(to_addr, value);
-      },
-      0x23 => { trace!("Copy Acc into [word]"); //STA
-        let to_addr = self.cpu.next_code_word(io);
-        let value = self.regs.psw.acc();
-        io.write_mem(to_addr, value);
-      },
-      0x30..=0x33 => {  //INX
-        let index = u2::new(opcode & 0b11);
-        trace!("Increment {}", self.debug_pair(index));
-        let value = self.read_pair(index);
-        self.write_pair(index, value.wrapping_add(1));
-      },
-      0x40..=0x43 | 0xC0..=0xC3 => {  //INR
-        let index = rotate_index(opcode);
-        trace!("Increment {}", self.debug_reg(index));
+
+  /// ### Step
+  ///
+  /// Runs one instruction, like `run_cycle`, and reports where it was and what it was.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::cpu::{I8080, NullIo, StepInfo};
+  ///
+  /// let mut io = NullIo::new(&[0x00, 0x3E, 0x05]); //NOP, MVI A, 5
+  /// let mut cpu = I8080::new();
+  /// cpu.step(&mut io);
+  /// assert_eq!(cpu.step(&mut io), StepInfo { pc_before: 1, opcode: 0x3E, cycles: 7 });
+  /// ```
+  pub fn step(&mut self, io: &mut impl IO) -> cpu::StepInfo {
+    let pc_before = self.cpu.pc;
+    let (opcode, cycles) = self.run_opcode(io);
+    cpu::StepInfo { pc_before, opcode, cycles: cycles as u32 }
+  }
+
+  /// Returns the extra clock cycles of a conditional call or return whose condition was met.
+  fn execute(&mut self, io: &mut impl IO, instruction: Instruction) -> u8 {
+    trace!("{}", instruction);
+    match instruction {
+      Instruction::Nop => (),
+      Instruction::Lxi(pair, word) => self.write_pair(pair, word),
+      Instruction::Stax(pair) => {
+        let to_addr = self.read_pair(pair);
+        io.write_mem(to_addr, self.regs.psw.acc());
+      },
+      Instruction::Shld(to_addr) => io.write_mem(to_addr, self.regs.hl.raw_value()),
+      Instruction::Sta(to_addr) => io.write_mem(to_addr, self.regs.psw.acc()),
+      Instruction::Inx(pair) => {
+        let value = self.read_pair(pair);
+        self.write_pair(pair, value.wrapping_add(1));
+      },
+      Instruction::Inr(index) => {
         let byte = self.read_reg(io, index);
         let (result, _, aux) = cpu::execute_add(byte, 1);
         self.write_reg(io, index, result);
         self.regs.psw = self.regs.psw.with_aux(aux);
         self.set_result_flags(result);
       },
-      0x50..=0x53 | 0xD0..=0xD3 => {  //DCR
-        let index = rotate_index(opcode);
-        trace!("Decrement {}", self.debug_reg(index));
+      Instruction::Dcr(index) => {
         let byte = self.read_reg(io, index);
         let (result, _, aux) = cpu::execute_sub(byte, 1);
         self.write_reg(io, index, result);
         self.regs.psw = self.regs.psw.with_aux(aux);
         self.set_result_flags(result);
       },
-      0x60..=0x63 | 0xE0..=0xE3 => {  //MVI
-        let byte = self.cpu.next_code_byte(io);
-        let to_index = rotate_index(opcode);
-        trace!("Copy 0x{:02X} into {}", byte, self.debug_reg(to_index));
-        self.write_reg(io, to_index, byte);
-      },
-      0x70 => { trace!("RLC");
+      Instruction::Mvi(index, byte) => self.write_reg(io, index, byte),
+      Instruction::Rlc => {
         let acc = self.regs.psw.acc().rotate_left(1);
         let carry = acc & 0b1 == 0b1;
         self.regs.psw = self.regs.psw.with_acc(acc).with_carry(carry);
       },
-      0x71 => { trace!("RAL");
+      Instruction::Rrc => {
+        let carry = self.regs.psw.acc() & 0b1 == 0b1;
+        let acc = self.regs.psw.acc().rotate_right(1);
+        self.regs.psw = self.regs.psw.with_acc(acc).with_carry(carry);
+      },
+      Instruction::Ral => {
         let new_carry = self.regs.psw.acc() >> 7 == 0b1;
         let acc = self.regs.psw.acc() << 1 | self.regs.psw.carry() as u8;
         self.regs.psw = self.regs.psw.with_acc(acc).with_carry(new_carry);
       },
-      0x72 => { trace!("DAA");
+      Instruction::Rar => {
+        let new_carry = self.regs.psw.acc() & 1 == 1;
+        let acc = self.regs.psw.acc() >> 1 | ((self.regs.psw.carry() as u8) << 7);
+        self.regs.psw = self.regs.psw.with_acc(acc).with_carry(new_carry);
+      },
+      Instruction::Daa => {
         let (acc, carry, aux) = cpu::execute_daa(self.regs.psw.acc(), self.regs.psw.carry(), self.regs.psw.aux());
         self.regs.psw = self.regs.psw.with_acc(acc).with_carry(carry).with_aux(aux);
         self.set_result_flags(acc);
       },
-      0x73 => { trace!("STC");
-        self.regs.psw = self.regs.psw.with_carry(true);
-      },
-      0x90..=0x93 => {  //HL += pair
-        let index = u2::new(opcode & 0b11);
-        trace!("DAD {}", self.debug_pair(index));
-        let (hl, carry) = self.regs.hl.raw_value().overflowing_add(self.read_pair(index));
+      Instruction::Cma => self.regs.psw = self.regs.psw.with_acc(!self.regs.psw.acc()),
+      Instruction::Stc => self.regs.psw = self.regs.psw.with_carry(true),
+      Instruction::Cmc => self.regs.psw = self.regs.psw.with_carry(!self.regs.psw.carry()),
+      Instruction::Dad(pair) => {  //HL += pair
+        let (hl, carry) = self.regs.hl.raw_value().overflowing_add(self.read_pair(pair));
         self.regs.hl = Word::new_with_raw_value(hl);
         self.regs.psw = self.regs.psw.with_carry(carry);
       },
-      0xA0 => { trace!("Copy [BC] into Acc"); //LDAX BC
-        let value = io.read_mem(self.regs.bc.raw_value());
-        self.regs.psw = self.regs.psw.with_acc(value);
-      },
-      0xA1 => { trace!("Copy [DE] into Acc"); //LDAX DE
-        let value = io.read_mem(self.regs.de.raw_value());
+      Instruction::Ldax(pair) => {
+        let value = io.read_mem(self.read_pair(pair));
         self.regs.psw = self.regs.psw.with_acc(value);
       },
-      0xA2 => { trace!("Copy [word] into HL"); //LHLD
-        let from_addr = self.cpu.next_code_word(io);
+      Instruction::Lhld(from_addr) => {
         let value = io.read_mem(from_addr);
         self.regs.hl = Word::new_with_raw_value(value);
       },
-      0xA3 => { trace!("Copy [word] into Acc"); //LDA
-        let from_addr = self.cpu.next_code_word(io);
+      Instruction::Lda(from_addr) => {
         let value = io.read_mem(from_addr);
         self.regs.psw = self.regs.psw.with_acc(value);
       },
-      0xB0..=0xB3 => {  //DCX
-        let index = u2::new(opcode & 0b11);
-        trace!("Decrement {}", self.debug_pair(index));
-        let value = self.read_pair(index);
-        self.write_pair(index, value.wrapping_sub(1));
+      Instruction::Dcx(pair) => {
+        let value = self.read_pair(pair);
+        self.write_pair(pair, value.wrapping_sub(1));
       },
-      0xF0 => { trace!("RRC");
-        let carry = self.regs.psw.acc() & 0b1 == 0b1;
-        let acc = self.regs.psw.acc().rotate_right(1);
-        self.regs.psw = self.regs.psw.with_acc(acc).with_carry(carry);
-      },
-      0xF1 => { trace!("RAR");
-        let new_carry = self.regs.psw.acc() & 1 == 1;
-        let acc = self.regs.psw.acc() >> 1 | ((self.regs.psw.carry() as u8) << 7);
-        self.regs.psw = self.regs.psw.with_acc(acc).with_carry(new_carry);
-      },
-      0xF2 => { trace!("CMA");
-        self.regs.psw = self.regs.psw.with_acc(!self.regs.psw.acc());
-      },
-      0xF3 | _ => { trace!("CMC");
-        self.regs.psw = self.regs.psw.with_carry(!self.regs.psw.carry());
-      },
-    };
-  }
-
-  fn decode2(&mut self, io: &mut impl IO, opcode: u8) {
-    let index = u3_index(opcode);
-    let byte = self.read_reg(io, index);
-    let (acc, carry, aux) = match opcode {
-      0x80..=0x87 => { trace!("ADD {}", self.debug_reg(index));
-        cpu::execute_add(self.regs.psw.acc(), byte)
-      },
-      0x88..=0x8F => { trace!("ADC {}", self.debug_reg(index));
-        cpu::execute_add_carry(self.regs.psw.acc(), byte, self.regs.psw.carry())
-      },
-      0x90..=0x97 => { trace!("SUB {}", self.debug_reg(index));
-        cpu::execute_sub(self.regs.psw.acc(), byte)
-      },
-      0x98..=0x9F => { trace!("SBB {}", self.debug_reg(index));
-        cpu::execute_sub_carry(self.regs.psw.acc(), byte, self.regs.psw.carry())
-      },
-      0xA0..=0xA7 => { trace!("ANA {}", self.debug_reg(index));
-        (self.regs.psw.acc() & byte, false, false)  //Logical expression will never carry..
-      },
-      0xA8..=0xAF => { trace!("XRA {}", self.debug_reg(index));
-        (self.regs.psw.acc() ^ byte, false, false)  //Logical expression will never carry..
+      Instruction::Mov(to_index, from_index) => {
+        let value = self.read_reg(io, from_index);
+        self.write_reg(io, to_index, value);
       },
-      0xB0..=0xB7 => { trace!("ORA {}", self.debug_reg(index));
-        (self.regs.psw.acc() | byte, false, false)  //Logical expression will never carry..
+      //Halting isn't modeled, so HLT runs as the MOV M, M it sits in place of
+      Instruction::Hlt => {
+        let m = u3::new(6);
+        let value = self.read_reg(io, m);
+        self.write_reg(io, m, value);
       },
-      0xB8..=0xBF | _ => { trace!("CMP {}", self.debug_reg(index));
-        cpu::execute_sub(self.regs.psw.acc(), byte)
+      Instruction::Alu(op, index) => {
+        let byte = self.read_reg(io, index);
+        self.execute_alu(op, byte);
       },
-    };
-    match opcode {
-      0xB8..=0xBF => (),  //Skip CMP. Don't write to accumulator if CMP.
-      _ => self.regs.psw = self.regs.psw.with_acc(acc),
-    };
-    self.regs.psw = self.regs.psw.with_carry(carry).with_aux(aux);
-    self.set_result_flags(acc);
-  }
-
-  fn decode3(&mut self, io: &mut impl IO, opcode: u8) {
-    let opcode = opcode.rotate_left(4); //Rotating makes decoding a lot easier..
-    match opcode {
-      0x0C..=0x0F | 0x8C..=0x8F => {
-        let index = rotate_index(opcode);
-        trace!("R{}", self.debug_condition(index));
-        if self.test_condition(index) {
-          self.cpu.pc = self.cpu.pop(io); //RET
-        }
+      Instruction::AluImmediate(op, byte) => self.execute_alu(op, byte),
+      Instruction::Rcond(condition) => if self.test_condition(condition) {
+        self.cpu.pc = self.cpu.pop(io);
+        return 6;
       },
-      0x1C..=0x1F => {
-        let to_index = u2::new(opcode & 0b11);
-        trace!("POP {}", self.debug_pair_push_pop(to_index));
+      Instruction::Ret => self.cpu.pc = self.cpu.pop(io),
+      Instruction::Pop(pair) => {
         let value = self.cpu.pop(io);
-        self.write_pair_pop(to_index, value);
+        self.write_pair_pop(pair, value);
       },
-      0x2C..=0x2F | 0xAC..=0xAF => {
-        let index = rotate_index(opcode);
-        let word = self.cpu.next_code_word(io);
-        trace!("J{} 0x{:04X}", self.debug_condition(index), word);
-        if self.test_condition(index) {
-          self.cpu.pc = word; //JMP
-        }
-      },
-      0x3C | 0xBC => {
-        let word = self.cpu.next_code_word(io);
-        trace!("JMP 0x{:04X}", word);
+      Instruction::Jcond(condition, word) => if self.test_condition(condition) {
         self.cpu.pc = word;
       },
-      0x3D => { let port = self.cpu.next_code_byte(io);
-        trace!("Output acc into port 0x{:02X}", port);
-        io.output(port, self.regs.psw.acc());
+      Instruction::Jmp(word) => self.cpu.pc = word,
+      Instruction::Out(port) => io.output(port, self.regs.psw.acc()),
+      Instruction::In(port) => {
+        let value = io.input(port);
+        self.regs.psw = self.regs.psw.with_acc(value);
       },
-      0x3E => { trace!("Xchange HL and [SP]"); //XTHL
+      Instruction::Xthl => {
         let value = io.read_mem(self.cpu.sp);
         io.write_mem(self.cpu.sp, self.regs.hl.raw_value());
         self.regs.hl = Word::new_with_raw_value(value);
       },
-      0x3F => { trace!("Disable Interrupts"); //DI
-        self.interrupts_enabled = false;
-      },
-      0x4C..=0x4F | 0xCC..=0xCF => {
-        let index = rotate_index(opcode);
-        let word = self.cpu.next_code_word(io);
-        trace!("C{} 0x{:04X}", self.debug_condition(index), word);
-        if self.test_condition(index) {
-          self.cpu.push(io, self.cpu.pc);
-          self.cpu.pc = word; //CALL
-        }
-      },
-      0x5C..=0x5F => {
-        let from_index = u2::new(opcode & 0b11);
-        trace!("PUSH {}", self.debug_pair_push_pop(from_index));
-        let value = self.read_pair_push(from_index);
-        self.cpu.push(io, value);
-      },
-      0x6C => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("ADI 0x{:02X}", byte);
-        let (result, carry, aux) = cpu::execute_add(self.regs.psw.acc(), byte);
-        self.regs.psw = self.regs.psw.with_acc(result).with_carry(carry).with_aux(aux);
-        self.set_result_flags(result);
-      },
-      0x6D => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("SUI 0x{:02X}", byte);
-        let (result, carry, aux) = cpu::execute_sub(self.regs.psw.acc(), byte);
-        self.regs.psw = self.regs.psw.with_acc(result).with_carry(carry).with_aux(aux);
-        self.set_result_flags(result);
-      },
-      0x6E => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("ANI 0x{:02X}", byte);
-        let result = self.regs.psw.acc() & byte;
-        self.regs.psw = self.regs.psw.with_acc(result).with_carry(false).with_aux(false);
-        self.set_result_flags(result);
-      },
-      0x6F => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("ORI 0x{:02X}", byte);
-        let result = self.regs.psw.acc() | byte;
-        self.regs.psw = self.regs.psw.with_acc(result).with_carry(false).with_aux(false);
-        self.set_result_flags(result);
-      },
-      0x7C..=0x7F | 0xFC..=0xFF => {  //RST weird call function. The program counter is set to the value 0000 0000 00nn n000.
-        let index = rotate_index(opcode);
-        trace!("RST {}", index);
+      Instruction::Xchg => core::mem::swap(&mut self.regs.hl, &mut self.regs.de),
+      Instruction::Di => self.interrupts_enabled = false,
+      Instruction::Ei => self.interrupts_enabled = true,
+      Instruction::Ccond(condition, word) => if self.test_condition(condition) {
         self.cpu.push(io, self.cpu.pc);
-        self.cpu.pc = (index.value() as u16) << 3;
-      },
-      0x9C..=0x9D => { trace!("RET");
-        self.cpu.pc = self.cpu.pop(io);
-      },
-      0x9E => { trace!("Copy HL into PC"); //PCHL
-        self.cpu.pc = self.regs.hl.raw_value();
-      },
-      0x9F => { trace!("Copy HL into SP"); //SPHL
-        self.cpu.sp = self.regs.hl.raw_value();
-      },
-      0xBD => {
-        let port = self.cpu.next_code_byte(io);
-        trace!("Input acc from port 0x{:02X}", port);
-        self.regs.psw = self.regs.psw.with_acc(io.input(port));
-      },
-      0xBE => { trace!("Xchange HL and DE");
-        core::mem::swap(&mut self.regs.hl, &mut self.regs.de);
-      },
-      0xBF => { trace!("Enable Interrupts");  //EI
-        self.interrupts_enabled = true;
+        self.cpu.pc = word;
+        return 6;
       },
-      0xDC..=0xDF => {
-        let word = self.cpu.next_code_word(io);
-        trace!("CALL 0x{:04X}", word);
+      Instruction::Call(word) => {
         self.cpu.push(io, self.cpu.pc);
         self.cpu.pc = word;
       },
-      0xEC => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("ACI 0x{:02X}", byte);
-        let (result, carry, aux) = cpu::execute_add_carry(self.regs.psw.acc(), byte, self.regs.psw.carry());
-        self.regs.psw = self.regs.psw.with_acc(result).with_carry(carry).with_aux(aux);
-        self.set_result_flags(result);
-      },
-      0xED => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("SBI 0x{:02X}", byte);
-        let (result, carry, aux) = cpu::execute_sub_carry(self.regs.psw.acc(), byte, self.regs.psw.carry());
-        self.regs.psw = self.regs.psw.with_acc(result).with_carry(carry).with_aux(aux);
-        self.set_result_flags(result);
-      },
-      0xEE => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("XRI 0x{:02X}", byte);
-        let result = self.regs.psw.acc() ^ byte;
-        self.regs.psw = self.regs.psw.with_acc(result).with_carry(false).with_aux(false);
-        self.set_result_flags(result);
+      Instruction::Push(pair) => {
+        let value = self.read_pair_push(pair);
+        self.cpu.push(io, value);
       },
-      0xEF | _ => {
-        let byte = self.cpu.next_code_byte(io);
-        trace!("CPI 0x{:02X}", byte);
-        let (result, carry, aux) = cpu::execute_sub(self.regs.psw.acc(), byte);
-        self.regs.psw = self.regs.psw.with_carry(carry).with_aux(aux);
-        self.set_result_flags(result);
+      Instruction::Rst(index) => {  //RST weird call function. The program counter is set to the value 0000 0000 00nn n000.
+        self.cpu.push(io, self.cpu.pc);
+        self.cpu.pc = (index.value() as u16) << 3;
       },
+      Instruction::Pchl => self.cpu.pc = self.regs.hl.raw_value(),
+      Instruction::Sphl => self.cpu.sp = self.regs.hl.raw_value(),
     };
+    0
+  }
+
+  fn execute_alu(&mut self, op: AluOp, byte: u8) {
+    let acc = self.regs.psw.acc();
+    let (result, carry, aux) = match op {
+      AluOp::Add => cpu::execute_add(acc, byte),
+      AluOp::Adc => cpu::execute_add_carry(acc, byte, self.regs.psw.carry()),
+      AluOp::Sub | AluOp::Cmp => cpu::execute_sub(acc, byte),
+      AluOp::Sbb => cpu::execute_sub_carry(acc, byte, self.regs.psw.carry()),
+      AluOp::Ana => (acc & byte, false, false),  //Logical expression will never carry..
+      AluOp::Xra => (acc ^ byte, false, false),
+      AluOp::Ora => (acc | byte, false, false),
+    };
+    if op != AluOp::Cmp {  //Don't write to accumulator if CMP.
+      self.regs.psw = self.regs.psw.with_acc(result);
+    }
+    self.regs.psw = self.regs.psw.with_carry(carry).with_aux(aux);
+    self.set_result_flags(result);
   }
   
   fn set_result_flags(&mut self, result: u8) {
+    let (sign, zero, parity) = cpu::result_flags(result);
     self.regs.psw = self.regs.psw
-                      .with_sign(result & 0b1000_0000 == 0b1000_0000)
-                      .with_zero(result == 0)
-                      .with_parity(result.count_ones() % 2 == 0);
+                      .with_sign(sign)
+                      .with_zero(zero)
+                      .with_parity(parity);
   }
 }
-
-/// A lot of opcodes store index this way
-fn u3_index(opcode: u8) -> u3 {
-  u3::new(opcode & 0b111)
-}
-
-/// After some examination, I found that a lot of the opcodes are written in this format:
-/// 0,2,4,6 or 1,3,5,7
-/// Rotating left converts all of the registers into standardized 1,2,3,4,5,6,7
-fn rotate_index(opcode: u8) -> u3 {
-  u3::new(opcode.rotate_left(1) & 0b111)
-}
@@ -9,34 +9,65 @@ use alloc::{vec, vec::Vec};
 /// I found however that a match lookup table is more efficient, using fewer instructions at the cost of a few bytes of memory.
 pub struct SegmentDecoder {
   lookup: Vec<u8>,
+  /// Reverse of `lookup`, indexed by segment bits, built once so `encode` doesn't scan
+  reverse: [Option<u4>; 256],
 }
 
 impl SegmentDecoder {
   
   /// 7 segment secoder with 6 and 9 not having a horizontal line
   pub fn new_s74x47() -> Self {
-    Self {
-      lookup: vec![
-        0b0_11_1_11_1, 0b0_00_0_11_0, 0b1_01_1_01_1, 0b1_00_1_11_1,
-        0b1_10_0_11_0, 0b1_10_1_10_1, 0b1_11_1_10_0, 0b0_00_0_11_1,
-        0b1_11_1_11_1, 0b1_10_0_11_1, 0b1_01_1_00_0, 0b1_00_1_10_0,
-        0b1_10_0_01_0, 0b1_10_1_00_1, 0b1_11_1_00_0, 0,
-      ]
-    }
+    Self::with_lookup(vec![
+      0b0_11_1_11_1, 0b0_00_0_11_0, 0b1_01_1_01_1, 0b1_00_1_11_1,
+      0b1_10_0_11_0, 0b1_10_1_10_1, 0b1_11_1_10_0, 0b0_00_0_11_1,
+      0b1_11_1_11_1, 0b1_10_0_11_1, 0b1_01_1_00_0, 0b1_00_1_10_0,
+      0b1_10_0_01_0, 0b1_10_1_00_1, 0b1_11_1_00_0, 0,
+    ])
   }
   
   /// 7 segment secoder with 6 and 9 having a horizontal line
   pub fn new_s74x247() -> Self {
+    Self::with_lookup(vec![
+      0b0_11_1_11_1, 0b0_00_0_11_0, 0b1_01_1_01_1, 0b1_00_1_11_1,
+      0b1_10_0_11_0, 0b1_10_1_10_1, 0b1_11_1_10_1, 0b0_00_0_11_1,
+      0b1_11_1_11_1, 0b1_10_1_11_1, 0b1_01_1_00_0, 0b1_00_1_10_0,
+      0b1_10_0_01_0, 0b1_10_1_00_1, 0b1_11_1_00_0, 0,
+    ])
+  }
+  
+  /// ### Custom Segment Decoder
+  ///
+  /// Build a decoder from a lookup table, one entry of segment bits for each of the 16 nibble values. Segment a is bit 0, through segment g in bit 6.
+  ///
+  /// ### Example
+  /// ```
+  /// use arbitrary_int::u4;
+  /// use chips::SegmentDecoder;
+  ///
+  /// //Only shows 0 and 1, everything else is a dash
+  /// let mut lookup = [0b1_00_0_00_0; 16];
+  /// lookup[0] = 0b0_11_1_11_1;
+  /// lookup[1] = 0b0_00_0_11_0;
+  /// let decoder = SegmentDecoder::from_table(lookup);
+  /// assert_eq!(decoder.decode(u4::new(1)), 0b0_00_0_11_0);
+  /// assert_eq!(decoder.decode(u4::new(7)), 0b1_00_0_00_0);
+  /// ```
+  pub fn from_table(lookup: [u8; 16]) -> Self {
+    Self::with_lookup(lookup.to_vec())
+  }
+
+  fn with_lookup(lookup: Vec<u8>) -> Self {
+    let mut reverse = [None; 256];
+    //Go backwards, so that if two values show the same segments, the lowest wins
+    for (value, &segments) in lookup.iter().enumerate().rev() {
+      reverse[segments as usize] = Some(u4::new(value as u8));
+    }
     Self {
-      lookup: vec![
-        0b0_11_1_11_1, 0b0_00_0_11_0, 0b1_01_1_01_1, 0b1_00_1_11_1,
-        0b1_10_0_11_0, 0b1_10_1_10_1, 0b1_11_1_10_1, 0b0_00_0_11_1,
-        0b1_11_1_11_1, 0b1_10_1_11_1, 0b1_01_1_00_0, 0b1_00_1_10_0,
-        0b1_10_0_01_0, 0b1_10_1_00_1, 0b1_11_1_00_0, 0,
-      ]
+      lookup,
+      reverse,
     }
   }
-  
+
   /// Convert u4 into 7 segment display bits
   #[inline]
   pub fn decode(&self, decimal: u4) -> u8 {
@@ -46,5 +77,20 @@ impl SegmentDecoder {
     }
     self.lookup[decimal_usize]
   }
-}
 
+  /// Convert 7 segment display bits back into the u4 which shows them. Returns `None` if no value shows those segments.
+  ///
+  /// ### Example
+  /// ```
+  /// use arbitrary_int::u4;
+  /// use chips::SegmentDecoder;
+  ///
+  /// let decoder = SegmentDecoder::new_s74x47();
+  /// assert_eq!(decoder.encode(0b1_01_1_01_1), Some(u4::new(2)));
+  /// assert_eq!(decoder.encode(0b1_11_1_11_0), None);
+  /// ```
+  #[inline]
+  pub fn encode(&self, segments: u8) -> Option<u4> {
+    self.reverse[segments as usize]
+  }
+}
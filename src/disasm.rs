@@ -0,0 +1,49 @@
+//! Disassembling whole ROMs, for any of the supported CPU cores
+
+use alloc::string::String;
+
+/// Which CPU core's instruction set to decode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arch {
+  /// Intel 8080
+  I8080,
+  /// Intel 4004
+  I4004,
+  /// Fairchild F3850
+  F3850,
+}
+
+impl Arch {
+  /// Decode a single instruction at the start of `bytes`, which sits at `address`. Returns the text, and how many bytes it took.
+  pub fn disassemble(self, bytes: &[u8], address: usize) -> (String, usize) {
+    match self {
+      Arch::I8080 => crate::cpu::i8080::disassemble(bytes, address),
+      Arch::I4004 => crate::mcs4::cpu4004::disassemble(bytes, address),
+      Arch::F3850 => crate::fairchild_f8::cpu3850::disassemble(bytes, address),
+    }
+  }
+}
+
+/// ### Disassemble a ROM
+///
+/// Walks `bytes` from the start, one instruction after another, giving the offset and text of each. Data mixed in with the code gets decoded as if it were instructions.
+///
+/// ### Example
+/// ```
+/// use chips::disasm::{disassemble_all, Arch};
+///
+/// let listing: Vec<_> = disassemble_all(&[0x3E, 0x05, 0x76], Arch::I8080).collect();
+/// assert_eq!(listing, [(0, "MVI A, 0x05".into()), (2, "HLT".into())]);
+/// ```
+pub fn disassemble_all(bytes: &[u8], arch: Arch) -> impl Iterator<Item = (usize, String)> + '_ {
+  let mut offset = 0;
+  core::iter::from_fn(move || {
+    if offset >= bytes.len() {
+      return None;
+    }
+    let (text, length) = arch.disassemble(&bytes[offset..], offset);
+    let item = (offset, text);
+    offset += length;
+    Some(item)
+  })
+}
@@ -0,0 +1,94 @@
+//! Display driver helpers
+
+use alloc::vec::Vec;
+use arbitrary_int::u4;
+
+/// ### Multiplexed 7 Segment Display
+///
+/// Calculators only light one digit at a time, strobing through the digits faster than the eye can see.
+///
+/// This latches the last segments written to each digit, so that a stable frame can be read at any time.
+///
+/// ### Example
+/// ```
+/// use chips::display::MultiplexedDisplay;
+///
+/// let mut display: MultiplexedDisplay<3> = MultiplexedDisplay::new();
+/// display.tick(0b0_00_0_11_0, 0b001);  //1
+/// display.tick(0b1_01_1_01_1, 0b010);  //2
+/// display.tick(0b1_00_1_11_1, 0b100);  //3
+/// assert_eq!(display.frame(), [0b0_00_0_11_0, 0b1_01_1_01_1, 0b1_00_1_11_1]);
+///
+/// //Strobing a digit again only changes that digit.
+/// display.tick(0, 0b010);
+/// assert_eq!(display.frame(), [0b0_00_0_11_0, 0, 0b1_00_1_11_1]);
+/// ```
+#[derive(Clone, Copy, PartialEq)]
+pub struct MultiplexedDisplay<const DIGITS: usize> {
+  /// Last segments written to each digit
+  digits: [u8; DIGITS],
+}
+
+impl<const DIGITS: usize> Default for MultiplexedDisplay<DIGITS> {
+  #[inline]
+  fn default() -> Self {
+    Self {
+      digits: [0; DIGITS],
+    }
+  }
+}
+
+impl<const DIGITS: usize> MultiplexedDisplay<DIGITS> {
+  /// Create a new blank display
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Write segments to the digits selected. Bit 0 of `digit_select` is digit 0.
+  ///
+  /// Normally only one digit is selected at a time, but if more are selected, they all latch the same segments.
+  #[inline]
+  pub fn tick(&mut self, segments: u8, digit_select: u32) {
+    for (index, digit) in self.digits.iter_mut().enumerate() {
+      if digit_select.checked_shr(index as u32).unwrap_or(0) & 1 == 1 {
+        *digit = segments;
+      }
+    }
+  }
+
+  /// Read the latched segments of every digit
+  #[inline]
+  pub fn frame(&self) -> [u8; DIGITS] {
+    self.digits
+  }
+
+  /// Look up how many digits is the generic const DIGITS set to.
+  pub const DIGITS: usize = DIGITS;
+}
+
+/// One frame of a machine's display, as polled by a front end
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayFrame {
+  /// Digit displays, one nibble per digit, leftmost digit first. 0xF is a blank digit, which `SegmentDecoder` shows with no segments lit.
+  Digits(Vec<u4>),
+  /// 7 segment displays showing more than digits, such as a minus sign or decimal point. One byte of lit segments per digit, leftmost digit first. Segment a is bit 0, through segment g in bit 6, and the decimal point is bit 7.
+  Segments(Vec<u8>),
+  /// Bitmap displays, one color index per pixel, row by row.
+  Pixels {
+    /// Pixels per row
+    width: usize,
+    /// Number of rows
+    height: usize,
+    /// Color index of each pixel
+    colors: Vec<u8>,
+  },
+}
+
+/// A machine with a display, so that one front end can draw any of them
+pub trait HasDisplay {
+  /// Whether the display is lit at all
+  fn display_on(&self) -> bool;
+  /// What the display is currently showing
+  fn pixels(&self) -> DisplayFrame;
+}
@@ -14,6 +14,10 @@
 
 use bitbybit::bitfield;
 use log::{trace,debug};
+use alloc::vec::Vec;
+use alloc::{format, string::String};
+use core::cell::Cell;
+use crate::snapshot::{self, Snapshot, SnapshotError};
 
 /// Used to communicate with board
 pub trait IO {
@@ -49,6 +53,12 @@ pub trait IO {
   /// Swap DC pointers
   fn swap_dc(&mut self);
   
+  /// Get pc0 pointer, the address of the next code byte, returns upper, lower
+  ///
+  /// Only `CPU::step` and `Chip::pc` need it, so it isn't required. Panics unless the board overrides it.
+  fn get_pc0(&self) -> (u8, u8) {
+    unimplemented!("this IO does not report pc0, implement IO::get_pc0 to use step or pc")
+  }
   /// ROMC07, ROMC0B - Get pc1 pointer, returns upper, lower
   fn get_pc1(&self) -> (u8, u8);
   /// Set pc1 pointer
@@ -56,7 +66,298 @@ pub trait IO {
   
 }
 
+/// ### Inert IO
+///
+/// Code and data reads come from a fixed byte slice, and read as 0 past its end. The program and data counters work like they would on a 3851 PSU, but writes and port IO do nothing.
+///
+/// Useful for running the CPU in isolation, without building a board.
+///
+/// ### Example
+/// ```
+/// use chips::fairchild_f8::cpu3850::{CPU, NullIo};
+///
+/// let mut io = NullIo::new(&[0x2B]); //NOP
+/// let mut cpu = CPU::new();
+/// assert_eq!(cpu.run_cycle(&mut io), 4);
+/// assert_eq!(io.pc0(), 1);
+/// ```
+pub struct NullIo<'a> {
+  /// Memory contents, starting at address 0
+  memory: &'a [u8],
+  pc0: u16,
+  pc1: u16,
+  dc0: u16,
+  dc1: u16,
+}
+
+impl<'a> NullIo<'a> {
+  /// Create IO which reads from `memory`
+  #[inline]
+  pub fn new(memory: &'a [u8]) -> Self {
+    Self {
+      memory,
+      pc0: 0,
+      pc1: 0,
+      dc0: 0,
+      dc1: 0,
+    }
+  }
+
+  /// Current program counter
+  #[inline]
+  pub fn pc0(&self) -> u16 {
+    self.pc0
+  }
+
+  #[inline]
+  fn read(&self, address: u16) -> u8 {
+    self.memory.get(address as usize).copied().unwrap_or(0)
+  }
+}
+
+impl IO for NullIo<'_> {
+  fn output(&mut self, _port: u8, _value: u8) {}
+  fn input(&self, _port: u8) -> u8 {
+    0
+  }
+  fn read_external_port(&self, _port: u8) -> u8 {
+    0
+  }
+
+  fn next_code(&mut self) -> u8 {
+    let byte = self.read(self.pc0);
+    self.pc0 = self.pc0.wrapping_add(1);
+    byte
+  }
+  fn peak_code(&self) -> i8 {
+    self.read(self.pc0) as i8
+  }
+  fn next_data(&mut self) -> u8 {
+    let byte = self.read(self.dc0);
+    self.dc0 = self.dc0.wrapping_add(1);
+    byte
+  }
+  fn write_data(&mut self, _data: u8) {
+    self.dc0 = self.dc0.wrapping_add(1);
+  }
+
+  fn jump(&mut self, upper: u8, lower: u8, push_pc: bool) {
+    if push_pc {
+      self.pc1 = self.pc0;
+    }
+    self.pc0 = u16::from_be_bytes([upper, lower]);
+  }
+  fn jump_relative(&mut self, relative_addr: i8) {
+    self.pc0 = self.pc0.wrapping_add(relative_addr as u16);
+  }
+  fn ret_pc(&mut self) {
+    self.pc0 = self.pc1;
+  }
+
+  fn add_dc0(&mut self, a: i8) {
+    self.dc0 = self.dc0.wrapping_add(a as u16);
+  }
+  fn get_dc0(&self) -> (u8, u8) {
+    let [upper, lower] = self.dc0.to_be_bytes();
+    (upper, lower)
+  }
+  fn set_dc0(&mut self, upper: u8, lower: u8) {
+    self.dc0 = u16::from_be_bytes([upper, lower]);
+  }
+  fn swap_dc(&mut self) {
+    core::mem::swap(&mut self.dc0, &mut self.dc1);
+  }
+
+  fn get_pc0(&self) -> (u8, u8) {
+    let [upper, lower] = self.pc0.to_be_bytes();
+    (upper, lower)
+  }
+  fn get_pc1(&self) -> (u8, u8) {
+    let [upper, lower] = self.pc1.to_be_bytes();
+    (upper, lower)
+  }
+  fn set_pc1(&mut self, upper: u8, lower: u8) {
+    self.pc1 = u16::from_be_bytes([upper, lower]);
+  }
+}
+
+/// One executed instruction, reported by `run_cycle_traced`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Insn {
+  /// Opcode byte
+  pub opcode: u8,
+  /// Base mnemonic. Registers and small immediates encoded in the opcode itself are not included.
+  pub mnemonic: &'static str,
+  /// Immediate bytes following the opcode. Only the first `operand_count` bytes are valid.
+  pub operands: [u8; 2],
+  /// How many operand bytes followed the opcode
+  pub operand_count: u8,
+  /// Clock pulses consumed
+  pub cycles: u8,
+}
+
+impl Insn {
+  /// Immediate bytes following the opcode
+  pub fn operands(&self) -> &[u8] {
+    &self.operands[..self.operand_count as usize]
+  }
+}
+
+/// Base mnemonic of an opcode
+pub fn mnemonic(opcode: u8) -> &'static str {
+  match opcode {
+    0x00..=0x0B | 0x0D..=0x11 | 0x1D | 0x1E | 0x40..=0x5F => "LR",
+    0x0C => "PK",
+    0x12 | 0x14 => "SR",
+    0x13 | 0x15 => "SL",
+    0x16 => "LM",
+    0x17 => "ST",
+    0x18 => "COM",
+    0x19 => "LNK",
+    0x1A => "DI",
+    0x1B => "EI",
+    0x1C => "POP",
+    0x1F => "INC",
+    0x20 => "LI",
+    0x21 => "NI",
+    0x22 => "OI",
+    0x23 => "XI",
+    0x24 => "AI",
+    0x25 => "CI",
+    0x26 => "IN",
+    0x27 => "OUT",
+    0x28 => "PI",
+    0x29 => "JMP",
+    0x2A => "DCI",
+    0x2B => "NOP",
+    0x2C => "XDC",
+    0x2D..=0x2F => "???",
+    0x30..=0x3F => "DS",
+    0x60..=0x67 => "LISU",
+    0x68..=0x6F => "LISL",
+    0x70..=0x7F => "LIS",
+    0x80..=0x87 => "BT",
+    0x88 => "AM",
+    0x89 => "AMD",
+    0x8A => "NM",
+    0x8B => "OM",
+    0x8C => "XM",
+    0x8D => "CM",
+    0x8E => "ADC",
+    0x8F => "BR7",
+    0x90 => "BR",
+    0x91..=0x9F => "BF",
+    0xA0..=0xAF => "INS",
+    0xB0..=0xBF => "OUTS",
+    0xC0..=0xCF => "AS",
+    0xD0..=0xDF => "ASD",
+    0xE0..=0xEF => "XS",
+    _ => "NS", //0xF0..=0xFF
+  }
+}
+
+/// ### Disassemble
+///
+/// Decodes the instruction at the start of `bytes`, which sits at `address` in ROM. Returns the text, and how many bytes it took.
+///
+/// Branch targets are shown as absolute addresses. Scratchpad register fields 12, 13 and 14 show as `S`, `I` and `D`, accessing through ISAR unchanged, incremented and decremented. A truncated instruction, or an unknown opcode, shows up as a `DB` byte. Empty `bytes` give empty text taking 0 bytes.
+///
+/// ### Example
+/// ```
+/// use chips::fairchild_f8::cpu3850::disassemble;
+///
+/// assert_eq!(disassemble(&[0x20, 0x12], 0), ("LI 0x12".into(), 2));
+/// assert_eq!(disassemble(&[0x29, 0x08, 0x00], 0), ("JMP 0x0800".into(), 3));
+/// assert_eq!(disassemble(&[0x94, 0xFE], 0x810), ("BF 0x4, 0x080F".into(), 2));
+/// assert_eq!(disassemble(&[0x4D], 0), ("LR A, I".into(), 1));
+/// assert_eq!(disassemble(&[0x4F], 0), ("DB 0x4F".into(), 1));
+/// assert_eq!(disassemble(&[], 0), ("".into(), 0));
+/// ```
+pub fn disassemble(bytes: &[u8], address: usize) -> (String, usize) {
+  let Some(&opcode) = bytes.first() else {
+    return (String::new(), 0);
+  };
+  let length = match opcode {
+    0x20..=0x27 | 0x80..=0x87 | 0x8F..=0x9F => 2,
+    0x28..=0x2A => 3,
+    _ => 1,
+  };
+  //Register field 15 has no register behind it
+  if bytes.len() < length || matches!(opcode, 0x2D..=0x2F | 0x3F | 0x4F | 0x5F | 0xCF | 0xDF | 0xEF | 0xFF) {
+    return (format!("DB 0x{:02X}", opcode), 1);
+  }
+  let low = opcode & 0xF;
+  let reg = || match low {
+    0..=11 => format!("R{:X}", low),
+    12 => String::from("S"),
+    13 => String::from("I"),
+    _ => String::from("D"),
+  };
+  //Relative to the address of the offset byte
+  let target = || format!("0x{:04X}", (address + 1).wrapping_add(bytes[1] as i8 as usize) & 0xFFFF);
+  let operands = match opcode {
+    0x00..=0x11 | 0x1D | 0x1E => String::from(match opcode {
+      0x00 => "A, KU", 0x01 => "A, KL", 0x02 => "A, QU", 0x03 => "A, QL",
+      0x04 => "KU, A", 0x05 => "KL, A", 0x06 => "QU, A", 0x07 => "QL, A",
+      0x08 => "K, P", 0x09 => "P, K", 0x0A => "A, IS", 0x0B => "IS, A",
+      0x0D => "P0, Q", 0x0E => "Q, DC", 0x0F => "DC, Q", 0x10 => "DC, H",
+      0x11 => "H, DC", 0x1D => "W, J", 0x1E => "J, W",
+      _ => "", //0x0C PK
+    }),
+    0x12 | 0x13 => String::from("1"),
+    0x14 | 0x15 => String::from("4"),
+    0x20..=0x27 => format!("0x{:02X}", bytes[1]),
+    0x28..=0x2A => format!("0x{:02X}{:02X}", bytes[1], bytes[2]),
+    0x30..=0x3F | 0xC0..=0xFF => reg(),
+    0x40..=0x4F => format!("A, {}", reg()),
+    0x50..=0x5F => format!("{}, A", reg()),
+    0x60..=0x6F => format!("{}", opcode & 0b111),
+    0x70..=0x7F | 0xA0..=0xBF => format!("0x{:X}", low),
+    0x80..=0x87 => format!("0x{:X}, {}", opcode & 0b111, target()),
+    0x8F | 0x90 => target(),
+    0x91..=0x9F => format!("0x{:X}, {}", low, target()),
+    _ => String::new(),
+  };
+  let text = if operands.is_empty() {
+    String::from(mnemonic(opcode))
+  } else {
+    format!("{} {}", mnemonic(opcode), operands)
+  };
+  (text, length)
+}
+
+/// ### ISAR Update
+///
+/// New ISAR value after a scratchpad access through the register field `op` of an instruction.
+///
+/// Field 13 increments and 14 decrements the lower octal digit of ISAR, wrapping around within the same group of 8 registers. Any other field leaves ISAR alone.
+///
+/// ### Example
+/// ```
+/// use chips::fairchild_f8::cpu3850::advance_isar;
+///
+/// assert_eq!(advance_isar(0o25, 13), 0o26);
+/// assert_eq!(advance_isar(0o27, 13), 0o20);
+/// assert_eq!(advance_isar(0o20, 14), 0o27);
+/// assert_eq!(advance_isar(0o20, 12), 0o20);
+/// ```
+pub fn advance_isar(isar: u8, op: u8) -> u8 {
+  let isar_low = isar & 0b111;
+  let new_isar_low = match (op, isar_low) {
+    (13, 0b111) => 0, //Wrap it around
+    (13, _) => isar_low + 1,
+
+    (14, 0) => 0b111, //Wrap it around
+    (14, _) => isar_low - 1,
+
+    _ => return isar,
+  };
+  (isar & 0b111000) | new_isar_low
+}
+
 /// Status Register (Flags). Also known as the W Register.
+///
+/// Bits match the W register exactly, as seen by `LR J,W` and `LR W,J`: 0 - Sign, 1 - Carry, 2 - Zero, 3 - Overflow, 4 - ICB.
 #[bitfield(u8, default: 0)]
 struct Flags {
   /// Interrupt (ICB)
@@ -75,9 +376,50 @@ struct Flags {
   #[bit(1, rw)]
   carry: bool,
 
-  /// Sign / negative (S): Set if result is negative
+  /// Sign (S): Set if result is positive, meaning bit 7 is clear
   #[bit(0, rw)]
-  negative: bool,
+  sign: bool,
+}
+
+/// Copy of the accumulator, ISAR, flags, scratchpad and IO ports of the F3850, for displaying without the `log` crate, or for save states.
+///
+/// The program counters and data counters live in the memory chips (PSU and DMI), not the CPU, so they need to be saved separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct F3850State {
+  /// Accumulator
+  pub acc: u8,
+  /// Indirect Scratchpad Address Register
+  pub isar: u8,
+  /// Sign flag, set if positive
+  pub sign: bool,
+  /// Carry flag
+  pub carry: bool,
+  /// Zero flag
+  pub zero: bool,
+  /// Overflow flag
+  pub overflow: bool,
+  /// Interrupt Control Bit
+  pub interrupt: bool,
+  /// Scratchpad registers
+  pub regs: [u8; 64],
+  /// IO ports 0 to 3
+  pub ports: [u8; 4],
+}
+
+impl Default for F3850State {
+  fn default() -> Self {
+    Self {
+      acc: 0,
+      isar: 0,
+      sign: false,
+      carry: false,
+      zero: false,
+      overflow: false,
+      interrupt: false,
+      regs: [0; 64],
+      ports: [0; 4],
+    }
+  }
 }
 
 /// Fairchild F3850 chip
@@ -94,6 +436,8 @@ pub struct CPU {
   pub reset: bool,
   /// IO Ports (Internal Register)
   pub ports: [u8; 4],
+  /// What to do with the unused opcodes 0x2D to 0x2F
+  pub on_invalid: crate::cpu::InvalidPolicy,
 }
 
 impl Default for CPU {
@@ -105,6 +449,7 @@ impl Default for CPU {
       regs: [0; 64],
       reset: false,
       ports: [0;4],
+      on_invalid: Default::default(),
     }
   }
 }
@@ -116,10 +461,104 @@ impl CPU {
     Default::default()
   }
 
+  /// Accumulator
+  #[inline]
+  pub fn acc(&self) -> u8 {
+    self.acc
+  }
+
+  /// Indirect Scratchpad Address Register
+  #[inline]
+  pub fn isar(&self) -> u8 {
+    self.isar
+  }
+
+  /// Load the whole ISAR at once, as LISU and LISL would together. Only the low 6 bits are kept.
+  #[inline]
+  pub fn set_isar(&mut self, value: u8) {
+    self.isar = value & 0b11_1111;
+  }
+
+  /// J register, scratchpad register 9, where LR J,W saves the flags
+  #[inline]
+  pub fn j(&self) -> u8 {
+    self.regs[9]
+  }
+
+  /// H register, HU:HL in scratchpad registers 10 and 11
+  #[inline]
+  pub fn hl(&self) -> u16 {
+    crate::cpu::join_u16_be(self.regs[10], self.regs[11])
+  }
+
+  /// K register, KU:KL in scratchpad registers 12 and 13, where LR K,P saves the return address
+  #[inline]
+  pub fn k(&self) -> u16 {
+    crate::cpu::join_u16_be(self.regs[12], self.regs[13])
+  }
+
+  /// Q register, QU:QL in scratchpad registers 14 and 15, where LR Q,DC saves the data counter
+  #[inline]
+  pub fn q(&self) -> u16 {
+    crate::cpu::join_u16_be(self.regs[14], self.regs[15])
+  }
+
+  /// ### Save CPU State
+  ///
+  /// Captures everything held inside the CPU, except a pending `reset`.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::fairchild_f8::cpu3850::{CPU, NullIo};
+  ///
+  /// let mut io = NullIo::new(&[0x20, 0x42, 0x55]); //LI 0x42, LR R5, A
+  /// let mut cpu = CPU::new();
+  /// cpu.run_cycle(&mut io);
+  /// cpu.run_cycle(&mut io);
+  ///
+  /// let mut copy = CPU::new();
+  /// copy.load_cpu(cpu.save_cpu());
+  /// assert_eq!((copy.acc(), copy.regs[5]), (0x42, 0x42));
+  /// ```
+  pub fn save_cpu(&self) -> F3850State {
+    let (sign, carry, zero, overflow, interrupt) = self.flags();
+    F3850State {
+      acc: self.acc,
+      isar: self.isar,
+      sign,
+      carry,
+      zero,
+      overflow,
+      interrupt,
+      regs: self.regs,
+      ports: self.ports,
+    }
+  }
+
+  /// Restore a state from `save_cpu`. Only the low 6 bits of the ISAR are kept.
+  pub fn load_cpu(&mut self, state: F3850State) {
+    self.acc = state.acc;
+    self.set_isar(state.isar);
+    self.flags = Flags::DEFAULT
+                  .with_sign(state.sign)
+                  .with_carry(state.carry)
+                  .with_zero(state.zero)
+                  .with_overflow(state.overflow)
+                  .with_interrupt(state.interrupt);
+    self.regs = state.regs;
+    self.ports = state.ports;
+  }
+
+  /// Flags in W register bit order: (sign, carry, zero, overflow, interrupt)
+  #[inline]
+  pub fn flags(&self) -> (bool, bool, bool, bool, bool) {
+    (self.flags.sign(), self.flags.carry(), self.flags.zero(), self.flags.overflow(), self.flags.interrupt())
+  }
+
   /// Print debug data of all registers
   pub fn print(&self) {
     trace!("Acc: 0x{:02X} ISAR: 0x{:02X}", self.acc, self.isar);
-    trace!("Interrupt: {} Overflow: {} Zero: {} Carry: {} Negative: {}", self.flags.interrupt(), self.flags.overflow(), self.flags.zero(), self.flags.carry(), self.flags.negative());
+    trace!("Interrupt: {} Overflow: {} Zero: {} Carry: {} Sign: {}", self.flags.interrupt(), self.flags.overflow(), self.flags.zero(), self.flags.carry(), self.flags.sign());
     trace!("R00: 0x{:02X} R01: 0x{:02X} R02: 0x{:02X} R03: 0x{:02X} R04: 0x{:02X}, R05: 0x{:02X} R06: 0x{:02X} R07: 0x{:02X} R10: 0x{:02X}   J: 0x{:02X}  HU: 0x{:02X}  HL: 0x{:02X}  KU: 0x{:02X},  KL: 0x{:02X}  QU: 0x{:02X}  QL: 0x{:02X}", self.regs[0x00], self.regs[0x01], self.regs[0x02], self.regs[0x03], self.regs[0x04], self.regs[0x05], self.regs[0x06], self.regs[0x07], self.regs[0x08], self.regs[0x09], self.regs[0x0A], self.regs[0x0B], self.regs[0x0C], self.regs[0x0D], self.regs[0x0E], self.regs[0x0F]);
     //trace!("R20: 0x{:02X} R11: 0x{:02X} R12: 0x{:02X} R13: 0x{:02X} R14: 0x{:02X}, R15: 0x{:02X} R16: 0x{:02X} R17: 0x{:02X}", self.regs[0x10], self.regs[0x11], self.regs[0x12], self.regs[0x13], self.regs[0x14], self.regs[0x15], self.regs[0x16], self.regs[0x17]);
     //trace!("R38: 0x{:02X} R19: 0x{:02X} R1A: 0x{:02X} R1B: 0x{:02X} R1C: 0x{:02X}, R1D: 0x{:02X} R1E: 0x{:02X} R1F: 0x{:02X}", self.regs[0x18], self.regs[0x19], self.regs[0x1A], self.regs[0x1B], self.regs[0x1C], self.regs[0x1D], self.regs[0x1E], self.regs[0x1F]);
@@ -129,6 +568,18 @@ impl CPU {
     //trace!("R78: 0x{:02X} R39: 0x{:02X} R3A: 0x{:02X} R3B: 0x{:02X} R3C: 0x{:02X}, R3D: 0x{:02X} R3E: 0x{:02X} R3F: 0x{:02X}", self.regs[0x38], self.regs[0x39], self.regs[0x3A], self.regs[0x3B], self.regs[0x3C], self.regs[0x3D], self.regs[0x3E], self.regs[0x3F]);
   }
 
+  /// ### Step
+  ///
+  /// Runs one instruction, like `run_cycle`, and reports where it was and what it was.
+  ///
+  /// If a reset was pending, no instruction ran, and `opcode` is the byte which was at `pc_before`.
+  pub fn step(&mut self, io: &mut impl IO) -> crate::cpu::StepInfo {
+    let (upper, lower) = io.get_pc0();
+    let opcode = io.peak_code() as u8;
+    let cycles = self.run_cycle(io) as u32;
+    crate::cpu::StepInfo { pc_before: crate::cpu::join_u16_be(upper, lower), opcode, cycles }
+  }
+
   /// Executes single instruction:
   ///
   /// 1. Read the next byte from ROM
@@ -230,13 +681,13 @@ impl CPU {
         let abs_addr = relative_addr.abs();
         let sign = if relative_addr > 0 { '+' } else { '-' };
         let condition = match opcode & 0b111 {
-          1 => { debug!("jmp if Positive to {}{}", sign, abs_addr); !self.flags.negative() },
+          1 => { debug!("jmp if Positive to {}{}", sign, abs_addr); self.flags.sign() },
           2 => { debug!("jmp if Carry to {}{}", sign, abs_addr); self.flags.carry() },
-          3 => { debug!("jmp if Carry or Positive to {}{}", sign, abs_addr); self.flags.carry() | !self.flags.negative() },
+          3 => { debug!("jmp if Carry or Positive to {}{}", sign, abs_addr); self.flags.carry() | self.flags.sign() },
           4 => { debug!("jmp if Zero to {}{}", sign, abs_addr); self.flags.zero() },
-          5 => { debug!("jmp if Zero or Positive to {}{}", sign, abs_addr); self.flags.zero() | !self.flags.negative() },
+          5 => { debug!("jmp if Zero or Positive to {}{}", sign, abs_addr); self.flags.zero() | self.flags.sign() },
           6 => { debug!("jmp if Zero or Carry to {}{}", sign, abs_addr); self.flags.zero() | self.flags.carry() },
-          7 => { debug!("jmp if Zero or Carry or Positive to {}{}", sign, abs_addr); self.flags.zero() | self.flags.carry() | !self.flags.negative() },
+          7 => { debug!("jmp if Zero or Carry or Positive to {}{}", sign, abs_addr); self.flags.zero() | self.flags.carry() | self.flags.sign() },
           _ => { debug!("Don't jump"); false },
         };
         if condition {
@@ -267,15 +718,15 @@ impl CPU {
         let sign = if relative_addr > 0 { '+' } else { '-' };
         let condition = match opcode {
           0x8F => { debug!("if isar low != 7 to {}{}", sign, abs_addr); self.isar & 0b111 != 0b111 },
-          0x91|0x95 => { debug!("jump if Negative to {}{}", sign, abs_addr); self.flags.negative() },
+          0x91|0x95 => { debug!("jump if Negative to {}{}", sign, abs_addr); !self.flags.sign() },
           0x92 => { debug!("jump if No Carry to {}{}", sign, abs_addr); !self.flags.carry() },
-          0x93|0x97 => { debug!("jump if No Carry and Negative to {}{}", sign, abs_addr); !self.flags.carry() & self.flags.negative() },
+          0x93|0x97 => { debug!("jump if No Carry and Negative to {}{}", sign, abs_addr); !self.flags.carry() & !self.flags.sign() },
           0x94 => { debug!("jump if Not Zero to {}{}", sign, abs_addr); !self.flags.zero() },
           0x96 => { debug!("jump if Not Carry and Not Zero to {}{}", sign, abs_addr); !self.flags.carry() & !self.flags.zero() },
           0x98 => { debug!("jump if No Overflow to {}{}", sign, abs_addr); !self.flags.overflow() },
-          0x99|0x9D => { debug!("jump if negative and No Overflow to {}{}", sign, abs_addr); self.flags.negative() & !self.flags.overflow() },
+          0x99|0x9D => { debug!("jump if negative and No Overflow to {}{}", sign, abs_addr); !self.flags.sign() & !self.flags.overflow() },
           0x9A => { debug!("jump if No Overflow and No Carry to {}{}", sign, abs_addr); !self.flags.overflow() & !self.flags.carry() },
-          0x9B|0x9F => { debug!("jump if No Overflow and No Carry and negative to {}{}", sign, abs_addr); !self.flags.overflow() & !self.flags.carry() & self.flags.negative() },
+          0x9B|0x9F => { debug!("jump if No Overflow and No Carry and negative to {}{}", sign, abs_addr); !self.flags.overflow() & !self.flags.carry() & !self.flags.sign() },
           0x9C => { debug!("jump if No Overflow and Not Zero to {}{}", sign, abs_addr); !self.flags.overflow() & !self.flags.zero() },
           0x9E => { debug!("jump if No Overflow and No Carry and Not Zero to {}{}", sign, abs_addr); !self.flags.overflow() & !self.flags.carry() & !self.flags.zero() },
           _ => { debug!("jump to {}{}", sign, abs_addr); true },  //0x90
@@ -310,27 +761,48 @@ impl CPU {
           _ => { debug!("NS R{:X}", reg); self.acc &= value; self.set_flags(); 4 }, //aNd register (0xF0..=0xFF)
         }
       },
-      _ => unreachable!("Unknown opcode: {:X}", opcode),
+      _ => { self.on_invalid.handle(opcode); 4 },  //0x2D..=0x2F
     }
   }
   
+  /// Same as `run_cycle`, but also reports the executed instruction to `sink`.
+  ///
+  /// The sink is not called when the cycle was spent on a reset, because no instruction was read.
+  pub fn run_cycle_traced(&mut self, io: &mut impl IO, sink: &mut impl FnMut(Insn)) -> u8 {
+    let mut trace_io = TraceIO {
+      io,
+      code: [0; 3],
+      code_len: 0,
+      peaked: Cell::new(None),
+    };
+    let cycles = self.run_cycle(&mut trace_io);
+    if trace_io.code_len > 0 {
+      let mut operands = [0; 2];
+      let mut operand_count = trace_io.code_len - 1;
+      operands[..operand_count].copy_from_slice(&trace_io.code[1..trace_io.code_len]);
+      //A branch which is taken only peaks at its relative address.
+      if let (0, Some(relative_addr)) = (operand_count, trace_io.peaked.get()) {
+        operands[0] = relative_addr;
+        operand_count = 1;
+      }
+      sink(Insn {
+        opcode: trace_io.code[0],
+        mnemonic: mnemonic(trace_io.code[0]),
+        operands,
+        operand_count: operand_count as u8,
+        cycles,
+      });
+    }
+    cycles
+  }
+
   fn reg_or_isar(&mut self, reg: u8) -> usize {
     (match reg {
       0..=11 => reg,
       12 => self.isar,
       _ => {
         let isar = self.isar;
-        let isar_low = self.isar & 0b111;
-        let new_isar_low = match (reg, isar_low) {
-          (13, 0b111) => 0, //Wrap it around
-          (13, _) => isar_low + 1,
-          
-          (14, 0) => 0b111, //Wrap it around
-          (14, _) => isar_low - 1,
-          
-          _ => unreachable!(),
-        };
-        self.isar = (self.isar & 0b111000) | new_isar_low;
+        self.isar = advance_isar(isar, reg);
         isar
       },
     }) as usize
@@ -366,7 +838,7 @@ impl CPU {
     let (_, carry7) = (num1 << 1).overflowing_add(num2 << 1);
 
     self.flags = self.flags
-                  .with_negative(result & 0b1000_0000 == 0b1000_0000)
+                  .with_sign(result & 0b1000_0000 == 0)
                   .with_carry(carry8)
                   .with_zero(result == 0)
                   .with_overflow(carry8 ^ carry7);
@@ -387,7 +859,7 @@ impl CPU {
     let carry7 = carry7_first | carry7_extra;
 
     self.flags = self.flags
-                  .with_negative(result & 0b1000_0000 == 0b1000_0000)
+                  .with_sign(result & 0b1000_0000 == 0)
                   .with_carry(carry8)
                   .with_zero(result == 0)
                   .with_overflow(carry8 ^ carry7);
@@ -396,7 +868,7 @@ impl CPU {
   
   fn set_flags(&mut self) {
     self.flags = self.flags
-                      .with_negative(self.acc & 0b1000_0000 == 0b1000_0000)
+                      .with_sign(self.acc & 0b1000_0000 == 0)
                       .with_carry(false)
                       .with_zero(self.acc == 0)
                       .with_overflow(false);
@@ -419,3 +891,127 @@ impl CPU {
     };
   }
 }
+
+impl<T: IO> crate::cpu::Chip<T> for CPU {
+  /// Run one instruction. A pending reset is handled on its own, without running an instruction.
+  #[inline]
+  fn run_instruction(&mut self, io: &mut T) -> u32 {
+    self.run_cycle(io) as u32
+  }
+  /// The program counter is kept in the memory chips, so it comes from `io`
+  #[inline]
+  fn pc(&self, io: &T) -> u16 {
+    let (upper, lower) = io.get_pc0();
+    crate::cpu::join_u16_be(upper, lower)
+  }
+  /// Raises the reset line. The jump back to 0 happens on the next instruction, because it goes through the IO. Registers keep their values, as on the real chip.
+  #[inline]
+  fn reset(&mut self) {
+    self.reset = true;
+  }
+  #[inline]
+  fn name(&self) -> &'static str {
+    "Fairchild F3850"
+  }
+}
+
+/// Passes everything through to the board, while recording the code bytes read.
+struct TraceIO<'a, T: IO> {
+  io: &'a mut T,
+  code: [u8; 3],
+  code_len: usize,
+  peaked: Cell<Option<u8>>,
+}
+
+impl<T: IO> IO for TraceIO<'_, T> {
+  fn output(&mut self, port: u8, value: u8) {
+    self.io.output(port, value);
+  }
+  fn input(&self, port: u8) -> u8 {
+    self.io.input(port)
+  }
+  fn read_external_port(&self, port: u8) -> u8 {
+    self.io.read_external_port(port)
+  }
+
+  fn next_code(&mut self) -> u8 {
+    let byte = self.io.next_code();
+    if let Some(slot) = self.code.get_mut(self.code_len) {
+      *slot = byte;
+      self.code_len += 1;
+    }
+    byte
+  }
+  fn peak_code(&self) -> i8 {
+    let byte = self.io.peak_code();
+    self.peaked.set(Some(byte as u8));
+    byte
+  }
+  fn next_data(&mut self) -> u8 {
+    self.io.next_data()
+  }
+  fn write_data(&mut self, data: u8) {
+    self.io.write_data(data);
+  }
+
+  fn jump(&mut self, upper: u8, lower: u8, push_pc: bool) {
+    self.io.jump(upper, lower, push_pc);
+  }
+  fn jump_relative(&mut self, relative_addr: i8) {
+    self.io.jump_relative(relative_addr);
+  }
+  fn ret_pc(&mut self) {
+    self.io.ret_pc();
+  }
+
+  fn add_dc0(&mut self, a: i8) {
+    self.io.add_dc0(a);
+  }
+  fn get_dc0(&self) -> (u8, u8) {
+    self.io.get_dc0()
+  }
+  fn set_dc0(&mut self, upper: u8, lower: u8) {
+    self.io.set_dc0(upper, lower);
+  }
+  fn swap_dc(&mut self) {
+    self.io.swap_dc();
+  }
+
+  fn get_pc0(&self) -> (u8, u8) {
+    self.io.get_pc0()
+  }
+  fn get_pc1(&self) -> (u8, u8) {
+    self.io.get_pc1()
+  }
+  fn set_pc1(&mut self, upper: u8, lower: u8) {
+    self.io.set_pc1(upper, lower);
+  }
+}
+
+/// Byte layout: flags, acc, isar, registers (64), reset, ports (4)
+impl Snapshot for CPU {
+  fn save(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(72);
+    bytes.push(self.flags.raw_value());
+    bytes.push(self.acc);
+    bytes.push(self.isar);
+    bytes.extend_from_slice(&self.regs);
+    bytes.push(self.reset as u8);
+    bytes.extend_from_slice(&self.ports);
+    bytes
+  }
+
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+    snapshot::check_length(bytes, 72)?;
+    if bytes[2] > 0b11_1111 { //ISAR is a 6 bit register
+      return Err(SnapshotError::InvalidData);
+    }
+    self.flags = Flags::new_with_raw_value(bytes[0]);
+    self.acc = bytes[1];
+    self.isar = bytes[2];
+    self.regs.copy_from_slice(&bytes[3..67]);
+    self.reset = bytes[67] != 0;
+    self.ports.copy_from_slice(&bytes[68..72]);
+    Ok(())
+  }
+}
@@ -6,6 +6,9 @@ use arbitrary_int::u6;
 
 const RAM_SIZE: usize = 1028;
 
+/// Addresses answered by one chip. Only the low 10 bits reach the RAM, so the bytes past 1K can't be addressed as memory.
+const PAGE_SIZE: usize = 1024;
+
 /// Fairchild 3852 RAM chip
 pub struct F3852 {
   /// RAM data
@@ -46,6 +49,19 @@ impl F3852 {
   pub fn print(&self) {
     debug!("PC0: 0x{:04X} PC1: 0x{:04X} DC0: 0x{:04X} DC1: 0x{:04X}", self.pc0, self.pc1, self.dc0, self.dc1);
   }
+  /// Whether a code fetch from `addr` is answered by this chip's RAM
+  #[inline]
+  pub fn owns_code(&self, addr: u16) -> bool {
+    let addr = addr as usize;
+    addr >= self.page_mask && addr < PAGE_SIZE + self.page_mask
+  }
+
+  /// Whether a data access at `addr` is answered by this chip's RAM
+  #[inline]
+  pub fn owns_data(&self, addr: u16) -> bool {
+    self.owns_code(addr)
+  }
+
   /// Used by the JMP, PI, and PK instructions.
   pub fn jump(&mut self, address: u16, push_pc: bool) {
     if push_pc {
@@ -74,8 +90,8 @@ impl F3852 {
   /// For reading the next instruction
   pub fn next_code(&mut self) -> u8 {
     let pointer = self.pc0 as usize;
-    self.pc0 += 1;  //Incremented even if it doesn't have it.
-    if pointer >= self.page_mask && pointer < RAM_SIZE + self.page_mask {
+    self.pc0 = self.pc0.wrapping_add(1);  //Incremented even if it doesn't have it.
+    if self.owns_code(pointer as u16) {
       return self.ram.read(pointer & 0b1111111111) //Take off the high page mask
     }
     0
@@ -85,7 +101,7 @@ impl F3852 {
   /// Read, without updated pc0. Used by relative jump
   pub fn peak_code(&self) -> i8 {
     let pointer = self.pc0 as usize;
-    if pointer >= self.page_mask && pointer < RAM_SIZE + self.page_mask {
+    if self.owns_code(pointer as u16) {
       return self.ram.read::<u8>(pointer & 0b1111111111) as i8 //Take off the high page mask
     }
     0
@@ -95,8 +111,8 @@ impl F3852 {
   /// Used by commands LM, AM, CM, etc reading data fram memory
   pub fn next_data(&mut self) -> u8 {
     let pointer = self.dc0 as usize;
-    self.dc0 += 1;  //Incremented even if it doesn't have it.
-    if pointer >= self.page_mask && pointer < RAM_SIZE + self.page_mask {
+    self.dc0 = self.dc0.wrapping_add(1);  //Incremented even if it doesn't have it.
+    if self.owns_data(pointer as u16) {
       return self.ram.read(pointer & 0b1111111111) //Take off the high page mask
     }
     0
@@ -105,8 +121,8 @@ impl F3852 {
   /// Used by command ST writing data to memory
   pub fn write_data(&mut self, data: u8) {
     let pointer = self.dc0 as usize;
-    self.dc0 += 1;  //Incremented even if it doesn't have it.
-    if pointer >= self.page_mask && pointer < RAM_SIZE + self.page_mask {
+    self.dc0 = self.dc0.wrapping_add(1);  //Incremented even if it doesn't have it.
+    if self.owns_data(pointer as u16) {
       self.ram.write(pointer & 0b1111111111, data); //Take off the high page mask
     }
   }
@@ -116,16 +132,22 @@ impl F3852 {
     (self.dc0,self.dc1) = (self.dc1,self.dc0);
   }
 
+  /// Whether `port` is one of this chip's IO ports, chosen by the port select
+  #[inline]
+  pub fn owns_port(&self, port: u8) -> bool {
+    (port as usize) >= self.port_mask && (port as usize) < (0b11 | self.port_mask)
+  }
+
   /// ramC1A
   pub fn write_port(&mut self, port: u8, value: u8) {
-    if (port as usize) >= self.port_mask && (port as usize) < (0b11 | self.port_mask) {
+    if self.owns_port(port) {
       self.ports[port as usize & 0b11] = value;
     }
   }
   
   /// ramC1B
   pub fn read_port(&self, port: u8) -> u8 {
-    if (port as usize) >= self.port_mask && (port as usize) < (0b11 | self.port_mask) {
+    if self.owns_port(port) {
       return self.ports[port as usize & 0b11]
     }
     0
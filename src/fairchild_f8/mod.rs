@@ -5,7 +5,8 @@ use log::{info, warn};
 pub mod cpu3850;
 pub mod psu3851;
 pub mod dmi3852;
-use crate::ram;
+use crate::{cpu, ram, OR};
+use crate::display::{DisplayFrame, HasDisplay};
 use arbitrary_int::u6;
 use alloc::vec;
 
@@ -15,33 +16,120 @@ pub struct Board {
   pub rams: vec::Vec<dmi3852::F3852>,
   pub vram: [ram::MK4027; 4],
   pub ports: [u8; 256], //external port values
+  /// Set when the video logic changes a VRAM bit
+  vram_changed: bool,
+  /// The video logic runs once every this many CPU instructions
+  video_divisor: u32,
+  /// CPU instructions run so far
+  cpu_cycles: u64,
+  /// Times the video logic ran so far
+  video_cycles: u64,
+  /// RGB for each 2 bit pixel color
+  palette: [(u8, u8, u8); 4],
+}
+
+/// What happened while running a frame with `Board::run_frame`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameResult {
+  /// Clock cycles the CPU ran for
+  pub cycles: u32,
+  /// Whether any pixel changed color, so the frame needs redrawing
+  pub vram_changed: bool,
+}
+
+/// Tone the Channel F speaker is playing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoundTone {
+  /// No sound
+  Silence,
+  /// 1 kHz square wave
+  High,
+  /// 500 Hz square wave
+  Medium,
+  /// 120 Hz square wave
+  Low,
+}
+
+/// Reasons a board could not be built
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+  /// The ROM data does not split evenly into 1K PSU chips
+  RomNotAligned {
+    /// Number of bytes given
+    length: usize,
+  },
+  /// More PSU chips are needed than there are port selects for
+  TooManyRoms {
+    /// Number of PSU chips needed
+    count: usize,
+  },
+  /// A Channel F BIOS dump is not the two 1K ROM chips it should be
+  BiosSize {
+    /// Number of bytes given
+    length: usize,
+  },
+  /// A cartridge dump is empty
+  EmptyCartridge,
 }
 
 impl Board {
+  /// The DMI RAM chips of a Channel F with the maze cartridge (videocart 10), as (page, port select).
+  ///
+  /// Boards get these by default. The first chip's ports are 0x24 and 0x25, which the maze cartridge hardwires to its RAM.
+  pub const DEFAULT_RAMS: [(u6, u6); 2] = [
+    (u6::new(0xA), u6::new(0b1001)),
+    (u6::new(0xB), u6::new(0xC)),
+  ];
+
+  /// Size of the Channel F BIOS, which is split over two 1K ROM chips, SL31253 and SL31254
+  pub const BIOS_SIZE: usize = 2 * psu3851::ROM_SIZE;
+
+  /// Most PSU chips a board can hold, limited by the 6 bit port select
+  pub const MAX_ROMS: usize = 63;
+
+  /// Channel F colors, as RGB, for each 2 bit pixel color.
+  ///
+  /// Port 1 bits 6 and 7 pick the color, inverted. So writing 0x00 draws green (3), 0x40 red (2), 0x80 blue (1), and 0xC0 the background (0).
+  ///
+  /// The background really depends on the palette bits at the end of each row, which aren't modeled, so it is black here.
+  pub const DEFAULT_PALETTE: [(u8, u8, u8); 4] = [
+    (0x10, 0x10, 0x10), //Background
+    (0x4B, 0x3F, 0xF3), //Blue
+    (0xFF, 0x31, 0x53), //Red
+    (0x02, 0xCC, 0x5D), //Green
+  ];
+
+  /// Create a new board
+  ///
+  /// Panics if the ROMs can't fill whole PSU chips. See `try_new`.
   pub fn new(bios_rom: Option<vec::Vec<u8>>, extra_rom: Option<vec::Vec<u8>>) -> Self {
-    let mut roms = vec![];
-    
-    let mut mask = 0;
-    if let Some(data) = bios_rom {
-      for chunk in data.chunks(1024) {
-        roms.push(psu3851::F3851::new(chunk.to_vec().try_into().unwrap(), u6::new(mask), u6::new(mask + 1)));
-        mask += 1;
-      }
+    match Self::try_new(bios_rom, extra_rom) {
+      Ok(board) => board,
+      Err(error) => panic!("Invalid F8 board: {:?}", error),
     }
-    
-    if let Some(data) = extra_rom {
-      for chunk in data.chunks(1024) {
-        roms.push(psu3851::F3851::new(chunk.to_vec().try_into().unwrap(), u6::new(mask), u6::new(mask + 1)));
-        mask += 1;
-      }
+  }
+
+  /// Create a new board, checking that every ROM fills whole 1K PSU chips, and that there are port selects left for all of them.
+  pub fn try_new(bios_rom: Option<vec::Vec<u8>>, extra_rom: Option<vec::Vec<u8>>) -> Result<Self, BoardError> {
+    let data: vec::Vec<vec::Vec<u8>> = [bios_rom, extra_rom].into_iter().flatten().collect();
+    if let Some(rom) = data.iter().find(|rom| rom.len() % psu3851::ROM_SIZE != 0) {
+      return Err(BoardError::RomNotAligned { length: rom.len() });
+    }
+    let count = data.iter().map(|rom| rom.len() / psu3851::ROM_SIZE).sum();
+    if count > Self::MAX_ROMS {
+      return Err(BoardError::TooManyRoms { count });
     }
-    Self {
+
+    let mut roms = vec![];
+    for (mask, chunk) in data.iter().flat_map(|rom| rom.chunks_exact(psu3851::ROM_SIZE)).enumerate() {
+      let mut rom = [0; psu3851::ROM_SIZE];
+      rom.copy_from_slice(chunk);
+      roms.push(psu3851::F3851::new(rom, u6::new(mask as u8), u6::new(mask as u8 + 1)));
+    }
+    Ok(Self {
       cpu: cpu3850::CPU::new(),
       roms,
-      rams: vec![
-        dmi3852::F3852::new(u6::new(0xA), u6::new(0b1001)), //Port 0x24 and 0x25 set for maze (videocart 10)
-        dmi3852::F3852::new(u6::new(0xB), u6::new(0xC)),
-      ],
+      rams: Self::DEFAULT_RAMS.iter().map(|&(page, port_select)| dmi3852::F3852::new(page, port_select)).collect(),
       vram: [
         ram::MK4027::new(),
         ram::MK4027::new(),
@@ -49,17 +137,115 @@ impl Board {
         ram::MK4027::new(),
       ],
       ports: [0; 256],
+      vram_changed: false,
+      video_divisor: 1,
+      cpu_cycles: 0,
+      video_cycles: 0,
+      palette: Self::DEFAULT_PALETTE,
+    })
+  }
+
+  /// ### Channel F
+  ///
+  /// Create a Channel F from a BIOS dump and an optional cartridge dump, such as `.bin` files.
+  ///
+  /// Unlike `try_new`, which takes any ROMs, the BIOS must be exactly `BIOS_SIZE`, and the cartridge must not be empty. Cartridges still need to fill whole 1K chips.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::fairchild_f8::{Board, BoardError};
+  ///
+  /// assert!(Board::try_channel_f(vec![0; Board::BIOS_SIZE], Some(vec![0; 0x800])).is_ok());
+  /// assert_eq!(Board::try_channel_f(vec![0; 0x7FF], None).err(), Some(BoardError::BiosSize { length: 0x7FF }));
+  /// ```
+  pub fn try_channel_f(bios: vec::Vec<u8>, cartridge: Option<vec::Vec<u8>>) -> Result<Self, BoardError> {
+    if bios.len() != Self::BIOS_SIZE {
+      return Err(BoardError::BiosSize { length: bios.len() });
+    }
+    if cartridge.as_ref().is_some_and(|cartridge| cartridge.is_empty()) {
+      return Err(BoardError::EmptyCartridge);
+    }
+    Self::try_new(Some(bios), cartridge)
+  }
+
+  /// Replace the DMI RAM chips, one for each (page, port select), for cartridges which don't match `DEFAULT_RAMS`.
+  ///
+  /// The maze cartridge hardwire goes to the chip with port select 0b1001, on ports 0x24 and 0x25. Without one, it is left out.
+  pub fn with_rams(mut self, rams: &[(u6, u6)]) -> Self {
+    self.rams = rams.iter().map(|&(page, port_select)| dmi3852::F3852::new(page, port_select)).collect();
+    self
+  }
+
+  /// Replace the RGB colors of `DEFAULT_PALETTE`, such as to match a particular TV
+  pub fn with_palette(mut self, palette: [(u8, u8, u8); 4]) -> Self {
+    self.palette = palette;
+    self
+  }
+
+  /// RGB of a 2 bit pixel color, as returned by `read_pixel`. Only the low 2 bits of `color` are used.
+  #[inline]
+  pub fn palette_rgb(&self, color: u8) -> (u8, u8, u8) {
+    self.palette[(color & 0b11) as usize]
+  }
+
+  /// Read the CPU accumulator, ISAR, flags and scratchpad at once, for debuggers
+  #[inline]
+  pub fn cpu_state(&self) -> cpu3850::F3850State {
+    self.cpu.save_cpu()
+  }
+
+  /// Run the video logic only once every `divisor` CPU instructions. Boards start with a divisor of 1, running it before every instruction.
+  ///
+  /// Panics if `divisor` is 0.
+  pub fn with_video_divisor(mut self, divisor: u32) -> Self {
+    assert!(divisor > 0, "Video divisor must be at least 1");
+    self.video_divisor = divisor;
+    self
+  }
+
+  /// How many times the video logic has run
+  #[inline]
+  pub fn video_cycles(&self) -> u64 {
+    self.video_cycles
+  }
+
+  /// Clock cycles in one NTSC field, with the CPU running at 1.7897725 MHz, and the TV at 60 Hz
+  pub const CYCLES_PER_FRAME: u32 = 29_830;
+
+  /// Run for one TV field.
+  ///
+  /// The video logic has no vertical blank signal to watch for, and games redraw VRAM whenever they like, so the frame end is a heuristic: the CPU is run until `CYCLES_PER_FRAME` clock cycles have passed.
+  pub fn run_frame(&mut self) -> FrameResult {
+    self.vram_changed = false;
+    let mut cycles = 0;
+    while cycles < Self::CYCLES_PER_FRAME {
+      cycles += self.run_cycle() as u32;
+    }
+    FrameResult {
+      cycles,
+      vram_changed: self.vram_changed,
     }
   }
 
   /// Runs the CPU and has it interact with the PSU
   pub fn run_cycle(&mut self) -> u8 {
-    {
+    self.run_cycle_with_video(|_, _, _| ())
+  }
+
+  /// Runs a cycle like `run_cycle`, calling `on_pixel(x, y, color)` whenever the PSU writes a pixel to VRAM.
+  ///
+  /// The pixel is reported even when it already had that color.
+  pub fn run_cycle_with_video(&mut self, mut on_pixel: impl FnMut(u8, u8, u8)) -> u8 {
+    if self.cpu_cycles % self.video_divisor as u64 == 0 {
       let mut io = VideoIO {
         board: self,
       };
-      io.run_cycle();
+      if let Some((x, y, color)) = io.run_cycle() {
+        on_pixel(x, y, color);
+      }
+      self.video_cycles += 1;
     }
+    self.cpu_cycles += 1;
     
     {
       let mut io = F3850IO {
@@ -72,41 +258,97 @@ impl Board {
 
   }
   
+  /// ### Sound
+  ///
+  /// The tone selected by bits 6 and 7 of port 5. The rest of port 5 holds the row of the next pixel to draw.
+  ///
+  /// | Bits 7, 6 | Tone    |
+  /// |-----------|---------|
+  /// | 00        | Silence |
+  /// | 01        | 1 kHz   |
+  /// | 10        | 500 Hz  |
+  /// | 11        | 120 Hz  |
+  ///
+  /// The tones are generated by the hardware, so audio front ends only need to poll this once a frame.
+  pub fn sound_state(&self) -> SoundTone {
+    match self.read_port(5) >> 6 {
+      0b00 => SoundTone::Silence,
+      0b01 => SoundTone::High,
+      0b10 => SoundTone::Medium,
+      _ => SoundTone::Low,
+    }
+  }
+
+  /// ### Read Pixel
+  ///
+  /// 2 bit color of the pixel at (x, y), out of 128x64. Bit 0 is the plane in VRAM chips 0 and 1, bit 1 is the plane in chips 2 and 3.
+  ///
+  /// Each plane needs 8192 bits, twice what one 4096 bit chip holds, so the top 32 rows are in the first chip of the plane, and the bottom 32 rows in the second.
+  ///
+  /// Panics if `x` is 128 or more, or `y` is 64 or more.
+  pub fn read_pixel(&self, x: usize, y: usize) -> u8 {
+    assert!(x < 128 && y < 64, "Pixel ({}, {}) is off the 128x64 screen", x, y);
+    let (chip, bit) = vram_address(x, y);
+    (self.vram[chip].read_bit(bit) as u8) | (self.vram[chip + 2].read_bit(bit) as u8) << 1
+  }
+
   /// Combines internal and external port values together
   pub fn read_port(&self, port: u8) -> u8 {
-    let mut ret = 0;
-    if port < 4 {
-      ret = self.cpu.ports[port as usize];
+    let ret = if port < 4 {
+      self.cpu.ports[port as usize]
     } else {
-      for rom in self.roms.iter() {
-        ret |= rom.read_port(port);
-      }
-      for ram in self.rams.iter() {
-        ret |= ram.read_port(port);
-      }
-    }
+      OR::or_all(self.roms.iter().map(|rom| rom.read_port(port))
+        .chain(self.rams.iter().map(|ram| ram.read_port(port))))
+    };
     ret | self.ports[port as usize]
   }
 }
 
+impl HasDisplay for Board {
+  /// The Channel F always sends video to the TV.
+  #[inline]
+  fn display_on(&self) -> bool {
+    true
+  }
+
+  /// 128x64 pixels, with 2 bit colors. Bit 0 comes from VRAM chips 0 and 1, bit 1 from chips 2 and 3.
+  fn pixels(&self) -> DisplayFrame {
+    let colors = (0..64).flat_map(|y| (0..128).map(move |x| (x, y)))
+      .map(|(x, y)| self.read_pixel(x, y))
+      .collect();
+    DisplayFrame::Pixels {
+      width: 128,
+      height: 64,
+      colors,
+    }
+  }
+}
+
 struct F3850IO<'a> {
   rams: &'a mut vec::Vec<dmi3852::F3852>,
   roms: &'a mut vec::Vec<psu3851::F3851>,
   ports: &'a mut [u8; 256],
 }
 
+impl F3850IO<'_> {
+  /// The bytes of all chips are ORed together, which only works if at most one chip answers at `address`. Panics in debug builds when more do, such as two chips set to the same page.
+  fn check_overlap(&self, address: u16) {
+    debug_assert!(
+      self.roms.iter().filter(|rom| rom.owns_code(address)).count() + self.rams.iter().filter(|ram| ram.owns_code(address)).count() <= 1,
+      "More than one memory chip answers at 0x{:04X}", address,
+    );
+  }
 
-/// Outputs (upper, lower)
-fn u16_to_u8(source: u16) -> (u8, u8) {
-  let bytes = source.to_be_bytes();
-  (bytes[0], bytes[1])
-}
-
-fn u8_to_u16(upper: u8, lower: u8) -> u16 {
-  u16::from_be_bytes([upper, lower])
+  /// All chips keep their own copy of the memory pointers, which are always equal
+  fn pointers(&self) -> Option<(u16, u16)> {
+    match (self.roms.first(), self.rams.first()) {
+      (Some(rom), _) => Some((rom.pc0, rom.dc0)),
+      (None, Some(ram)) => Some((ram.pc0, ram.dc0)),
+      (None, None) => None,
+    }
+  }
 }
 
-
 impl cpu3850::IO for F3850IO<'_> {
   fn output(&mut self, port: u8, value: u8) {
     info!("OUT Port: {} Value: {:08b}", port, value);
@@ -117,9 +359,10 @@ impl cpu3850::IO for F3850IO<'_> {
       ram.write_port(port, value);
     }
     
-    //Hardwired for videocart 10 (maze)
+    //Hardwired for videocart 10 (maze), to whichever RAM has ports 0x24 and 0x25
     //Source - https://www.reddit.com/r/ChannelF/comments/91cpj8/reading_and_writing_from_ports_36_37/
-    if port == 0x24 {
+    let maze_ram = if port == 0x24 { self.rams.iter().position(|ram| ram.owns_port(0x24)) } else { None };
+    if let Some(index) = maze_ram {
       let port24 = value as usize;
       let addr1 = (port24 & 0b00000010) << 2  //1 maps to 3
                 | (port24 & 0b00000100);      //2 maps to 2
@@ -136,14 +379,15 @@ impl cpu3850::IO for F3850IO<'_> {
 
       let hardwired_address = addr1 | addr2;
       
+      let ram = &mut self.rams[index];
       let is_write = (port24 & 0b1) != 0;
       if is_write {
         //Write port bit to ram.
-        self.rams[0].ram.write_bit(hardwired_address, (port24 & 0b1000) != 0);
+        ram.ram.write_bit(hardwired_address, (port24 & 0b1000) != 0);
       } else {
         //Read. Update the port to contain the ram bit, so it can be read next time.
-        let data_bit = (self.rams[0].ram.read_bit(hardwired_address) as u8) << 7;
-        self.rams[0].write_port(0x24, (value & 0b01111111) | data_bit);
+        let data_bit = (ram.ram.read_bit(hardwired_address) as u8) << 7;
+        ram.write_port(0x24, (value & 0b01111111) | data_bit);
       }
     }
   }
@@ -166,6 +410,9 @@ impl cpu3850::IO for F3850IO<'_> {
   
   /// Read next code byte
   fn next_code(&mut self) -> u8 {
+    if let Some((pc0, _)) = self.pointers() {
+      self.check_overlap(pc0);
+    }
     let mut ret = 0;
     for rom in &mut *self.roms {
       ret |= rom.next_code();  //We must run it for all ROMS so they update their pc0.
@@ -189,6 +436,9 @@ impl cpu3850::IO for F3850IO<'_> {
   
   /// Read next data byte
   fn next_data(&mut self) -> u8 {
+    if let Some((_, dc0)) = self.pointers() {
+      self.check_overlap(dc0);
+    }
     let mut ret = 0;
     for rom in &mut *self.roms {
       ret |= rom.next_data();  //We must run it for all ROMS so they update their dc0.
@@ -211,7 +461,7 @@ impl cpu3850::IO for F3850IO<'_> {
 
   /// Jump to direct address. push_pc will back up the current position, so you can return to it later. (Call vs Jump)
   fn jump(&mut self, upper: u8, lower: u8, push_pc: bool) {
-    let addr = u8_to_u16(upper, lower);
+    let addr = cpu::join_u16_be(upper, lower);
     for rom in &mut *self.roms {
       rom.jump(addr, push_pc);
     }
@@ -256,11 +506,11 @@ impl cpu3850::IO for F3850IO<'_> {
     for ram in self.rams.iter() {
       ret |= ram.dc0;
     }
-    u16_to_u8(ret)
+    cpu::split_u16_be(ret)
   }
   /// Set dc0 pointer
   fn set_dc0(&mut self, upper: u8, lower: u8) {
-    let dc0 = u8_to_u16(upper, lower);
+    let dc0 = cpu::join_u16_be(upper, lower);
     for rom in &mut *self.roms {
       rom.dc0 = dc0;
     }
@@ -278,6 +528,10 @@ impl cpu3850::IO for F3850IO<'_> {
     }
   }
   
+  /// Get pc0 pointer, returns upper, lower
+  fn get_pc0(&self) -> (u8, u8) {
+    cpu::split_u16_be(OR::or_all(self.roms.iter().map(|rom| rom.pc0).chain(self.rams.iter().map(|ram| ram.pc0))))
+  }
   /// Get pc1 pointer, returns upper, lower
   fn get_pc1(&self) -> (u8, u8) {
     let mut ret = 0;
@@ -287,11 +541,11 @@ impl cpu3850::IO for F3850IO<'_> {
     for ram in self.rams.iter() {
       ret |= ram.pc1;
     }
-    u16_to_u8(ret)
+    cpu::split_u16_be(ret)
   }
   /// Set pc1 pointer
   fn set_pc1(&mut self, upper: u8, lower: u8) {
-    let pc1 = u8_to_u16(upper, lower);
+    let pc1 = cpu::join_u16_be(upper, lower);
     for rom in &mut *self.roms {
       rom.pc1 = pc1;
     }
@@ -303,6 +557,13 @@ impl cpu3850::IO for F3850IO<'_> {
 }
 
 
+/// VRAM chip of color plane 0 holding pixel (x, y), and the bit inside of it. Plane 1 is 2 chips further.
+fn vram_address(x: usize, y: usize) -> (usize, usize) {
+  //The pixels 128x64 = 8192 which is twice of our vram 4096. So one chip has the 0 to 4095 and the other chip has 4096 to 8191
+  let address = x + y * 128;
+  (address / 4096, address % 4096)
+}
+
 struct VideoIO<'a> {
   board: &'a mut Board,
 }
@@ -310,26 +571,18 @@ struct VideoIO<'a> {
 impl VideoIO<'_> {
   /// This fills in my lacking knowledge of the communication that goes on between the CPU/PSU and the VRAM.
   /// The reason this cannot be done on the CPU out, is that multiple ports needs to be read at the same time, which causes a self reference error.
-  fn run_cycle(&mut self) {
+  /// Returns the (x, y, color) of the pixel written, if any.
+  fn run_cycle(&mut self) -> Option<(u8, u8, u8)> {
     if self.read_cpu_port(0) & 0b100000 == 0b100000 {
-      //The pixels 128x64 = 8192 which is twice of our vram 4096. So one chip has the 0 to 4095 and the other chip has 4096 to 8191
       let color = !self.read_cpu_port(1) >> 6; //We only care about the inverted bits 6 and 7.
       let video_x = self.read_rom_port(4) & 0b01111111;  //Don't include the last bit. It keeps getting set for some reason, but is beyond the 128 limit.
       let video_y = self.read_rom_port(5) & 0b00111111;  //Don't include the last 2 bits. It is beyond the 64 limit.
-      let address = (video_x as usize) + (video_y as usize) * 128;
-      let value = if color & 0b1 == 0b1 { true } else { false };
-      if address < 4096 {
-        self.board.vram[0].write_bit(address, value);
-      } else {
-        self.board.vram[1].write_bit(address - 4096, value);
-      }
-      
-      let value = if color & 0b10 == 0b10 { true } else { false };
-      if address < 4096 {
-        self.board.vram[2].write_bit(address, value);
-      } else {
-        self.board.vram[3].write_bit(address - 4096, value);
-      }
+      let (chip, bit) = vram_address(video_x as usize, video_y as usize);
+      self.write_vram_bit(chip, bit, color & 0b1 == 0b1);
+      self.write_vram_bit(chip + 2, bit, color & 0b10 == 0b10);
+      Some((video_x, video_y, color & 0b11))
+    } else {
+      None
     }
   }
 
@@ -343,6 +596,15 @@ impl VideoIO<'_> {
     ret | self.board.ports[port as usize]
   }
   
+  /// Write a VRAM bit, noting if it changed
+  fn write_vram_bit(&mut self, chip: usize, address: usize, value: bool) {
+    let vram = &mut self.board.vram[chip];
+    if vram.read_bit(address) != value {
+      self.board.vram_changed = true;
+      vram.write_bit(address, value);
+    }
+  }
+
   /// Read from CPU IO port (Internal + external)
   fn read_cpu_port(&self, port: u8) -> u8 {
     self.board.cpu.ports[port as usize] | self.board.ports[port as usize]
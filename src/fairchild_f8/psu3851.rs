@@ -5,7 +5,7 @@ use crate::ROM;
 use log::debug;
 use arbitrary_int::u6;
 
-const ROM_SIZE: usize = 1024;
+pub(super) const ROM_SIZE: usize = 1024;
 
 /// Fairchild 3851 chip
 pub struct F3851 {
@@ -47,6 +47,19 @@ impl F3851 {
   pub fn print(&self) {
     debug!("PC0: 0x{:04X} PC1: 0x{:04X} DC0: 0x{:04X}", self.pc0, self.pc1, self.dc0);
   }
+  /// Whether a code fetch from `addr` is answered by this chip's ROM
+  #[inline]
+  pub fn owns_code(&self, addr: u16) -> bool {
+    let addr = addr as usize;
+    addr >= self.page_mask && addr < ROM_SIZE + self.page_mask
+  }
+
+  /// Whether a data access at `addr` is answered by this chip's ROM
+  #[inline]
+  pub fn owns_data(&self, addr: u16) -> bool {
+    self.owns_code(addr)
+  }
+
   /// Used by the JMP, PI, and PK instructions.
   pub fn jump(&mut self, address: u16, push_pc: bool) {
     if push_pc {
@@ -75,8 +88,8 @@ impl F3851 {
   /// For reading the next instruction
   pub fn next_code(&mut self) -> u8 {
     let pointer = self.pc0 as usize;
-    self.pc0 += 1;  //Incremented even if it doesn't have it.
-    if pointer >= self.page_mask && pointer < ROM_SIZE + self.page_mask {
+    self.pc0 = self.pc0.wrapping_add(1);  //Incremented even if it doesn't have it.
+    if self.owns_code(pointer as u16) {
       return self.rom.read(pointer & 0b1111111111) //Take off the high page mask
     }
     0
@@ -86,7 +99,7 @@ impl F3851 {
   /// Read, without updated pc0. Used by relative jump
   pub fn peak_code(&self) -> i8 {
     let pointer = self.pc0 as usize;
-    if pointer >= self.page_mask && pointer < ROM_SIZE + self.page_mask {
+    if self.owns_code(pointer as u16) {
       return self.rom.read::<u8>(pointer & 0b1111111111) as i8 //Take off the high page mask
     }
     0
@@ -96,8 +109,8 @@ impl F3851 {
   /// Used by commands LM, AM, CM, etc reading data from memory
   pub fn next_data(&mut self) -> u8 {
     let pointer = self.dc0 as usize;
-    self.dc0 += 1;  //Incremented even if it doesn't have it.
-    if pointer >= self.page_mask && pointer < ROM_SIZE + self.page_mask {
+    self.dc0 = self.dc0.wrapping_add(1);  //Incremented even if it doesn't have it.
+    if self.owns_data(pointer as u16) {
       return self.rom.read(pointer & 0b1111111111) //Take off the high page mask
     }
     0
@@ -13,7 +13,7 @@
 //! * <https://patentimages.storage.googleapis.com/44/5c/ab/197897f4ecaacb/US4001569.pdf>
 use log::{info,trace};
 
-use crate::shifter;
+use crate::{cpu, shifter};
 
 use super::Register;
 use super::WordSelect;
@@ -52,30 +52,17 @@ pub struct AnR {
 }
 
 
-// BCD add
+// BCD add of a single digit
 fn add(num1: u4, num2: u4, carry: bool) -> (u4, bool) {
-  let mut result = num1.value() + num2.value();
-  if carry {
-    result += 1;
-  }
-  if result >= 10 {
-    (u4::new(result - 10), true)
-  } else {
-    (u4::new(result), false)
-  }
+  //A high digit of 9 passes the carry out of the low digit straight through, like the 0 digits do for the borrow in `sub`
+  let (result, carry) = cpu::bcd_add(0x90 | num1.value(), num2.value(), carry);
+  (u4::new(result & 0xF), carry)
 }
 
-//BCD subtract
+//BCD subtract of a single digit
 fn sub(num1: u4, num2: u4, borrow: bool) -> (u4, bool) {
-  let mut result = num1.value() as isize - num2.value() as isize;
-  if borrow {
-    result -= 1;
-  }
-  if result < 0 {
-    (u4::new((result + 10) as u8), true)
-  } else {
-    (u4::new(result as u8), false)
-  }
+  let (result, borrow) = cpu::bcd_sub(num1.value(), num2.value(), borrow);
+  (u4::new(result & 0xF), borrow)
 }
 
 impl AnR {
@@ -36,6 +36,21 @@ pub struct CnT {
   timer: usize,
 }
 
+/// Copy of the C&T registers, for debuggers and save states
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct CntState {
+  /// Pointer
+  pub pointer: u4,
+  /// Status flags
+  pub status: Indexer16,
+  /// Carry - Can we jump?
+  pub carry: bool,
+  /// Program Counter
+  pub next_address: u8,
+  /// Saved Program Counter for call/return
+  pub saved_address: u8,
+}
+
 impl CnT {
   /// Initialize with defaults
   pub fn new() -> Self {
@@ -49,6 +64,17 @@ impl CnT {
     trace!("Next Address: {:04o} Saved Address: {:04o} Status: {:?} Pointer: {:X} Carry: {}", self.next_address, self.saved_address, self.status, self.pointer, self.carry);
   }
 
+  /// Read all registers and flags at once
+  pub fn state(&self) -> CntState {
+    CntState {
+      pointer: self.pointer,
+      status: self.status,
+      carry: self.carry,
+      next_address: self.next_address,
+      saved_address: self.saved_address,
+    }
+  }
+
   fn increment_pointer(&mut self) {
     if self.pointer == u4::new(0b1111) {
       self.pointer = u4::new(0);
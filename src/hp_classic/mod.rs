@@ -14,9 +14,11 @@ use alloc::vec;
 const ROM_CHIP_LEN: usize = 320;  /// 256 * 10 bits = 2560 bits of ROM data. 2560 / 8 = 320 bytes
 use arbitrary_int::{
   u3,   //ROM #
+  u4,   //Display digit
   u10,  //ROM opcode
 };
 
+use crate::display::{DisplayFrame, HasDisplay};
 use crate::shifter;
 type WordSelect = shifter::Shifter16<14>;
 /// Each of A&R and RAM shift registers consisted of 14 nibbles (56 bits).
@@ -79,3 +81,23 @@ impl<const EXTRA_REGS: usize> Board<EXTRA_REGS> {
   }
 
 }
+
+impl<const EXTRA_REGS: usize> HasDisplay for Board<EXTRA_REGS> {
+  #[inline]
+  fn display_on(&self) -> bool {
+    self.anr.display_on
+  }
+
+  /// The 14 digits of A. A digit of 9 in B blanks the digit in A.
+  fn pixels(&self) -> DisplayFrame {
+    let a = self.anr.a.read_parallel();
+    let b = self.anr.b.read_parallel();
+    DisplayFrame::Digits((0..14).rev().map(|index| {
+      if (b >> (index * 4)) & 0xF == 9 {
+        u4::new(0xF)
+      } else {
+        u4::new(((a >> (index * 4)) & 0xF) as u8)
+      }
+    }).collect())
+  }
+}
@@ -63,26 +63,18 @@ impl ROM {
   }
 
   fn unpack_data(&self, index: u8) -> u10 {
-    // Calculate byte positions
+    // Calculate byte positions. A 10 bit word always spans exactly 2 bytes.
+    // The last word (index 255) starts at bit 2550, which is bit 6 of byte 318, and ends in byte 319.
     let bit_start = (index as usize) * 10;
     let first_byte_index = bit_start / 8;
-    let second_byte_index = first_byte_index + 1;
 
-    // Ensure the byte indices are within bounds
-    if second_byte_index >= self.packed_data.len() {
-        panic!("Index out of bounds");
-    }
-
-    // Extract bytes
+    // Extract bytes. The data is always a full 320 byte chip, so the second byte is always in range.
     let first_byte = self.packed_data[first_byte_index] as u16;
-    let second_byte = self.packed_data[second_byte_index] as u16;
-
-    // Calculate how many bits of the first byte belong to the 10-bit value
-    let bits_from_first_byte = 8 - (bit_start % 8);
+    let second_byte = self.packed_data[first_byte_index + 1] as u16;
 
     // Combine the bits from the two bytes to form the 10-bit value
-    let value = (first_byte >> (8 - bits_from_first_byte)) | (second_byte << bits_from_first_byte) & 0x3FF;
-    
+    let value = ((first_byte | (second_byte << 8)) >> (bit_start % 8)) & 0x3FF;
+
     u10::new(value)
   }
 
@@ -1,13 +1,15 @@
 //! Small Indexable Register Chips
 
 use arbitrary_int::u4;
+use alloc::vec::Vec;
+use crate::snapshot::{self, Snapshot, SnapshotError};
 
 /// ### Small Indexable Register Chip
 ///
 /// Many chips contain small (128 bit or less) registers.
 /// These are not shift registers, because you can access any part of it without shifting.
 /// This is separate from large RAM chips, which need to be indexed as an array.
-#[derive(Default, Clone, Copy, PartialEq)]
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
 pub struct Indexer64 {
     /// The indexer's "memory"
     pub data: u64,
@@ -52,6 +54,88 @@ impl Indexer64 {
     // Set the new value at the specified index
     self.data |= (nibble.value() as u64) << index;
   }
+
+  /// Read nibble, or `None` if `index` is past the 16 nibbles
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Indexer64;
+  /// use arbitrary_int::u4;
+  ///
+  /// let indexer = Indexer64::new();
+  /// assert_eq!(indexer.try_read_nibble(15), Some(u4::new(0)));
+  /// assert_eq!(indexer.try_read_nibble(16), None);
+  /// ```
+  #[inline]
+  pub fn try_read_nibble(&self, index: u8) -> Option<u4> {
+    (index < 16).then(|| self.read_nibble(index))
+  }
+
+  /// Write nibble, or `None` without writing anything if `index` is past the 16 nibbles
+  #[inline]
+  pub fn try_write_nibble(&mut self, index: u8, nibble: u4) -> Option<()> {
+    (index < 16).then(|| self.write_nibble(index, nibble))
+  }
+
+  /// ### To Bytes
+  ///
+  /// Little endian, so nibble 0 is the low half of the first byte. This is the layout saved by `Snapshot`.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Indexer64;
+  ///
+  /// let indexer = Indexer64 { data: 0x0123_4567_89AB_CDEF };
+  /// assert_eq!(indexer.to_bytes(), [0xEF, 0xCD, 0xAB, 0x89, 0x67, 0x45, 0x23, 0x01]);
+  /// assert_eq!(Indexer64::from_bytes(indexer.to_bytes()), indexer);
+  /// ```
+  #[inline]
+  pub fn to_bytes(&self) -> [u8; 8] {
+    self.data.to_le_bytes()
+  }
+
+  /// Inverse of `to_bytes`
+  #[inline]
+  pub fn from_bytes(bytes: [u8; 8]) -> Self {
+    Self {
+      data: u64::from_le_bytes(bytes),
+    }
+  }
+
+  /// Read 4 consecutive nibbles as a word, with the nibble at `nibble_index` being the least significant. Nibbles past the end read as 0.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Indexer64;
+  ///
+  /// let indexer = Indexer64 { data: 0x0000_0000_0000_4321 };
+  /// assert_eq!(indexer.read_word_le(0), 0x4321);
+  /// assert_eq!(indexer.read_word_le(1), 0x0432);
+  /// ```
+  #[inline]
+  pub fn read_word_le(&self, nibble_index: u8) -> u16 {
+    (self.data as u128).checked_shr(nibble_index as u32 * 4).unwrap_or(0) as u16
+  }
+
+  /// Read 4 consecutive nibbles as a word, with the nibble at `nibble_index` being the most significant. Nibbles past the end read as 0.
+  ///
+  /// The 4004 stores register pairs this way, with the even register holding the high nibble.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Indexer64;
+  ///
+  /// let indexer = Indexer64 { data: 0x0000_0000_0000_4321 };
+  /// assert_eq!(indexer.read_word_be(0), 0x1234);
+  /// assert_eq!(indexer.read_word_be(14), 0x0000);
+  /// ```
+  #[inline]
+  pub fn read_word_be(&self, nibble_index: u8) -> u16 {
+    let word = self.read_word_le(nibble_index);
+    //Reverse the nibble order
+    let word = word.swap_bytes();
+    ((word & 0xF0F0) >> 4) | ((word & 0x0F0F) << 4)
+  }
 }
 
 /// 16 bit indexer chip
@@ -100,4 +184,75 @@ impl Indexer16 {
     // Set the new value at the specified index
     self.data |= (nibble.value() as u16) << index;
   }
+
+  /// Read nibble, or `None` if `index` is past the 4 nibbles
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Indexer16;
+  /// use arbitrary_int::u4;
+  ///
+  /// let indexer = Indexer16::new();
+  /// assert_eq!(indexer.try_read_nibble(3), Some(u4::new(0)));
+  /// assert_eq!(indexer.try_read_nibble(4), None);
+  /// ```
+  #[inline]
+  pub fn try_read_nibble(&self, index: u8) -> Option<u4> {
+    (index < 4).then(|| self.read_nibble(index))
+  }
+
+  /// Write nibble, or `None` without writing anything if `index` is past the 4 nibbles
+  #[inline]
+  pub fn try_write_nibble(&mut self, index: u8, nibble: u4) -> Option<()> {
+    (index < 4).then(|| self.write_nibble(index, nibble))
+  }
+
+  /// ### To Bytes
+  ///
+  /// Little endian, so nibble 0 is the low half of the first byte. This is the layout saved by `Snapshot`.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Indexer16;
+  ///
+  /// let indexer = Indexer16 { data: 0xABCD };
+  /// assert_eq!(indexer.to_bytes(), [0xCD, 0xAB]);
+  /// assert_eq!(Indexer16::from_bytes(indexer.to_bytes()), indexer);
+  /// ```
+  #[inline]
+  pub fn to_bytes(&self) -> [u8; 2] {
+    self.data.to_le_bytes()
+  }
+
+  /// Inverse of `to_bytes`
+  #[inline]
+  pub fn from_bytes(bytes: [u8; 2]) -> Self {
+    Self {
+      data: u16::from_le_bytes(bytes),
+    }
+  }
+}
+
+impl Snapshot for Indexer64 {
+  fn save(&self) -> Vec<u8> {
+    self.to_bytes().to_vec()
+  }
+
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+    snapshot::check_length(bytes, 8)?;
+    *self = Self::from_bytes(bytes.try_into().unwrap());
+    Ok(())
+  }
+}
+
+impl Snapshot for Indexer16 {
+  fn save(&self) -> Vec<u8> {
+    self.to_bytes().to_vec()
+  }
+
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+    snapshot::check_length(bytes, 2)?;
+    *self = Self::from_bytes(bytes.try_into().unwrap());
+    Ok(())
+  }
 }
@@ -0,0 +1,97 @@
+//! Keyboard matrix chips
+
+/// ### Keyboard Matrix Scanner
+///
+/// Keys are wired at the crossing of a row and a column line. The scanning chip drives one row at a time, and reads back which columns are connected.
+///
+/// Up to 8 columns are supported, so that a full row can be read as a byte. More fails to compile.
+///
+/// ### Example
+/// ```
+/// use chips::keyboard::Matrix;
+///
+/// let mut keys: Matrix<4, 5> = Matrix::new();
+/// keys.press(1, 0);
+/// keys.press(1, 3);
+/// keys.press(2, 4);
+/// assert_eq!(keys.scan(0), 0);
+/// assert_eq!(keys.scan(1), 0b01001);
+/// assert_eq!(keys.scan(2), 0b10000);
+///
+/// keys.release(1, 3);
+/// assert_eq!(keys.scan(1), 0b00001);
+/// ```
+///
+/// A 9th column doesn't fit in the row byte:
+/// ```compile_fail
+/// use chips::keyboard::Matrix;
+///
+/// let keys: Matrix<4, 9> = Matrix::new();
+/// ```
+#[derive(Clone, Copy, PartialEq)]
+pub struct Matrix<const ROWS: usize, const COLS: usize> {
+  /// Column bits of the pressed keys, for each row
+  pressed: [u8; ROWS],
+}
+
+impl<const ROWS: usize, const COLS: usize> Default for Matrix<ROWS, COLS> {
+  #[inline]
+  fn default() -> Self {
+    const { assert!(COLS <= 8, "A row is read as a byte, so at most 8 columns are supported") };
+    Self {
+      pressed: [0; ROWS],
+    }
+  }
+}
+
+impl<const ROWS: usize, const COLS: usize> Matrix<ROWS, COLS> {
+  /// Create a new keyboard with no keys pressed
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Hold down the key at row and column
+  ///
+  /// Panics if `row` or `col` is past the matrix.
+  ///
+  /// ### Example
+  /// ```should_panic
+  /// use chips::keyboard::Matrix;
+  ///
+  /// let mut keys: Matrix<4, 5> = Matrix::new();
+  /// keys.press(0, 5);
+  /// ```
+  #[inline]
+  pub fn press(&mut self, row: usize, col: usize) {
+    self.pressed[row] |= Self::col_bit(col);
+  }
+
+  /// Let go of the key at row and column
+  ///
+  /// Panics if `row` or `col` is past the matrix.
+  #[inline]
+  pub fn release(&mut self, row: usize, col: usize) {
+    self.pressed[row] &= !Self::col_bit(col);
+  }
+
+  /// Bit of a column in the row byte
+  #[inline]
+  fn col_bit(col: usize) -> u8 {
+    assert!(col < COLS, "Column {} is past the {} columns of the matrix", col, COLS);
+    1 << col
+  }
+
+  /// Drive a row, and read back the column bits of all pressed keys in it
+  ///
+  /// Panics if `row` is past the matrix.
+  #[inline]
+  pub fn scan(&self, row: usize) -> u8 {
+    self.pressed[row]
+  }
+
+  /// Look up how many rows is the generic const ROWS set to.
+  pub const ROWS: usize = ROWS;
+  /// Look up how many columns is the generic const COLS set to.
+  pub const COLS: usize = COLS;
+}
@@ -0,0 +1,91 @@
+//! Latch and Register chips
+
+/// 7400 Series 8 bit transparent latch
+pub type S74X373 = Latch8;
+/// 7400 Series 8 bit clocked register
+pub type S74X377 = Register8;
+
+/// ### 8 bit Transparent Latch
+///
+/// While enable is high, the output follows the input. When enable goes low, the last value is held.
+///
+/// ### Example
+/// ```
+/// use chips::latch::Latch8;
+///
+/// let mut latch = Latch8::new();
+/// latch.latch(0x12, true);
+/// assert_eq!(latch.output(), 0x12);
+/// latch.latch(0x34, true);
+/// assert_eq!(latch.output(), 0x34);  //Transparent
+/// latch.latch(0x56, false);
+/// assert_eq!(latch.output(), 0x34);  //Held
+/// ```
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct Latch8 {
+  /// The latch's "memory"
+  value: u8,
+}
+
+impl Latch8 {
+  /// Create a new latch
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Pass data through if enabled, otherwise hold the previous value
+  #[inline]
+  pub fn latch(&mut self, data: u8, enable: bool) {
+    if enable {
+      self.value = data;
+    }
+  }
+
+  /// Read the output lines
+  #[inline]
+  pub fn output(&self) -> u8 {
+    self.value
+  }
+}
+
+/// ### 8 bit Clocked Register
+///
+/// Data is only captured on a clock edge. Changes on the input between clocks are not seen on the output.
+///
+/// ### Example
+/// ```
+/// use chips::latch::Register8;
+///
+/// let mut register = Register8::new();
+/// assert_eq!(register.output(), 0);
+/// register.clock(0x12);
+/// assert_eq!(register.output(), 0x12);
+/// register.clock(0x34);
+/// assert_eq!(register.output(), 0x34);
+/// ```
+#[derive(Default, Clone, Copy, PartialEq)]
+pub struct Register8 {
+  /// The register's "memory"
+  value: u8,
+}
+
+impl Register8 {
+  /// Create a new register
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Rising clock edge. Capture the data.
+  #[inline]
+  pub fn clock(&mut self, data: u8) {
+    self.value = data;
+  }
+
+  /// Read the output lines
+  #[inline]
+  pub fn output(&self) -> u8 {
+    self.value
+  }
+}
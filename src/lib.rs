@@ -21,11 +21,23 @@ extern crate alloc;
 pub mod and; pub use and::AND;
 pub mod or; pub use or::OR;
 pub mod nand; pub use nand::NAND;
+pub mod mux; pub use mux::S74X157;
+pub mod alu74181; pub use alu74181::S74X181;
+pub mod pla;
+pub mod microcode;
 pub mod decoder; pub use decoder::SegmentDecoder;
+pub mod display;
 pub mod counter; pub use counter::Counter;
+pub mod clock; pub use clock::Clock;
+pub mod scheduler;
+pub mod latch; pub use latch::{Latch8, Register8};
 pub mod shifter; pub use shifter::{Shifter64};
+pub mod bcd; pub use bcd::BcdRegister;
 pub mod rom; pub use rom::ROM;
 pub mod ram; pub use ram::RAM;
+mod memory;
+pub mod membus;
+pub mod keyboard;
 pub mod cpu;
 pub mod hp_classic;
 pub mod mcs4;
@@ -33,6 +45,10 @@ pub mod tms0800;
 pub mod fairchild_f8;
 //pub mod memory_pointer; pub use memory_pointer::MemoryPointer;
 pub mod indexer; pub use indexer::{Indexer16, Indexer64};
+pub mod snapshot; pub use snapshot::Snapshot;
+pub mod util;
+pub mod disasm;
+pub mod asm;
 
 /// Generic ROM / RAM read operations
 pub trait ReadArr {
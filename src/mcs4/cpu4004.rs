@@ -12,10 +12,15 @@
 
 use arbitrary_int::u4;
 use log::{trace,debug};
+use alloc::vec::Vec;
+use alloc::collections::BTreeSet;
+use alloc::{format, string::String};
 use crate::Indexer64;
-use super::{ControlLines, Address, Byte};
+use crate::cpu::HardwareStack;
+use crate::snapshot::{self, Snapshot, SnapshotError};
+use super::{ControlLines, Addr12, Byte};
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 enum ContinueFrom {
   #[default]
   StartOver,
@@ -26,6 +31,131 @@ enum ContinueFrom {
   SetIndirectReg,
 }
 
+/// ### Disassemble
+///
+/// Decodes the instruction at the start of `bytes`, which sits at `address` in ROM. Returns the text, and how many bytes it took.
+///
+/// JCN and ISZ only hold the low 8 bits of their target, so `address` fills in the ROM chip. A truncated two word instruction, or an unknown opcode, shows up as a `DB` byte. Empty `bytes` give empty text taking 0 bytes.
+///
+/// ### Example
+/// ```
+/// use chips::mcs4::cpu4004::disassemble;
+///
+/// assert_eq!(disassemble(&[0xD5], 0), ("LDM 0x5".into(), 1));
+/// assert_eq!(disassemble(&[0x51, 0x23], 0), ("JMS 0x123".into(), 2));
+/// assert_eq!(disassemble(&[0x1C, 0x40], 0x2FE), ("JCN 0xC, 0x340".into(), 2));
+/// assert_eq!(disassemble(&[0x40], 0), ("DB 0x40".into(), 1));
+/// assert_eq!(disassemble(&[], 0), ("".into(), 0));
+/// ```
+pub fn disassemble(bytes: &[u8], address: usize) -> (String, usize) {
+  disassemble_with(bytes, address, |target| format!("0x{:03X}", target))
+}
+
+/// ### Branch Target
+///
+/// Where the JCN, ISZ, JUN or JMS at the start of `bytes` can go to. Other instructions, including JIN which jumps to a register pair, give `None`.
+///
+/// ### Example
+/// ```
+/// use chips::mcs4::cpu4004::branch_target;
+///
+/// assert_eq!(branch_target(&[0x43, 0x21], 0), Some(0x321));
+/// assert_eq!(branch_target(&[0x71, 0x05], 0x1FE), Some(0x205));
+/// assert_eq!(branch_target(&[0xD5], 0), None);
+/// ```
+pub fn branch_target(bytes: &[u8], address: usize) -> Option<usize> {
+  let opcode = *bytes.first()?;
+  let low = *bytes.get(1)? as usize;
+  match opcode >> 4 {
+    0x1 | 0x7 => Some(((address + 2) & 0xF00) | low),
+    0x4 | 0x5 => Some(((opcode as usize & 0xF) << 8) | low),
+    _ => None,
+  }
+}
+
+/// ### Disassemble with Labels
+///
+/// Walks all of `bytes` as a ROM starting at address 0. Every branch and call target within the ROM gets an `L_xxx` label, which is put in front of the instruction at that address, and used in place of the hex address by the instructions going there.
+///
+/// Targets outside of `bytes`, or in the middle of an instruction, are left as hex.
+///
+/// ### Example
+/// ```
+/// use chips::mcs4::cpu4004::disassemble_with_labels;
+///
+/// let listing = disassemble_with_labels(&[0xD5, 0x40, 0x00]);
+/// assert_eq!(listing, [(0, "L_000: LDM 0x5".into()), (1, "JUN L_000".into())]);
+/// ```
+pub fn disassemble_with_labels(bytes: &[u8]) -> Vec<(usize, String)> {
+  let mut starts = BTreeSet::new();
+  let mut targets = BTreeSet::new();
+  let mut offset = 0;
+  while offset < bytes.len() {
+    starts.insert(offset);
+    if let Some(target) = branch_target(&bytes[offset..], offset) {
+      targets.insert(target);
+    }
+    offset += disassemble(&bytes[offset..], offset).1;
+  }
+  let labels: BTreeSet<usize> = targets.intersection(&starts).copied().collect();
+
+  let mut listing = Vec::new();
+  let mut offset = 0;
+  while offset < bytes.len() {
+    let (text, length) = disassemble_with(&bytes[offset..], offset, |target| if labels.contains(&target) {
+      format!("L_{:03X}", target)
+    } else {
+      format!("0x{:03X}", target)
+    });
+    let text = if labels.contains(&offset) { format!("L_{:03X}: {}", offset, text) } else { text };
+    listing.push((offset, text));
+    offset += length;
+  }
+  listing
+}
+
+/// Disassembles, showing branch targets with `name`
+fn disassemble_with(bytes: &[u8], address: usize, name: impl Fn(usize) -> String) -> (String, usize) {
+  let Some(&opcode) = bytes.first() else {
+    return (String::new(), 0);
+  };
+  let modifier = opcode & 0xF;
+  let pair = modifier >> 1;
+  let two_word = matches!(opcode >> 4, 0x1 | 0x4 | 0x5 | 0x7) || opcode & 0xF1 == 0x20;
+  if two_word && bytes.len() < 2 {
+    return (format!("DB 0x{:02X}", opcode), 1);
+  }
+  //JCN and ISZ stay within the ROM chip which the PC is on after both words are read
+  let near = || ((address + 2) & 0xF00) | bytes[1] as usize;
+  let far = || ((modifier as usize) << 8) | bytes[1] as usize;
+  let text = match opcode >> 4 {
+    0x0 => String::from("NOP"),
+    0x1 => format!("JCN 0x{:X}, {}", modifier, name(near())),
+    0x2 if modifier & 1 == 0 => format!("FIM P{}, 0x{:02X}", pair, bytes[1]),
+    0x2 => format!("SRC P{}", pair),
+    0x3 if modifier & 1 == 0 => format!("FIN P{}", pair),
+    0x3 => format!("JIN P{}", pair),
+    0x4 => format!("JUN {}", name(far())),
+    0x5 => format!("JMS {}", name(far())),
+    0x6 => format!("INC R{:X}", modifier),
+    0x7 => format!("ISZ R{:X}, {}", modifier, name(near())),
+    0x8 => format!("ADD R{:X}", modifier),
+    0x9 => format!("SUB R{:X}", modifier),
+    0xA => format!("LD R{:X}", modifier),
+    0xB => format!("XCH R{:X}", modifier),
+    0xC => format!("BBL 0x{:X}", modifier),
+    0xD => format!("LDM 0x{:X}", modifier),
+    0xE => String::from(["WRM", "WMP", "WRR", "WPM", "WR0", "WR1", "WR2", "WR3",
+                         "SBM", "RDM", "RDR", "ADM", "RD0", "RD1", "RD2", "RD3"][modifier as usize]),
+    _ => match modifier {
+      0xE | 0xF => format!("DB 0x{:02X}", opcode),
+      _ => String::from(["CLB", "CLC", "IAC", "CMC", "CMA", "RAL", "RAR", "TCC",
+                         "DAC", "TCS", "STC", "DAA", "KBP", "DCL"][modifier as usize]),
+    },
+  };
+  (text, if two_word { 2 } else { 1 })
+}
+
 /// Intel 4004 chip
 #[derive(Default)]
 pub struct CPU {
@@ -35,11 +165,9 @@ pub struct CPU {
   control_output: ControlLines,
   
   /// Program Counter
-  pc: Address,
+  pc: Addr12,
   
-  stack: [Address; 4],
-  effective_address: usize,
-  push_count: u8, //Undocumented feature
+  stack: HardwareStack<Addr12, 4>,
   
   /// Currently processing opcode
   opcode: Byte,
@@ -49,6 +177,9 @@ pub struct CPU {
   acc: u4,
   
   regs: Indexer64,
+
+  /// What to do with the unused opcode 0xFF
+  pub on_invalid: crate::cpu::InvalidPolicy,
 }
 
 impl CPU {
@@ -60,6 +191,20 @@ impl CPU {
     }
   }
   
+  /// Reset line. Clears all registers, flags and the stack, and jumps to 0. `on_invalid` is kept.
+  pub fn reset(&mut self) {
+    *self = Self {
+      on_invalid: self.on_invalid,
+      ..Self::new()
+    };
+  }
+
+  /// Is the CPU in the middle of a two word instruction?
+  #[inline]
+  pub(super) fn mid_instruction(&self) -> bool {
+    !matches!(self.continue_from, ContinueFrom::StartOver)
+  }
+
   /// Print debug data of all registers
   pub(super) fn print(&self) {
     if matches!(self.continue_from, ContinueFrom::StartOver) {
@@ -68,18 +213,56 @@ impl CPU {
     }
   }
 
+  /// Program counter
+  #[inline]
+  pub fn get_pc(&self) -> u16 {
+    self.pc.raw_value()
+  }
+
+  /// Jump straight to `addr`, such as a boot vector, or when resuming from a save state. Only the low 12 bits are used.
+  #[inline]
+  pub fn set_pc(&mut self, addr: u16) {
+    self.pc = Addr12::wrap(addr);
+  }
+
+  /// All 16 index registers, R0 through RF
+  pub fn registers(&self) -> [u4; 16] {
+    core::array::from_fn(|index| self.regs.read_nibble(index as u8))
+  }
+
+  /// Accumulator
+  #[inline]
+  pub fn acc(&self) -> u4 {
+    self.acc
+  }
+
+  /// Carry flag. After a subtraction, set means there was no borrow.
+  #[inline]
+  pub fn carry(&self) -> bool {
+    self.carry
+  }
+
+  /// Current level of the test flag, as checked by JCN
+  #[inline]
+  pub fn test(&self) -> bool {
+    self.test
+  }
+
   /// Set test flag. This is the only way chips could signal the 4004 directly.
   pub fn set_test_flag(&mut self, test: bool) {
     self.test = test;
   }
 
+  /// `val` is a 4 bit sum with its carry in bit 4. Subtraction adds the complement, so there carry means no borrow.
   fn set_acc_carry(&mut self, val: u8) {
     self.carry = val > 0xF;
     self.acc = u4::new(val & 0xF);
   }
   
   /// A1, A2, A3 clock - Send address
-  pub(super) fn get_addr(&self) -> Address {
+  ///
+  /// Each instruction cycle is three phases: `set_address_phase`, `fetch_phase` and `execute_phase`, called in that order. `Board::run_cycle` drives them for a standard board, but custom bus logic can be put in between.
+  pub fn set_address_phase(&self) -> Addr12 {
     if let ContinueFrom::SetIndirectReg = self.continue_from {
       //Special case for FIN command
       self.pc.with_high(self.regs.read_nibble(0))
@@ -90,7 +273,7 @@ impl CPU {
   }
 
   /// M1 and M2 clock - Receive opcode. Returns the control lines being on or off, depending if there is an io instruction coming up.
-  pub(super) fn set_opcode(&mut self, opcode: Byte) -> ControlLines {
+  pub fn fetch_phase(&mut self, opcode: Byte) -> ControlLines {
     self.opcode = opcode;
     if matches!(self.continue_from, ContinueFrom::StartOver) && self.opcode.high().value() == 0xE {
       self.control_output
@@ -100,12 +283,14 @@ impl CPU {
   }
 
   /// X1 and X2 clock - Execute. It can read from memory, or write to memory
-  pub(super) fn run_cycle(&mut self, data_in: u4) -> super::ExecuteOut {
+  ///
+  /// `data_in` is whatever the selected ROM or RAM chips put on the bus for an I/O read.
+  pub fn execute_phase(&mut self, data_in: u4) -> super::ExecuteOut {
     let mut data_out = super::ExecuteOut::Nothing;
     
     //FIN and JIN require pc to not change. Everyone else should increment. Including BBL!
     if !(self.opcode.high().value() == 3 && matches!(self.continue_from, ContinueFrom::StartOver)) {
-      self.pc = Address::new_with_raw_value((self.pc.raw_value() + 1) & 0xFFF);  //If we reach 0xFFF, we wrap back around
+      self.pc = self.pc.next();  //If we reach 0xFFF, we wrap back around
     }
     
     match self.continue_from {
@@ -182,10 +367,8 @@ impl CPU {
           },
           0xC => { trace!("BBL Acc={:X}", self.opcode.low()); //Branch Back and Load (Return from function)
             self.acc = self.opcode.low();
-            if self.push_count > 0 {  //Undocumented feature...
-              self.push_count -= 1;
-              self.effective_address = ((self.effective_address as i8 - 1) & 0x3) as usize;  //Loops back around
-              self.pc = self.stack[self.effective_address]; //Reset to address from stack
+            if let Some(pc) = self.stack.pop() {  //Undocumented feature... returning without a call is ignored
+              self.pc = pc; //Reset to address from stack
               trace!("PC:{:X}", self.pc.raw_value());
             }
           },
@@ -264,6 +447,7 @@ impl CPU {
               self.carry = true;
             },
             0xB => { trace!("DAA"); //Decimal Adjust Accumulator
+              //Per the MCS-4 manual, add 6 if the accumulator is over 9 or carry is set. Carry only ever gets set, never cleared.
               let mut val = self.acc.value();
               if val > 9 || self.carry {
                 val += 6;
@@ -298,7 +482,7 @@ impl CPU {
               };
             },
             0xE => { trace!("Invalid code 0xFE run by exerciser. Does nothing."); },
-            _ => unreachable!("Unknown opcode: F{:X}", modifier),
+            _ => self.on_invalid.handle(0xFF),
           },
           _ => trace!("NOP"), //0x0
         }
@@ -320,10 +504,8 @@ impl CPU {
       },
       ContinueFrom::CallFar => {
         trace!("Call to {:X}{:X}{:X}", self.previous_modifier, self.opcode.high(), self.opcode.low());
-        self.stack[self.effective_address] = self.pc;
-        self.effective_address = (self.effective_address + 1) & 0x3;  //Loops around
-        self.push_count += 1;
-        self.pc = Address::builder()
+        self.stack.push(self.pc);
+        self.pc = Addr12::builder()
                         .with_chip_index(self.previous_modifier)
                         .with_high(self.opcode.high())
                         .with_low(self.opcode.low())
@@ -332,7 +514,7 @@ impl CPU {
       },
       ContinueFrom::JumpFar => {
         trace!("Jump to {:X}{:X}{:X}", self.previous_modifier, self.opcode.high(), self.opcode.low());
-        self.pc = Address::builder()
+        self.pc = Addr12::builder()
                         .with_chip_index(self.previous_modifier)
                         .with_high(self.opcode.high())
                         .with_low(self.opcode.low())
@@ -351,4 +533,70 @@ impl CPU {
     data_out
   }
 
-}
\ No newline at end of file
+}
+
+/// Byte layout: continue from, previous modifier, control lines, pc (2), stack (4 x 2), effective address, push count, opcode, carry, test, acc, registers (8)
+impl Snapshot for CPU {
+  fn save(&self) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(27);
+    bytes.push(self.continue_from as u8);
+    bytes.push(self.previous_modifier.value());
+    bytes.push(self.control_output.raw_value());
+    bytes.extend_from_slice(&self.pc.raw_value().to_le_bytes());
+    for address in self.stack.entries {
+      bytes.extend_from_slice(&address.raw_value().to_le_bytes());
+    }
+    bytes.push(self.stack.index as u8);
+    bytes.push(self.stack.push_count);
+    bytes.push(self.opcode.raw_value());
+    bytes.push(self.carry as u8);
+    bytes.push(self.test as u8);
+    bytes.push(self.acc.value());
+    bytes.extend_from_slice(&self.regs.data.to_le_bytes());
+    bytes
+  }
+
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+    snapshot::check_length(bytes, 27)?;
+    let address = |index: usize| -> Result<Addr12, SnapshotError> {
+      let raw = u16::from_le_bytes([bytes[index], bytes[index + 1]]);
+      if raw > 0xFFF {
+        return Err(SnapshotError::InvalidData);
+      }
+      Ok(Addr12::new_with_raw_value(raw))
+    };
+    let nibble = |index: usize| -> Result<u4, SnapshotError> {
+      u4::try_new(bytes[index]).map_err(|_| SnapshotError::InvalidData)
+    };
+    let continue_from = match bytes[0] {
+      0 => ContinueFrom::StartOver,
+      1 => ContinueFrom::JumpConditional,
+      2 => ContinueFrom::CallFar,
+      3 => ContinueFrom::JumpFar,
+      4 => ContinueFrom::SetReg,
+      5 => ContinueFrom::SetIndirectReg,
+      _ => return Err(SnapshotError::InvalidData),
+    };
+    if bytes[13] > 3 {
+      return Err(SnapshotError::InvalidData);
+    }
+    *self = Self {
+      continue_from,
+      previous_modifier: nibble(1)?,
+      control_output: ControlLines::new_with_raw_value(bytes[2]),
+      pc: address(3)?,
+      stack: HardwareStack {
+        entries: [address(5)?, address(7)?, address(9)?, address(11)?],
+        index: bytes[13] as usize,
+        push_count: bytes[14],
+      },
+      opcode: Byte::new_with_raw_value(bytes[15]),
+      carry: bytes[16] != 0,
+      test: bytes[17] != 0,
+      acc: nibble(18)?,
+      regs: Indexer64 { data: u64::from_le_bytes(bytes[19..27].try_into().unwrap()) },
+      on_invalid: self.on_invalid,  //Configuration, not state
+    };
+    Ok(())
+  }
+}
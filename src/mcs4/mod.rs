@@ -4,17 +4,23 @@ pub mod cpu4004;
 pub mod rom4001;
 pub mod ram4002;
 pub mod shifter4003;
+#[cfg(feature = "helpers")]
+pub mod runner;
+#[cfg(feature = "helpers")]
+pub use runner::{run_mcs4, RunOutcome};
 
 use bitbybit::bitfield;
 use arbitrary_int::{u2, u4};
 use alloc::vec;
+use crate::{Indexer16, Indexer64};
 
 /// Memory Control, coming from CPU, read by ROM and RAM.
 #[bitfield(u8, default: 0)]
-struct ControlLines {
-  //0..=3
+pub struct ControlLines {
+  /// CM-RAM lines, one for each RAM bank, chosen by DCL
   #[bit(0, rw)]
   ram: [bool; 4],
+  /// CM-ROM line
   #[bit(4, rw)]
   rom: bool,
 }
@@ -31,9 +37,9 @@ pub struct Byte {
   low: u4,
 }
 
-/// 12 bit instruction address
+/// 12 bit instruction address, shared by the CPU program counter and the ROM chips
 #[bitfield(u16, default: 0)]
-pub struct Address {
+pub struct Addr12 {
   /// Which ROM chip?
   #[bits(8..=11, rw)]
   chip_index: u4,
@@ -47,9 +53,59 @@ pub struct Address {
   low: u4,
 }
 
+/// Older name of `Addr12`
+pub type Address = Addr12;
 
+impl Addr12 {
+  /// ### Wrap
+  ///
+  /// Address from any 16 bit value, keeping only the low 12 bits.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::mcs4::Addr12;
+  ///
+  /// assert_eq!(Addr12::wrap(0x1234).raw_value(), 0x234);
+  /// ```
+  #[inline]
+  pub fn wrap(raw: u16) -> Self {
+    Self::new_with_raw_value(raw & 0xFFF)
+  }
+
+  /// Address from the ROM chip and the byte inside of it
+  #[inline]
+  pub fn from_parts(chip_index: u4, offset: u8) -> Self {
+    Self::new_with_raw_value((chip_index.value() as u16) << 8 | offset as u16)
+  }
+
+  /// Which byte inside of the ROM chip? This is the high and low addresses together.
+  #[inline]
+  pub fn offset(self) -> u8 {
+    self.raw_value() as u8
+  }
+
+  /// ### Next Address
+  ///
+  /// The address after this one. Addresses are 12 bits, so 0xFFF wraps back around to 0.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::mcs4::Addr12;
+  ///
+  /// assert_eq!(Addr12::new_with_raw_value(0x0FF).next().raw_value(), 0x100);
+  /// assert_eq!(Addr12::new_with_raw_value(0xFFF).next().raw_value(), 0x000);
+  /// ```
+  #[inline]
+  pub fn next(self) -> Self {
+    Self::wrap(self.raw_value().wrapping_add(1))
+  }
+}
+
+
+/// What the CPU sends out to the ROM and RAM chips after the execute phase
 #[derive(Clone,Copy)]
-enum ExecuteOut {
+pub enum ExecuteOut {
+  /// Nothing for the other chips to do
   Nothing,
   /// Chip index=2 bit, Reg Index=2 bit, Char Index=4 bit
   SRC(Byte),
@@ -57,6 +113,56 @@ enum ExecuteOut {
   Write(Byte),
 }
 
+/// The 4004 talks to the ROM and RAM chips directly on the board, so it is the whole board which runs instructions.
+impl crate::cpu::Chip<()> for Board {
+  /// Run instruction cycles until a full instruction is done. Each instruction cycle is 8 clock cycles, and two word instructions take two of them.
+  fn run_instruction(&mut self, _io: &mut ()) -> u32 {
+    let mut cycles = 0;
+    loop {
+      self.run_cycle();
+      cycles += 8;
+      if !self.cpu.mid_instruction() {
+        return cycles;
+      }
+    }
+  }
+  #[inline]
+  fn pc(&self, _io: &()) -> u16 {
+    self.cpu.get_pc()
+  }
+  /// Resets the CPU. ROM and RAM contents are kept.
+  #[inline]
+  fn reset(&mut self) {
+    self.cpu.reset();
+  }
+  #[inline]
+  fn name(&self) -> &'static str {
+    "Intel 4004"
+  }
+}
+
+/// Reasons a board could not be built
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardError {
+  /// No ROM data was given
+  EmptyRom,
+  /// The ROM data needs more than the 16 ROM chips the 4004 can address
+  RomTooLarge {
+    /// Number of bytes given
+    length: usize,
+  },
+  /// The ROM data does not split evenly into 256 byte ROM chips
+  RomNotAligned {
+    /// Number of bytes given
+    length: usize,
+  },
+  /// More RAM chips were asked for than the 4 a bank can select
+  TooManyRams {
+    /// Number of RAM chips asked for
+    count: u8,
+  },
+}
+
 /// MCS-4 Board
 pub struct Board {
   pub roms: vec::Vec<rom4001::ROM>,
@@ -66,12 +172,35 @@ pub struct Board {
 
 impl Board {
   /// Create a new board
+  ///
+  /// Panics if the data can't fill whole ROM chips. See `try_new`.
   pub fn new(data: vec::Vec<u8>, ram_count: u8) -> Self {
+    match Self::try_new(data, ram_count) {
+      Ok(board) => board,
+      Err(error) => panic!("Invalid MCS-4 board: {:?}", error),
+    }
+  }
+
+  /// Create a new board, checking that `data` fills between 1 and 16 ROM chips, and that there are at most 4 RAM chips.
+  pub fn try_new(data: vec::Vec<u8>, ram_count: u8) -> Result<Self, BoardError> {
+    if data.is_empty() {
+      return Err(BoardError::EmptyRom);
+    }
+    if data.len() % 0x100 != 0 {
+      return Err(BoardError::RomNotAligned { length: data.len() });
+    }
+    if data.len() > 16 * 0x100 {
+      return Err(BoardError::RomTooLarge { length: data.len() });
+    }
+    if ram_count > 4 {
+      return Err(BoardError::TooManyRams { count: ram_count });
+    }
+
     let mut roms = vec![];
-    let mut page = 0;
-    for chunk in data.chunks(0x100) {
-      roms.push(rom4001::ROM::new(chunk.to_vec().try_into().unwrap(), u4::new(page)));
-      page += 1;
+    for (page, chunk) in data.chunks_exact(0x100).enumerate() {
+      let mut rom = [0; 0x100];
+      rom.copy_from_slice(chunk);
+      roms.push(rom4001::ROM::new(rom, u4::new(page as u8)));
     }
     
     let mut rams = vec![];
@@ -79,35 +208,117 @@ impl Board {
       rams.push(ram4002::RAM::new(u2::new(page)));
     }
     
-    Self {
+    Ok(Self {
       roms: roms,
       rams: rams,
       cpu: cpu4004::CPU::new(),
-    }
+    })
   }
   
+  /// Create a new board from a raw ROM dump, such as a `.bin` file.
+  ///
+  /// Dumps don't always fill the last ROM chip, so it gets padded with zeros.
+  pub fn from_rom_file(bytes: &[u8], ram_count: u8) -> Result<Self, BoardError> {
+    if bytes.is_empty() {
+      return Err(BoardError::EmptyRom);
+    }
+    if bytes.len() > 16 * 0x100 {
+      return Err(BoardError::RomTooLarge { length: bytes.len() });
+    }
+    let mut data = bytes.to_vec();
+    data.resize(bytes.len().next_multiple_of(0x100), 0);
+    Self::try_new(data, ram_count)
+  }
+
+  /// Debugger read of a RAM character, without going through the CPU
+  #[inline]
+  pub fn peek_ram(&self, chip: usize, reg: u2, char: u4) -> u4 {
+    self.rams[chip].registers[reg.value() as usize].characters.read_nibble(char.value())
+  }
+
+  /// Debugger write of a RAM character, without going through the CPU
+  #[inline]
+  pub fn poke_ram(&mut self, chip: usize, reg: u2, char: u4, value: u4) {
+    self.rams[chip].registers[reg.value() as usize].characters.write_nibble(char.value(), value);
+  }
+
+  /// Debugger read of every register of a RAM chip, as (characters, status) pairs
+  pub fn dump_ram(&self, chip: usize) -> [(Indexer64, Indexer16); 4] {
+    let ram = &self.rams[chip];
+    core::array::from_fn(|reg| {
+      let reg = u2::new(reg as u8);
+      (ram.read_full_character(reg), ram.read_full_status(reg))
+    })
+  }
+
+  /// Debugger read of a ROM byte, from whichever chip the address selects. Chip indexes past the last ROM wrap around, the same as CPU fetches.
+  pub fn peek_rom(&self, address: Addr12) -> u8 {
+    let address = self.wrap_chip_index(address);
+    self.roms.iter().fold(0, |byte, rom| byte | rom.get_opcode(address))
+  }
+
+  /// The ROM chip index wraps around the number of ROM chips. The Evaluation Kit fails without this wrapping.
+  #[inline]
+  fn wrap_chip_index(&self, address: Addr12) -> Addr12 {
+    let chip_index = (address.chip_index().value() as usize).checked_rem(self.roms.len()).unwrap_or(0);
+    address.with_chip_index(u4::new(chip_index as u8))
+  }
+
+  /// Debugger read of the CPU accumulator
+  #[inline]
+  pub fn acc(&self) -> u4 {
+    self.cpu.acc()
+  }
+
+  /// Debugger read of the CPU carry flag
+  #[inline]
+  pub fn carry(&self) -> bool {
+    self.cpu.carry()
+  }
+
+  /// Debugger read of the CPU test flag
+  #[inline]
+  pub fn test(&self) -> bool {
+    self.cpu.test()
+  }
+
+  /// Run one full instruction, like `Chip::run_instruction`, and report where it was and what it was.
+  pub fn step(&mut self) -> crate::cpu::StepInfo {
+    let pc_before = self.cpu.get_pc();
+    let opcode = self.peek_rom(Addr12::new_with_raw_value(pc_before));
+    let cycles = crate::cpu::Chip::run_instruction(self, &mut ());
+    crate::cpu::StepInfo { pc_before, opcode, cycles }
+  }
+
+  /// Run `count` full instructions, each taking one or two instruction cycles. Returns the total clock cycles taken.
+  pub fn run_instructions(&mut self, count: usize) -> u64 {
+    crate::cpu::run_for(self, &mut (), count)
+  }
+
+  /// Drive the CPU test line, the way a peripheral such as a keyboard strobe would
+  #[inline]
+  pub fn signal_test(&mut self, level: bool) {
+    self.cpu.set_test_flag(level);
+  }
+
+  /// Current level of the CPU test line
+  #[inline]
+  pub fn test_line(&self) -> bool {
+    self.cpu.test()
+  }
+
   /// Run an instruction cycle with all chips
   pub fn run_cycle(&mut self) {
     //CPU sends address to ROM
     //A1, A2, A3
     //ROM sends data to everyone
     //M1 and M2
-    let mut address = self.cpu.get_addr();
-    //Evaluation Kit fails without this wrapping..
-    address = address.with_chip_index(u4::new(((address.chip_index().value() as usize) % self.roms.len()) as u8));
-    
-    let opcode = Byte::new_with_raw_value({
-      let mut opcode = 0;
-      for i in 0..self.roms.len() {
-        opcode |= self.roms[i].get_opcode(address);
-      }
-      opcode
-    });
+    let opcode = Byte::new_with_raw_value(self.peek_rom(self.cpu.set_address_phase()));
     
     self.cpu.print();
     log::trace!("Opcode: {:02X}", opcode.raw_value());
     
-    let control_lines = self.cpu.set_opcode(opcode);
+    let control_lines = self.cpu.fetch_phase(opcode);
 
     //The control line will signal which ROM/RAM chip should listen to the command.
     //CPU executes, and ROM or RAM exchange info with CPU
@@ -123,7 +334,7 @@ impl Board {
         read |= self.rams[i].io_read(opcode.low());
       }
     }
-    let command = self.cpu.run_cycle(read);
+    let command = self.cpu.execute_phase(read);
     match command {
       ExecuteOut::SRC(data) => {
         for i in 0..self.roms.len() {
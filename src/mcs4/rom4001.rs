@@ -1,20 +1,7 @@
 //! The 4001 ROM was limited to only 256 bytes of data. It was also unusual for having 4 io ports for the CPU to read/write with peripheral devices. Up to 16 ROM could be connected together, allowing for a maximum of 4 KB of binary code to be stored.
 
-use arbitrary_int::u4;
+use arbitrary_int::{u2, u4};
 use log::trace;
-use bitbybit::bitfield;
-
-/// 8 bit ROM address
-#[bitfield(u8, default: 0)]
-struct Address {
-  /// Which high address inside of the ROM chip?
-  #[bits(4..=7, rw)]
-  high: u4,
-
-  /// Which low address inside of the ROM chip?
-  #[bits(0..=3, rw)]
-  low: u4,
-}
 
 /// Intel 4001 chip
 pub struct ROM {
@@ -23,8 +10,12 @@ pub struct ROM {
   active: bool,
   /// 0x100 or 256 bytes of ROM data
   data: [u8; 0x100],
-  /// 4 bits of io ports
+  /// 4 bits of io ports, as last written by the CPU
   pub ports: u4,
+  /// Which io port lines are inputs, instead of outputs
+  input_mask: u4,
+  /// Levels driven onto the input lines by peripherals
+  inputs: u4,
 }
 
 impl ROM {
@@ -36,17 +27,37 @@ impl ROM {
       active: page_mask == u4::new(0),
       data,
       ports: Default::default(),
+      input_mask: Default::default(),
+      inputs: Default::default(),
     }
   }
 
+  /// Choose which io port lines are inputs. Set bits are inputs, clear bits are outputs. All lines start as outputs.
+  ///
+  /// The real 4001 had this chosen by the mask when the ROM was made.
+  #[inline]
+  pub fn configure_ports(&mut self, input_mask: u4) {
+    self.input_mask = input_mask;
+  }
+
+  /// Drive an input line from a peripheral, such as a keyboard
+  #[inline]
+  pub fn set_input_line(&mut self, line: u2, level: bool) {
+    let bit = u4::new(1 << line.value());
+    self.inputs = if level { self.inputs | bit } else { self.inputs & !bit };
+  }
+
+  /// What the CPU reads from the io ports: input lines as driven, and output lines as written
+  #[inline]
+  pub fn read_ports(&self) -> u4 {
+    (self.ports & !self.input_mask) | (self.inputs & self.input_mask)
+  }
+
   /// Clock A1, A2, A3 - Set address
   /// Clock M1, M2 - Send opcode
-  pub fn get_opcode(&self, addr: super::Address) -> u8 {
+  pub fn get_opcode(&self, addr: super::Addr12) -> u8 {
     if self.page_mask == addr.chip_index() {
-      let address = Address::DEFAULT
-                  .with_low(addr.low())
-                  .with_high(addr.high());
-      self.data[address.raw_value() as usize]
+      self.data[addr.offset() as usize]
     } else {
       0
     }
@@ -60,8 +71,8 @@ impl ROM {
   /// Clock X2 - I/O instruction. It can send data back to the CPU
   pub fn io_read(&self, modifier: u4) -> u4 {
     if self.active && matches!(modifier.value(), 0xA) {
-      trace!("RDR {}", self.ports); //Read ROM Port to CPU
-      self.ports
+      trace!("RDR {}", self.read_ports()); //Read ROM Port to CPU
+      self.read_ports()
     } else {
       u4::new(0)
     }
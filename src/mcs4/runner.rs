@@ -0,0 +1,57 @@
+//! Ready made harness for running MCS-4 programs, wired the same way as the Evaluation Kit with 4001-0009
+
+use super::Board;
+
+/// How a program run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunOutcome {
+  /// Did the program reach a success address before running out of cycles?
+  pub success: bool,
+  /// Program counter when the run stopped
+  pub pc: u16,
+  /// Instruction cycles which were run
+  pub cycles: usize,
+}
+
+/// ### Run an MCS-4 Program
+///
+/// Builds a board with 4001 ROMs holding `binary`, `ram_count` 4002 RAMs and a 4004 CPU, then runs up to `max_cycles` instruction cycles.
+///
+/// Like the Evaluation Kit, the CPU test line is wired to output port bit 0 of the first RAM chip.
+///
+/// The run counts as a success once `is_success` returns true for the program counter, checked after every instruction cycle.
+///
+/// Panics if the board can't be built, see `Board::from_rom_file`.
+///
+/// ### Example
+/// ```
+/// use chips::mcs4;
+///
+/// //JUN 0x050
+/// let outcome = mcs4::run_mcs4(&[0x40, 0x50], 1, 10, |pc| pc == 0x050);
+/// assert!(outcome.success);
+/// assert_eq!(outcome.pc, 0x050);
+/// assert_eq!(outcome.cycles, 2);
+/// ```
+pub fn run_mcs4(binary: &[u8], ram_count: u8, max_cycles: usize, is_success: impl Fn(u16) -> bool) -> RunOutcome {
+  let mut board = match Board::from_rom_file(binary, ram_count) {
+    Ok(board) => board,
+    Err(error) => panic!("Invalid MCS-4 board: {:?}", error),
+  };
+
+  for cycles in 1..=max_cycles {
+    board.run_cycle();
+
+    //This is wired in the test board
+    if let Some(ram) = board.rams.first() {
+      let level = ram.ports.value() & 1 == 1;
+      board.signal_test(level);
+    }
+
+    let pc = board.cpu.get_pc();
+    if is_success(pc) {
+      return RunOutcome { success: true, pc, cycles };
+    }
+  }
+  RunOutcome { success: false, pc: board.cpu.get_pc(), cycles: max_cycles }
+}
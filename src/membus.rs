@@ -0,0 +1,118 @@
+//! Memory mapped bus
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::mem::size_of;
+use core::ops::Range;
+use crate::{cpu::MemoryIO, RAM, ROM};
+
+/// A chip which can be mapped onto the bus. Offsets are relative to the start of the chip's region.
+pub trait Device {
+  /// Read a byte from the chip
+  fn read(&self, offset: usize) -> u8;
+  /// Write a byte to the chip. Read only chips ignore this.
+  fn write(&mut self, offset: usize, value: u8);
+  /// Bytes in the chip. A region mapped to it can't be any longer.
+  fn size(&self) -> usize;
+}
+
+impl<const LENGTH: usize> Device for ROM<LENGTH> {
+  #[inline]
+  fn read(&self, offset: usize) -> u8 {
+    ROM::read(self, offset)
+  }
+  #[inline]
+  fn write(&mut self, _offset: usize, _value: u8) {}
+  #[inline]
+  fn size(&self) -> usize {
+    LENGTH
+  }
+}
+
+impl<const LENGTH: usize> Device for RAM<LENGTH> {
+  #[inline]
+  fn read(&self, offset: usize) -> u8 {
+    RAM::read(self, offset)
+  }
+  #[inline]
+  fn write(&mut self, offset: usize, value: u8) {
+    RAM::write(self, offset, value);
+  }
+  #[inline]
+  fn size(&self) -> usize {
+    LENGTH
+  }
+}
+
+/// ### Memory Mapped Bus
+///
+/// Routes each CPU memory access to whichever chip is mapped at that address. If regions overlap, the one mapped first wins.
+///
+/// Reading unmapped memory returns 0xFF, like a floating data bus. Writing to it does nothing.
+///
+/// ### Example
+/// ```
+/// use chips::{membus::Bus, RAM, ROM};
+///
+/// let mut bus = Bus::new();
+/// bus.map(0x0000..0x0004, ROM::new([1, 2, 3, 4]));
+/// bus.map(0x8000..0x8100, RAM::<0x100>::new());
+///
+/// bus.write(0x8010, 0x42);
+/// assert_eq!(bus.read(0x8010), 0x42);
+/// assert_eq!(bus.read(0x0002), 3);
+/// assert_eq!(bus.read(0x4000), 0xFF);
+/// ```
+#[derive(Default)]
+pub struct Bus {
+  /// Address range, and the chip mapped to it
+  regions: Vec<(Range<usize>, Box<dyn Device>)>,
+}
+
+impl Bus {
+  /// Create an empty bus
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Map a chip onto an address range
+  ///
+  /// Panics if the range is longer than the chip.
+  pub fn map(&mut self, range: Range<usize>, device: impl Device + 'static) {
+    assert!(range.len() <= device.size(), "Range {:#X}..{:#X} is longer than the {} byte chip", range.start, range.end, device.size());
+    self.regions.push((range, Box::new(device)));
+  }
+
+  /// Read a byte from whichever chip is mapped at the address
+  pub fn read(&self, address: usize) -> u8 {
+    self.regions.iter()
+      .find(|(range, _)| range.contains(&address))
+      .map_or(0xFF, |(range, device)| device.read(address - range.start))
+  }
+
+  /// Write a byte to whichever chip is mapped at the address
+  pub fn write(&mut self, address: usize, value: u8) {
+    if let Some((range, device)) = self.regions.iter_mut().find(|(range, _)| range.contains(&address)) {
+      device.write(address - range.start, value);
+    }
+  }
+}
+
+impl MemoryIO<u16> for Bus {
+  fn read_mem<T: crate::ReadArr>(&self, address: u16) -> T {
+    let mut bytes = [0; 8];
+    for (index, byte) in bytes.iter_mut().take(size_of::<T>()).enumerate() {
+      *byte = self.read(address.wrapping_add(index as u16) as usize);
+    }
+    T::read(&bytes)
+  }
+
+  fn write_mem<T: crate::WriteArr>(&mut self, address: u16, value: T) {
+    let mut bytes = [0; 8];
+    T::write(&mut bytes, value);
+    for (index, byte) in bytes.iter().take(size_of::<T>()).enumerate() {
+      self.write(address.wrapping_add(index as u16) as usize, *byte);
+    }
+  }
+}
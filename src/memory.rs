@@ -0,0 +1,77 @@
+//! Pieces shared by the RAM and ROM chips
+
+use alloc::boxed::Box;
+use core::cell::Cell;
+
+/// Reads and writes of each address of a chip, when profiling
+///
+/// Reads only borrow the chip, so the counts are kept in a `Cell`.
+#[derive(Default)]
+pub(crate) struct AccessCounts {
+  counts: Option<Box<Cell<[u32]>>>,
+}
+
+impl AccessCounts {
+  /// Start counting `LENGTH` addresses, all from 0
+  #[inline]
+  pub(crate) fn enable<const LENGTH: usize>(&mut self) {
+    self.counts = Some(Box::new(Cell::new([0; LENGTH])));
+  }
+
+  /// Count an access to every byte from `addr` to `addr + len`, if profiling is enabled
+  #[inline]
+  pub(crate) fn count(&self, addr: usize, len: usize) {
+    if let Some(counts) = &self.counts {
+      let counts = counts.as_slice_of_cells();
+      let end = (addr + len).min(counts.len());
+      for count in &counts[addr..end] {
+        count.set(count.get().saturating_add(1));
+      }
+    }
+  }
+
+  /// The counts, if profiling is enabled
+  #[inline]
+  pub(crate) fn get(&mut self) -> Option<&[u32]> {
+    self.counts.as_mut().map(|counts| &*counts.get_mut())
+  }
+}
+
+/// Serialize the chip data as bytes
+#[cfg(feature = "serde")]
+pub(crate) fn serialize_data<S: serde::Serializer>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+  serializer.serialize_bytes(data)
+}
+
+/// Deserialize chip data from bytes, or a sequence of bytes for formats without a bytes type. The length must match exactly.
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_data<'de, D: serde::Deserializer<'de>, const LENGTH: usize>(deserializer: D) -> Result<[u8; LENGTH], D::Error> {
+  use serde::de::{Error, SeqAccess, Visitor};
+
+  struct DataVisitor<const LENGTH: usize>;
+
+  impl<'de, const LENGTH: usize> Visitor<'de> for DataVisitor<LENGTH> {
+    type Value = [u8; LENGTH];
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+      write!(formatter, "{} bytes", LENGTH)
+    }
+
+    fn visit_bytes<E: Error>(self, bytes: &[u8]) -> Result<Self::Value, E> {
+      bytes.try_into().map_err(|_| E::invalid_length(bytes.len(), &self))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+      let mut data = [0; LENGTH];
+      for (index, byte) in data.iter_mut().enumerate() {
+        *byte = seq.next_element()?.ok_or_else(|| A::Error::invalid_length(index, &self))?;
+      }
+      if seq.next_element::<u8>()?.is_some() {
+        return Err(A::Error::invalid_length(LENGTH + 1, &self));
+      }
+      Ok(data)
+    }
+  }
+
+  deserializer.deserialize_bytes(DataVisitor::<LENGTH>)
+}
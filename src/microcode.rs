@@ -0,0 +1,105 @@
+//! Microcode sequencer
+
+use alloc::vec::Vec;
+
+/// ### Microcode Sequencer
+///
+/// Table driven alternative to a hand written `match` decoder. Each opcode has a row of control words, which are stepped through one at a time by the micro program counter.
+///
+/// A control word is a `u32`. Every set bit is a micro-op: bit 0 is micro-op 0, bit 1 is micro-op 1, and so on. What each micro-op does is up to the user, in the callback passed to `step`. Micro-ops of one control word fire in order, from the lowest bit up.
+///
+/// An instruction ends after the last control word in its row. The caller then fetches the next opcode, and passes it to `decode`.
+///
+/// ### Example
+/// ```
+/// use chips::microcode::MicroSequencer;
+///
+/// //A tiny accumulator machine
+/// const FETCH_OPERAND: u32 = 1 << 0;
+/// const ADD: u32 = 1 << 1;
+/// const CLEAR: u32 = 1 << 2;
+/// let mut sequencer = MicroSequencer::new(vec![
+///   vec![CLEAR],              //Opcode 0: Clear
+///   vec![FETCH_OPERAND, ADD], //Opcode 1: Add immediate, in 2 steps
+/// ]);
+///
+/// let program = [1, 5, 1, 7, 0, 1, 2];
+/// let (mut pc, mut acc, mut operand) = (0, 0, 0);
+/// let mut steps = 0;
+/// let mut accs = vec![];
+/// while pc < program.len() {
+///   sequencer.decode(program[pc]);
+///   pc += 1;
+///   loop {
+///     steps += 1;
+///     let done = sequencer.step(|micro_op| match micro_op {
+///       0 => { operand = program[pc]; pc += 1; },
+///       1 => acc += operand,
+///       2 => acc = 0,
+///       _ => unreachable!(),
+///     });
+///     if done {
+///       break;
+///     }
+///   }
+///   accs.push(acc);
+/// }
+/// assert_eq!(accs, [5, 12, 0, 2]);
+/// assert_eq!(steps, 7);
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct MicroSequencer {
+  /// Control words of every opcode, indexed by (opcode, micro pc)
+  microcode: Vec<Vec<u32>>,
+  /// Current instruction
+  opcode: u8,
+  /// Which control word of the current instruction runs next
+  micro_pc: usize,
+}
+
+impl MicroSequencer {
+  /// Create a sequencer from the microcode table. `microcode[opcode]` is the list of control words for that opcode.
+  #[inline]
+  pub fn new(microcode: Vec<Vec<u32>>) -> Self {
+    Self {
+      microcode,
+      opcode: 0,
+      micro_pc: 0,
+    }
+  }
+
+  /// Start running a new instruction
+  #[inline]
+  pub fn decode(&mut self, opcode: u8) {
+    self.opcode = opcode;
+    self.micro_pc = 0;
+  }
+
+  /// Fire every micro-op of the next control word. Returns true when the instruction is finished.
+  ///
+  /// Opcodes without microcode finish straight away, without firing anything.
+  pub fn step(&mut self, mut micro_op: impl FnMut(u32)) -> bool {
+    let row = self.microcode.get(self.opcode as usize).map_or(&[][..], |row| &row[..]);
+    if let Some(&control_word) = row.get(self.micro_pc) {
+      for bit in 0..u32::BITS {
+        if control_word >> bit & 1 == 1 {
+          micro_op(bit);
+        }
+      }
+      self.micro_pc += 1;
+    }
+    self.micro_pc >= row.len()
+  }
+
+  /// Current instruction
+  #[inline]
+  pub fn opcode(&self) -> u8 {
+    self.opcode
+  }
+
+  /// Which control word of the current instruction runs next
+  #[inline]
+  pub fn micro_pc(&self) -> usize {
+    self.micro_pc
+  }
+}
@@ -0,0 +1,44 @@
+//! Multiplexer chips
+
+use arbitrary_int::u4;
+
+/// ### 74157 Quad 2-line to 1-line Multiplexer
+///
+/// Four lanes share one select line. When select is low, the A inputs pass through. When select is high, the B inputs pass through.
+///
+/// ### Example
+/// ```
+/// use arbitrary_int::u4;
+/// use chips::mux::S74X157;
+///
+/// assert_eq!(S74X157::select_nibble(false, u4::new(0x3), u4::new(0xC)), u4::new(0x3));
+/// assert_eq!(S74X157::select_nibble(true, u4::new(0x3), u4::new(0xC)), u4::new(0xC));
+///
+/// let a = [u4::new(1), u4::new(2), u4::new(3)];
+/// let b = [u4::new(7), u4::new(8), u4::new(9)];
+/// let mut out = [u4::new(0); 3];
+/// S74X157::select_bus(true, &a, &b, &mut out);
+/// assert_eq!(out, b);
+/// S74X157::select_bus(false, &a, &b, &mut out);
+/// assert_eq!(out, a);
+/// ```
+pub struct S74X157;
+
+impl S74X157 {
+  /// Select between two nibbles
+  #[inline]
+  pub fn select_nibble(sel: bool, a: u4, b: u4) -> u4 {
+    if sel { b } else { a }
+  }
+
+  /// Select between two nibble buses at once.
+  ///
+  /// Only as many lanes as the shorter of `out` and the selected bus are written.
+  #[inline]
+  pub fn select_bus(sel: bool, a: &[u4], b: &[u4], out: &mut [u4]) {
+    let from = if sel { b } else { a };
+    for (lane, value) in out.iter_mut().zip(from.iter()) {
+      *lane = *value;
+    }
+  }
+}
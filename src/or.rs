@@ -13,6 +13,13 @@ pub type S74X32 = OR<u4>;
 /// use chips::or::S74X32;
 ///
 /// assert_eq!(S74X32::or(u4::new(0b1011),u4::new(0b1101)), u4::new(0b1111));
+/// assert_eq!(S74X32::or(u4::new(0b1000),u4::new(0b0001)), u4::new(0b1001));
+/// //Stays within 4 bits
+/// assert_eq!(S74X32::or(u4::new(0xF),u4::new(0xF)), u4::new(0xF));
+///
+/// assert_eq!(S74X32::or_bits(false, false), false);
+/// assert_eq!(S74X32::or_bits(true, false), true);
+/// assert_eq!(S74X32::or_bits(true, true), true);
 /// ```
 pub struct OR<T> {
   phantom: PhantomData<T>,
@@ -24,4 +31,27 @@ impl <T> OR<T> where T: ops::BitOr<Output=T> {
   pub fn or(num1: T, num2: T) -> T {
     num1 | num2
   }
+
+  /// Bit OR
+  #[inline]
+  pub fn or_bits(bit1: bool, bit2: bool) -> bool {
+    bit1 | bit2
+  }
+}
+
+impl <T> OR<T> where T: ops::BitOr<Output=T> + Default {
+  /// OR every value together, like several chips driving the same bus. No values reads as 0.
+  ///
+  /// ### Example
+  /// ```
+  /// use arbitrary_int::u4;
+  /// use chips::or::S74X32;
+  ///
+  /// assert_eq!(S74X32::or_all([u4::new(0b0001), u4::new(0b0100), u4::new(0b1000)]), u4::new(0b1101));
+  /// assert_eq!(S74X32::or_all([]), u4::new(0));
+  /// ```
+  #[inline]
+  pub fn or_all(values: impl IntoIterator<Item = T>) -> T {
+    values.into_iter().fold(T::default(), |total, value| total | value)
+  }
 }
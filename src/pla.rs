@@ -0,0 +1,93 @@
+//! Programmable Logic Array chips
+
+use alloc::vec::Vec;
+
+/// One row of the PLA's AND plane, and its connections into the OR plane.
+///
+/// The term is true when every input bit selected by `mask` equals the same bit in `value`. Input bits outside of `mask` are "don't care".
+///
+/// When the term is true, every bit set in `outputs` is driven high.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ProductTerm {
+  /// Which input bits this term looks at
+  pub mask: u32,
+  /// Required value of the input bits in `mask`
+  pub value: u32,
+  /// Output bits driven high when the term is true
+  pub outputs: u32,
+}
+
+/// ### Programmable Logic Array
+///
+/// An AND plane of product terms, feeding an OR plane. Each output is the OR of every true product term connected to it.
+///
+/// Calculator and CPU chips commonly use these for instruction decoding.
+///
+/// ### Example
+/// ```
+/// use chips::pla::{Pla, ProductTerm};
+///
+/// //Full adder. Inputs: bit 0 = A, bit 1 = B, bit 2 = carry in. Outputs: bit 0 = sum, bit 1 = carry out.
+/// let term = |mask, value, outputs| ProductTerm { mask, value, outputs };
+/// let adder: Pla<3, 2> = Pla::from_terms(vec![
+///   term(0b111, 0b001, 0b01), //Sum is true when an odd number of inputs are true
+///   term(0b111, 0b010, 0b01),
+///   term(0b111, 0b100, 0b01),
+///   term(0b111, 0b111, 0b01),
+///   term(0b011, 0b011, 0b10), //Carry is true when any two inputs are true
+///   term(0b101, 0b101, 0b10),
+///   term(0b110, 0b110, 0b10),
+/// ]);
+/// for input in 0..8_u32 {
+///   //A 2 bit sum of the 3 input bits
+///   assert_eq!(adder.evaluate(input), input.count_ones());
+/// }
+///
+/// //The same logic, as a truth table indexed by input
+/// let adder: Pla<3, 2> = Pla::from_truth_table(&[0, 1, 1, 2, 1, 2, 2, 3]);
+/// assert_eq!(adder.evaluate(0b110), 2);
+/// ```
+#[derive(Clone, PartialEq)]
+pub struct Pla<const INPUTS: u32, const OUTPUTS: u32> {
+  /// AND plane rows
+  terms: Vec<ProductTerm>,
+}
+
+impl<const INPUTS: u32, const OUTPUTS: u32> Pla<INPUTS, OUTPUTS> {
+  const INPUT_MASK: u32 = ((1u64 << INPUTS) - 1) as u32;
+  const OUTPUT_MASK: u32 = ((1u64 << OUTPUTS) - 1) as u32;
+
+  /// Create a PLA from product terms
+  #[inline]
+  pub fn from_terms(terms: Vec<ProductTerm>) -> Self {
+    Self {
+      terms,
+    }
+  }
+
+  /// Create a PLA from a truth table. `table[input]` is the output for that input. Missing entries output 0.
+  pub fn from_truth_table(table: &[u32]) -> Self {
+    let terms = table.iter().enumerate()
+      .filter(|(_, &outputs)| outputs != 0)
+      .map(|(input, &outputs)| ProductTerm {
+        mask: Self::INPUT_MASK,
+        value: input as u32,
+        outputs,
+      })
+      .collect();
+    Self::from_terms(terms)
+  }
+
+  /// Drive the inputs, and read the outputs
+  pub fn evaluate(&self, input: u32) -> u32 {
+    let input = input & Self::INPUT_MASK;
+    self.terms.iter()
+      .filter(|term| input & term.mask == term.value & term.mask)
+      .fold(0, |outputs, term| outputs | term.outputs) & Self::OUTPUT_MASK
+  }
+
+  /// Look up how many inputs is the generic const INPUTS set to.
+  pub const INPUTS: u32 = INPUTS;
+  /// Look up how many outputs is the generic const OUTPUTS set to.
+  pub const OUTPUTS: u32 = OUTPUTS;
+}
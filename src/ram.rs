@@ -1,5 +1,9 @@
 //! All RAM Chips
 
+use alloc::vec::Vec;
+use crate::memory::AccessCounts;
+use crate::snapshot::{self, Snapshot, SnapshotError};
+
 /// Intel 2107B is a 512 byte RAM.
 pub type I2107B = RAM<0x200>;
 
@@ -9,6 +13,54 @@ pub type MK4015 = RAM<0x200>;
 /// Mostek 4027 is a 512 byte (4096 bit) DRAM with 120-200ns access time, 320-375ns cycle
 pub type MK4027 = RAM<0x200>;
 
+/// ### Access Timing
+///
+/// How long a RAM part takes, so a scheduler can add wait states on a slow bus.
+///
+/// The part aliases are all the same `RAM<0x200>` type, so the timing is kept on a marker for each part instead. Where a part came in several speed grades, the slowest is used.
+///
+/// ### Example
+/// ```
+/// use chips::ram::{AccessTiming, MK4027Timing};
+///
+/// //At 4 MHz there are 250ns per clock cycle
+/// assert_eq!(MK4027Timing::wait_cycles(250), 0);
+/// //At 10 MHz
+/// assert_eq!(MK4027Timing::wait_cycles(100), 1);
+/// ```
+pub trait AccessTiming {
+  /// Nanoseconds from the address to the data being valid
+  const ACCESS_NS: u32;
+  /// Nanoseconds before the next access can start
+  const CYCLE_NS: u32;
+
+  /// Clock cycles the CPU has to wait on a read, on top of the one it always takes, with `clock_ns` nanoseconds per clock cycle.
+  ///
+  /// Panics if `clock_ns` is 0.
+  #[inline]
+  fn wait_cycles(clock_ns: u32) -> u32 {
+    Self::ACCESS_NS.div_ceil(clock_ns) - 1
+  }
+}
+
+/// Timing of the Mostek 4015
+#[derive(Debug, Clone, Copy)]
+pub struct MK4015Timing;
+
+impl AccessTiming for MK4015Timing {
+  const ACCESS_NS: u32 = 250;
+  const CYCLE_NS: u32 = 380;
+}
+
+/// Timing of the Mostek 4027, slowest grade
+#[derive(Debug, Clone, Copy)]
+pub struct MK4027Timing;
+
+impl AccessTiming for MK4027Timing {
+  const ACCESS_NS: u32 = 200;
+  const CYCLE_NS: u32 = 375;
+}
+
 /// ### Generic RAM Chip
 /// Internally stores all data as bytes, but can read/write in u4,u8,u16,etc.
 ///
@@ -23,13 +75,22 @@ pub type MK4027 = RAM<0x200>;
 pub struct RAM<const LENGTH: usize> {
   /// Data in the RAM chip
   pub data: [u8; LENGTH],
+  /// Address being watched for writes
+  watch: Option<usize>,
+  /// Last write to the watched address, not yet taken
+  watch_hit: Option<(usize, u8)>,
+  /// Reads and writes of each address, when profiling
+  access_counts: AccessCounts,
 }
 
 impl<const LENGTH: usize> Default for RAM<LENGTH> {
   #[inline]
   fn default() -> Self {
     Self {
-      data: [Default::default(); LENGTH]
+      data: [Default::default(); LENGTH],
+      watch: None,
+      watch_hit: None,
+      access_counts: Default::default(),
     }
   }
 }
@@ -44,6 +105,7 @@ impl<const LENGTH: usize> RAM<LENGTH> {
   /// Read at address
   #[inline]
   pub fn read<T: crate::ReadArr>(&self, addr: usize) -> T {
+    self.access_counts.count(addr, core::mem::size_of::<T>());
     T::read(&self.data[addr..])
   }
 
@@ -51,6 +113,12 @@ impl<const LENGTH: usize> RAM<LENGTH> {
   #[inline]
   pub fn write<T: crate::WriteArr>(&mut self, addr: usize, value: T) {
     T::write(&mut self.data[addr..], value);
+    self.access_counts.count(addr, core::mem::size_of::<T>());
+    if let Some(watch) = self.watch {
+      if (addr..addr + core::mem::size_of::<T>()).contains(&watch) {
+        self.watch_hit = Some((watch, self.data[watch]));
+      }
+    }
   }
 
   /// Read bits
@@ -59,6 +127,7 @@ impl<const LENGTH: usize> RAM<LENGTH> {
     let byte_addr = bit_addr / 8;
     let which_bit = bit_addr % 8;
     let mask = 1 << which_bit;
+    self.access_counts.count(byte_addr, 1);
     (self.data[byte_addr] & mask) != 0
   }
 
@@ -73,6 +142,122 @@ impl<const LENGTH: usize> RAM<LENGTH> {
     } else {
       self.data[byte_addr] &= !mask;
     }
+    self.access_counts.count(byte_addr, 1);
+    if self.watch == Some(byte_addr) {
+      self.watch_hit = Some((byte_addr, self.data[byte_addr]));
+    }
+  }
+
+  /// ### Read Bit Span
+  ///
+  /// Read `count` bits, up to 64, starting at `start_bit`. Bits are numbered the same as `read_bit`, and `start_bit` ends up in bit 0 of the result.
+  ///
+  /// Whole bytes are read at a time, rather than bit by bit.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::RAM;
+  ///
+  /// let mut ram: RAM<4> = RAM::new();
+  /// ram.write_bits(4, 12, 0xABC);
+  /// assert_eq!(ram.data[..2], [0xC0, 0xAB]);
+  /// assert_eq!(ram.read_bits(4, 12), 0xABC);
+  /// assert_eq!(ram.read_bits(8, 4), 0xB);
+  /// ```
+  pub fn read_bits(&self, start_bit: usize, count: usize) -> u64 {
+    assert!(count <= 64, "Can't read {} bits at once", count);
+    let mut value = 0;
+    let mut done = 0;
+    while done < count {
+      let bit_addr = start_bit + done;
+      let byte_addr = bit_addr / 8;
+      let which_bit = bit_addr % 8;
+      let take = (8 - which_bit).min(count - done);
+      let bits = (self.data[byte_addr] >> which_bit) as u64 & ((1 << take) - 1);
+      self.access_counts.count(byte_addr, 1);
+      value |= bits << done;
+      done += take;
+    }
+    value
+  }
+
+  /// Write the low `count` bits of `value`, up to 64, starting at `start_bit`. The opposite of `read_bits`.
+  pub fn write_bits(&mut self, start_bit: usize, count: usize, value: u64) {
+    assert!(count <= 64, "Can't write {} bits at once", count);
+    let mut done = 0;
+    while done < count {
+      let bit_addr = start_bit + done;
+      let byte_addr = bit_addr / 8;
+      let which_bit = bit_addr % 8;
+      let take = (8 - which_bit).min(count - done);
+      let mask = (((1_u16 << take) - 1) << which_bit) as u8;
+      let bits = ((value >> done) as u8) << which_bit;
+      self.data[byte_addr] = (self.data[byte_addr] & !mask) | (bits & mask);
+      self.access_counts.count(byte_addr, 1);
+      if self.watch == Some(byte_addr) {
+        self.watch_hit = Some((byte_addr, self.data[byte_addr]));
+      }
+      done += take;
+    }
+  }
+
+  /// Watch an address for writes, replacing any previous watch. Writes to it can be picked up with `take_watch_hit`.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::RAM;
+  ///
+  /// let mut ram: RAM<0x10> = RAM::new();
+  /// ram.set_watch(5);
+  /// ram.write(4, 0x12_u8);
+  /// assert_eq!(ram.take_watch_hit(), None);
+  /// ram.write(4, 0x3456_u16); //Covers address 5 too
+  /// assert_eq!(ram.take_watch_hit(), Some((5, 0x34)));
+  /// assert_eq!(ram.take_watch_hit(), None);
+  /// ram.write_bit(5 * 8, false);
+  /// assert_eq!(ram.take_watch_hit(), Some((5, 0x34)));
+  /// ```
+  #[inline]
+  pub fn set_watch(&mut self, addr: usize) {
+    self.watch = Some(addr);
+  }
+
+  /// Stop watching for writes
+  #[inline]
+  pub fn clear_watch(&mut self) {
+    self.watch = None;
+    self.watch_hit = None;
+  }
+
+  /// The address and new value of the last write to the watched address, if there was one since the last call
+  #[inline]
+  pub fn take_watch_hit(&mut self) -> Option<(usize, u8)> {
+    self.watch_hit.take()
+  }
+
+  /// Start counting reads and writes of every address. Counts start at 0.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::RAM;
+  ///
+  /// let mut ram: RAM<4> = RAM::new();
+  /// assert!(ram.access_counts().is_none());
+  /// ram.enable_profiling();
+  /// ram.read::<u8>(1);
+  /// ram.read::<u8>(1);
+  /// ram.write(2, 0x1234_u16);
+  /// assert_eq!(ram.access_counts().unwrap(), [0, 2, 1, 1]);
+  /// ```
+  #[inline]
+  pub fn enable_profiling(&mut self) {
+    self.access_counts.enable::<LENGTH>();
+  }
+
+  /// Reads and writes of each address since profiling was enabled
+  #[inline]
+  pub fn access_counts(&mut self) -> Option<&[u32]> {
+    self.access_counts.get()
   }
 
   /// For initializing the RAM the same way that you would initialize a ROM with all data at once
@@ -83,4 +268,33 @@ impl<const LENGTH: usize> RAM<LENGTH> {
 
   /// Look up how many bytes is the generic const LENGTH set to.
   pub const LENGTH: usize = LENGTH;
-}
\ No newline at end of file
+}
+
+/// Only the data is serialized. Watches and profiling are left off after deserializing.
+#[cfg(feature = "serde")]
+impl<const LENGTH: usize> serde::Serialize for RAM<LENGTH> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    crate::memory::serialize_data(&self.data, serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const LENGTH: usize> serde::Deserialize<'de> for RAM<LENGTH> {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let mut ram = Self::new();
+    ram.set_total(crate::memory::deserialize_data(deserializer)?);
+    Ok(ram)
+  }
+}
+
+impl<const LENGTH: usize> Snapshot for RAM<LENGTH> {
+  fn save(&self) -> Vec<u8> {
+    self.data.to_vec()
+  }
+
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+    snapshot::check_length(bytes, LENGTH)?;
+    self.data.copy_from_slice(bytes);
+    Ok(())
+  }
+}
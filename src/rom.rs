@@ -1,5 +1,9 @@
 //! Simple ROM chips
 
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::memory::AccessCounts;
+
 /// Intel 8702 is a 256 byte ROM.
 pub type I8702 = ROM<0x100>;
 /// Intel 3604 is a 512 byte ROM.
@@ -27,6 +31,8 @@ pub type TMS2716 = ROM<0x800>;
 pub struct ROM<const LENGTH: usize> {
   /// Data in the ROM chip
   data: [u8; LENGTH],
+  /// Reads of each address, when profiling
+  access_counts: AccessCounts,
 }
 
 impl<const LENGTH: usize> ROM<LENGTH> {
@@ -38,15 +44,207 @@ impl<const LENGTH: usize> ROM<LENGTH> {
   pub fn new(data: [u8; LENGTH]) -> Self {
     Self {
       data,
+      access_counts: Default::default(),
     }
   }
   
   /// Read byte at address
   #[inline]
   pub fn read<T: crate::ReadArr>(&self, addr: usize) -> T {
+    self.access_counts.count(addr, core::mem::size_of::<T>());
     T::read(&self.data[addr..])
   }
+
+  /// Start counting reads of every address. Useful for finding hot loops and dead code.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::ROM;
+  ///
+  /// let mut rom: ROM<3> = ROM::new([10,20,30]);
+  /// rom.enable_profiling();
+  /// rom.read::<u8>(1);
+  /// rom.read::<u8>(1);
+  /// assert_eq!(rom.access_counts().unwrap()[1], 2);
+  /// assert_eq!(rom.access_counts().unwrap()[2], 0);
+  /// ```
+  #[inline]
+  pub fn enable_profiling(&mut self) {
+    self.access_counts.enable::<LENGTH>();
+  }
+
+  /// Reads of each address since profiling was enabled
+  #[inline]
+  pub fn access_counts(&mut self) -> Option<&[u32]> {
+    self.access_counts.get()
+  }
   
   /// Look up how many bytes is the generic const LENGTH set to.
   pub const LENGTH: usize = LENGTH;
 }
+
+/// Only the data is serialized. Profiling is left off after deserializing.
+#[cfg(feature = "serde")]
+impl<const LENGTH: usize> serde::Serialize for ROM<LENGTH> {
+  fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    crate::memory::serialize_data(&self.data, serializer)
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const LENGTH: usize> serde::Deserialize<'de> for ROM<LENGTH> {
+  fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    crate::memory::deserialize_data(deserializer).map(Self::new)
+  }
+}
+
+/// Pack words of `bits` width tightly, least significant bit first. This is the layout `PackedRom` and the HP Classic ROMs read.
+///
+/// Bits above `bits` in each word are ignored.
+///
+/// ### Example
+/// ```
+/// use chips::rom::{self, PackedRom};
+///
+/// let packed = rom::from_words(&[0x3FF, 0x001, 0x155], 10);
+/// assert_eq!(packed, [0xFF, 0x07, 0x50, 0x15]);
+///
+/// let rom: PackedRom<11, 3> = PackedRom::new(rom::from_words(&[0x400, 0x7FF, 0x2AA], 11));
+/// assert_eq!(rom.read_word(1), 0x7FF);
+/// ```
+pub fn from_words(words: &[u16], bits: u32) -> Vec<u8> {
+  let mut packed = vec![0; (words.len() * bits as usize).div_ceil(8)];
+  for (index, &word) in words.iter().enumerate() {
+    let word = (word as u32) & ((1 << bits) - 1);
+    let bit_start = index * bits as usize;
+    let shifted = word << (bit_start % 8);
+    for (offset, byte) in shifted.to_le_bytes().iter().enumerate() {
+      if let Some(packed_byte) = packed.get_mut(bit_start / 8 + offset) {
+        *packed_byte |= byte;
+      }
+    }
+  }
+  packed
+}
+
+/// ### Packed Word ROM
+///
+/// Calculator chips like the HP Classic (10 bit) and TMS0800 (11 bit) ROMs store words which are not a multiple of 8 bits.
+///
+/// The words are packed tightly, least significant bit first. Word 0 starts at bit 0 of byte 0, and word 1 starts right after it.
+///
+/// ### Example
+/// ```
+/// use chips::rom::PackedRom;
+///
+/// //10 bit words 0x3FF, 0x001, 0x155
+/// let rom: PackedRom<10, 3> = PackedRom::new(vec![0xFF, 0x07, 0x50, 0x15]);
+/// assert_eq!(rom.read_word(0), 0x3FF);
+/// assert_eq!(rom.read_word(1), 0x001);
+/// assert_eq!(rom.read_word(2), 0x155);
+///
+/// //11 bit words 0x400, 0x7FF, 0x2AA. The second word spans 3 bytes.
+/// let rom: PackedRom<11, 3> = PackedRom::new(vec![0x00, 0xFC, 0xBF, 0xAA, 0x00]);
+/// assert_eq!(rom.read_word(0), 0x400);
+/// assert_eq!(rom.read_word(1), 0x7FF);
+/// assert_eq!(rom.read_word(2), 0x2AA);
+/// ```
+pub struct PackedRom<const BITS: u32, const WORDS: usize> {
+  /// Packed words
+  packed_data: Vec<u8>,
+}
+
+impl<const BITS: u32, const WORDS: usize> PackedRom<BITS, WORDS> {
+  /// Create a new ROM from packed data. It must be exactly `BYTES` long.
+  #[inline]
+  pub fn new(packed_data: Vec<u8>) -> Self {
+    assert_eq!(packed_data.len(), Self::BYTES, "Packed ROM data is the wrong length");
+    Self {
+      packed_data,
+    }
+  }
+
+  /// Read the word at index
+  pub fn read_word(&self, index: usize) -> u16 {
+    assert!(index < WORDS, "Word index out of bounds");
+    let bit_start = index * BITS as usize;
+    let byte_index = bit_start / 8;
+    //A word of up to 16 bits spans at most 3 bytes. The last word may not need all of them.
+    let mut bytes = [0; 4];
+    for (offset, byte) in bytes.iter_mut().take(3).enumerate() {
+      *byte = self.packed_data.get(byte_index + offset).copied().unwrap_or(0);
+    }
+    let value = u32::from_le_bytes(bytes) >> (bit_start % 8);
+    (value & ((1 << BITS) - 1)) as u16
+  }
+
+  /// Look up how many bits per word is the generic const BITS set to.
+  pub const BITS: u32 = BITS;
+  /// Look up how many words is the generic const WORDS set to.
+  pub const WORDS: usize = WORDS;
+  /// How many bytes the packed data takes up
+  pub const BYTES: usize = (BITS as usize * WORDS).div_ceil(8);
+}
+
+/// ### Bank Switched ROM
+///
+/// Cartridges larger than the CPU's address window are split into banks of `WINDOW` bytes. Only the selected bank is visible through `read`.
+///
+/// Selecting a bank past the last one wraps around, as if the unused high bank select lines were not connected.
+///
+/// ### Example
+/// ```
+/// use chips::rom::BankedRom;
+///
+/// let mut rom: BankedRom<4> = BankedRom::new(vec![vec![1, 2, 3, 4], vec![5, 6, 7, 8]]);
+/// assert_eq!(rom.read::<u8>(1), 2);
+/// rom.select(1);
+/// assert_eq!(rom.read::<u8>(1), 6);
+/// rom.select(2); //Wraps back to bank 0
+/// assert_eq!(rom.read::<u8>(1), 2);
+/// ```
+pub struct BankedRom<const WINDOW: usize> {
+  /// Every bank, each WINDOW bytes long
+  banks: Vec<Vec<u8>>,
+  /// Which bank is visible
+  selected: usize,
+}
+
+impl<const WINDOW: usize> BankedRom<WINDOW> {
+  /// Create a new banked ROM. Each bank must be exactly `WINDOW` bytes, and there must be at least one.
+  pub fn new(banks: Vec<Vec<u8>>) -> Self {
+    assert!(!banks.is_empty(), "Banked ROM needs at least one bank");
+    assert!(banks.iter().all(|bank| bank.len() == WINDOW), "Every ROM bank must be WINDOW bytes long");
+    Self {
+      banks,
+      selected: 0,
+    }
+  }
+
+  /// Switch which bank is visible
+  #[inline]
+  pub fn select(&mut self, bank: usize) {
+    self.selected = bank % self.banks.len();
+  }
+
+  /// Which bank is visible
+  #[inline]
+  pub fn selected(&self) -> usize {
+    self.selected
+  }
+
+  /// How many banks there are
+  #[inline]
+  pub fn bank_count(&self) -> usize {
+    self.banks.len()
+  }
+
+  /// Read from the selected bank at address
+  #[inline]
+  pub fn read<T: crate::ReadArr>(&self, addr: usize) -> T {
+    T::read(&self.banks[self.selected][addr..])
+  }
+
+  /// Look up how many bytes is the generic const WINDOW set to.
+  pub const WINDOW: usize = WINDOW;
+}
@@ -0,0 +1,64 @@
+//! Multi-chip timing
+
+use alloc::vec::Vec;
+use crate::Clock;
+
+/// ### Virtual Clock Scheduler
+///
+/// Boards often mix chips running at different speeds off of one master oscillator. Each chip is registered with its clock divisor, and the scheduler decides which chips get a `run_cycle` on every master tick.
+///
+/// The scheduler does not own the chips. Instead, `tick` calls back with the handle of every chip which is due, in the order they were registered. Register chips which feed other chips first.
+///
+/// ### Example
+/// ```
+/// use chips::scheduler::Scheduler;
+///
+/// let mut scheduler = Scheduler::new();
+/// let cpu = scheduler.add(1);
+/// let video = scheduler.add(2);
+///
+/// let mut order = Vec::new();
+/// for _ in 0..4 {
+///   scheduler.tick(|chip| order.push(chip));
+/// }
+/// assert_eq!(order, [cpu, cpu, video, cpu, cpu, video]);
+/// assert_eq!(scheduler.elapsed(), 4);
+/// ```
+#[derive(Default, Clone, PartialEq)]
+pub struct Scheduler {
+  /// One divider per registered chip
+  clocks: Vec<Clock>,
+  /// Master ticks since creation
+  elapsed: u64,
+}
+
+impl Scheduler {
+  /// Create a new scheduler with no chips
+  #[inline]
+  pub fn new() -> Self {
+    Default::default()
+  }
+
+  /// Register a chip which runs once every `divisor` master ticks. Returns the handle passed back by `tick`.
+  #[inline]
+  pub fn add(&mut self, divisor: u32) -> usize {
+    self.clocks.push(Clock::new(divisor));
+    self.clocks.len() - 1
+  }
+
+  /// Advance the master clock by one tick, calling `run` for every chip which is due, in registration order.
+  pub fn tick(&mut self, mut run: impl FnMut(usize)) {
+    self.elapsed += 1;
+    for (chip, clock) in self.clocks.iter_mut().enumerate() {
+      if clock.tick() {
+        run(chip);
+      }
+    }
+  }
+
+  /// Master ticks since creation
+  #[inline]
+  pub fn elapsed(&self) -> u64 {
+    self.elapsed
+  }
+}
@@ -1,6 +1,8 @@
 //! All Shift Register Chips
 
 use arbitrary_int::{ u4 };
+use alloc::vec::Vec;
+use crate::snapshot::{self, Snapshot, SnapshotError};
 
 /// 7400 Series 8 bit shift register
 //pub type S74X166 = Shifter<u8, 8>;
@@ -21,11 +23,13 @@ use arbitrary_int::{ u4 };
 ///
 /// ### Example
 /// ```
-/// use chips::Shifter;
+/// use chips::Shifter64;
+/// use chips::shifter::Direction;
 ///
-/// let mut shifter: Shifter<10> = Shifter::new();
-/// shifter.read_write_serial(Direction::Left, true, false);
-/// shifter.read_write_serial(Direction::Left, true, true);
+/// let mut shifter: Shifter64<10> = Shifter64::new(0);
+/// shifter.shift_with_bit(Direction::Left, true);
+/// shifter.shift_with_bit(Direction::Left, false);
+/// assert_eq!(shifter.read_parallel(), 0b10);
 /// ```
 #[derive(Default, Clone, Copy, PartialEq)]
 pub struct Shifter64<const NUM_BITS: u32> {
@@ -114,6 +118,34 @@ impl<const NUM_BITS: u32> Shifter64<NUM_BITS> {
     u4::new(nibble & 0xF)
   }
 
+  /// ### Linear Feedback Shift Register
+  ///
+  /// Shifts left once, feeding in the parity (XOR) of the `taps` bits. Returns the bit shifted out.
+  ///
+  /// A common 8 bit tap set is `0b1011_1000` (x^8 + x^6 + x^5 + x^4 + 1), which steps through all 255 nonzero values before repeating.
+  ///
+  /// ### Example
+  /// ```
+  /// use chips::Shifter64;
+  ///
+  /// let mut lfsr: Shifter64<8> = Shifter64::new(1);
+  /// let mut period = 0;
+  /// loop {
+  ///   lfsr.lfsr_step(0b1011_1000);
+  ///   period += 1;
+  ///   if lfsr.read_parallel() == 1 {
+  ///     break;
+  ///   }
+  /// }
+  /// assert_eq!(period, 255);
+  /// ```
+  pub fn lfsr_step(&mut self, taps: u64) -> bool {
+    let feedback = (self.data & taps).count_ones() % 2 == 1;
+    let out_bit = self.read_bit(Direction::Left);
+    self.shift_with_bit(Direction::Left, feedback);
+    out_bit
+  }
+
 }
 
 /// 16 bit shifter chip
@@ -204,3 +236,37 @@ impl<const NUM_BITS: u32> Shifter16<NUM_BITS> {
   }
 
 }
+
+impl<const NUM_BITS: u32> Snapshot for Shifter64<NUM_BITS> {
+  fn save(&self) -> Vec<u8> {
+    self.data.to_le_bytes().to_vec()
+  }
+
+  /// Fails if any bits are set beyond NUM_BITS
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+    snapshot::check_length(bytes, 8)?;
+    let data = u64::from_le_bytes(bytes.try_into().unwrap());
+    if data & !Self::MASK != 0 {
+      return Err(SnapshotError::InvalidData);
+    }
+    self.data = data;
+    Ok(())
+  }
+}
+
+impl<const NUM_BITS: u32> Snapshot for Shifter16<NUM_BITS> {
+  fn save(&self) -> Vec<u8> {
+    self.data.to_le_bytes().to_vec()
+  }
+
+  /// Fails if any bits are set beyond NUM_BITS
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError> {
+    snapshot::check_length(bytes, 2)?;
+    let data = u16::from_le_bytes(bytes.try_into().unwrap());
+    if data & !Self::MASK != 0 {
+      return Err(SnapshotError::InvalidData);
+    }
+    self.data = data;
+    Ok(())
+  }
+}
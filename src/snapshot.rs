@@ -0,0 +1,52 @@
+//! Save states for chips
+//!
+//! Every chip which implements `Snapshot` can be saved into a byte array, and later restored from it.
+//!
+//! A board can checkpoint a whole machine by saving each of its chips.
+
+use alloc::vec::Vec;
+
+/// Reasons a save state could not be loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotError {
+  /// The save state does not have the number of bytes this chip expects
+  WrongLength {
+    /// How many bytes the chip expects
+    expected: usize,
+    /// How many bytes were passed in
+    found: usize,
+  },
+  /// A value in the save state is out of range for the chip
+  InvalidData,
+}
+
+/// Save and load the full state of a chip
+///
+/// ### Example
+/// ```
+/// use chips::{RAM, Snapshot};
+///
+/// let mut ram: RAM<4> = RAM::new();
+/// ram.write(1, 0x12_u8);
+/// let saved = ram.save();
+///
+/// let mut restored: RAM<4> = RAM::new();
+/// restored.load(&saved).unwrap();
+/// assert_eq!(restored.read::<u8>(1), 0x12);
+/// assert!(restored.load(&[0; 3]).is_err());
+/// ```
+pub trait Snapshot {
+  /// Save the chip's state into bytes
+  fn save(&self) -> Vec<u8>;
+  /// Restore the chip's state from bytes created by `save`
+  fn load(&mut self, bytes: &[u8]) -> Result<(), SnapshotError>;
+}
+
+/// Make sure the save state is the exact size expected
+pub(crate) fn check_length(bytes: &[u8], expected: usize) -> Result<(), SnapshotError> {
+  if bytes.len() == expected {
+    Ok(())
+  } else {
+    Err(SnapshotError::WrongLength { expected, found: bytes.len() })
+  }
+}
@@ -3,6 +3,7 @@
 use log::{debug, trace};
 use arbitrary_int::{u4,u5};
 use crate::shifter;
+use crate::SegmentDecoder;
 
 /// 11 nibbles of BCD digits
 pub type Register = shifter::Shifter64<44>;
@@ -46,6 +47,9 @@ pub enum Dest {
 pub struct Opcode{ operation: Oper, dest: Dest, arg1: Arg1, arg2: Arg2, hex: bool }
 
 impl Opcode {
+  /// Does nothing, such as for filling the opcode table of an ALU that only needs its registers
+  pub const WAIT: Self = Self::new(Dest::None, Arg1::None, Oper::Wait, Arg2::None, false);
+
   pub const fn new(dest: Dest, arg1: Arg1, operation: Oper, arg2: Arg2, hex: bool) -> Self {
     Opcode {
       operation,
@@ -93,6 +97,54 @@ impl ALU {
     }
   }
   
+  /// ### LED Segments
+  ///
+  /// Segments for each of the 11 digits of A, most significant first. Segment a is bit 0, through segment g in bit 6.
+  ///
+  /// The B digit in the same position controls how a digit is shown:
+  /// * 0xF blanks the digit, such as for leading zeros.
+  /// * 2 lights the decimal point after the digit, in bit 7.
+  ///
+  /// The leftmost digit of A, digit 10, holds the sign rather than a number. Any non zero value there is negative, and shows a minus in that column even when B blanks it. A positive sign leaves the column blank.
+  ///
+  /// ### Example
+  /// ```
+  /// use arbitrary_int::u4;
+  /// use chips::SegmentDecoder;
+  /// use chips::tms0800::alu::{ALU, Opcode, Register};
+  ///
+  /// let mut alu = ALU::new([Opcode::WAIT; 32], [u4::new(0); 16]);
+  /// alu.a = Register::new(0x000_0000_3141);
+  /// alu.b = Register::new(0xFFF_FFFF_0200);
+  /// let segments = alu.segment_output(&SegmentDecoder::new_s74x47());
+  /// assert_eq!(segments[..7], [0; 7]);
+  /// assert_eq!(segments[7..], [0b1_00_1_11_1, 0b0_00_0_11_0 | 0x80, 0b1_10_0_11_0, 0b0_00_0_11_0]);
+  ///
+  /// //Negative
+  /// alu.a = Register::new(0x900_0000_3141);
+  /// assert_eq!(alu.segment_output(&SegmentDecoder::new_s74x47())[0], 0b1_00_0_00_0);
+  /// ```
+  pub fn segment_output(&self, decoder: &SegmentDecoder) -> [u8; 11] {
+    let a = self.a.read_parallel();
+    let b = self.b.read_parallel();
+    core::array::from_fn(|column| {
+      let shift = (10 - column) * 4;
+      let digit = u4::new(((a >> shift) & 0xF) as u8);
+      if column == 0 {
+        if digit.value() == 0 {
+          return 0;
+        }
+        let minus = 0b100_0000;  //Segment g
+        return if (b >> shift) & 0xF == 2 { minus | 0x80 } else { minus };
+      }
+      match (b >> shift) & 0xF {
+        0xF => 0,
+        2 => decoder.decode(digit) | 0x80,
+        _ => decoder.decode(digit),
+      }
+    })
+  }
+
   /// Returns carry
   pub fn run_cycle(&mut self, mut word_select: WordSelect, instruction: u5, mask: u4) -> bool {
     let mut carry = false;
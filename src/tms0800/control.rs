@@ -12,7 +12,7 @@ pub struct ControlUnit {
   pub fb: Flag,
   d: shifter::Shifter16<10>,  //In the patent, this is a 11 bit shifter, but it keeps skipping over the 11th bit, so I make it 10 bits.
   
-  /// Set by keyboard
+  /// Set by keyboard. The D line is in the low bits, see `keypad::Keypad`.
   pub current_keypress: u16,
 }
 
@@ -26,6 +26,18 @@ impl ControlUnit {
       current_keypress: 0,
     }
   }
+
+  /// Hold down `key`, wired according to `keypad`. SCAN, WAITDK and the key match jumps pick it up from `current_keypress`.
+  #[inline]
+  pub fn press(&mut self, keypad: &impl super::keypad::Keypad, key: super::keypad::CalcKey) {
+    self.current_keypress = keypad.keycode(key);
+  }
+
+  /// Let go of all keys
+  #[inline]
+  pub fn release(&mut self) {
+    self.current_keypress = 0;
+  }
   
   pub fn run_cycle(&mut self, mut word_select: super::WordSelect, opcode: u11, class: u2, instruction: u5, mut carry: bool) -> bool {
     self.pc += u9::new(1);
@@ -58,7 +70,7 @@ impl ControlUnit {
                 trace!("Jump on key match to {:03X}", addr);
                 //self.count = 100;
 
-                if self.d.read_parallel() == self.current_keypress {
+                if self.d.read_parallel() == self.current_keypress & super::keypad::D_LINES {
                   self.pc = u9::new(addr);
                 }
               },
@@ -99,7 +111,7 @@ impl ControlUnit {
       },
       _ => {  //3 - Register Instruction
         if instruction.value() == 0x1A {  //AKCN instruction needs to keep repeating as it tallies up the key code.
-          if self.d.read_parallel() | 0b1 != self.current_keypress {  //Keep trying until we are done reading all of d.
+          if self.d.read_parallel() | 0b1 != self.current_keypress & super::keypad::D_LINES {  //Keep trying until we are done reading all of d.
             self.pc -= u9::new(1);
           }
         }
@@ -0,0 +1,92 @@
+//! Mapping calculator keys to the scan codes the control unit waits for
+
+/// A key on the calculator, by what it does rather than where it is wired
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CalcKey {
+  /// Number keys 0 to 9
+  Digit(u8),
+  /// Decimal point
+  Decimal,
+  /// +
+  Add,
+  /// -
+  Subtract,
+  /// ×
+  Multiply,
+  /// ÷
+  Divide,
+  /// =
+  Equals,
+  /// C, clears everything
+  Clear,
+  /// CE, clears only the last entry
+  ClearEntry,
+}
+
+/// Wiring of keys into the scanning matrix
+///
+/// The control unit walks a single bit through the D scan lines. A key is seen when the D line it is wired to is being driven, on the K line it is wired to.
+///
+/// A key code holds both. Bits 0 to 9 are a one-hot of the D line, within `D_LINES`. The bits above are a one-hot of the K line, starting with KN at `K_SHIFT`. So every key in a layout gets its own code.
+pub trait Keypad {
+  /// Scan code for `key`, ready for `ControlUnit::current_keypress`
+  fn keycode(&self, key: CalcKey) -> u16;
+}
+
+/// Bits of a key code holding the D line
+pub const D_LINES: u16 = 0x3FF;
+
+/// First bit of a key code holding the K line
+pub const K_SHIFT: u16 = 10;
+
+/// ### TI Style Keypad
+///
+/// The default layout. Keys are wired to two K lines, each of which spans the 10 D scan lines.
+///
+/// | D line | Bit | KN line | KO line |
+/// |--------|-----|---------|---------|
+/// | D1     | 0   | 0       | =       |
+/// | D2     | 1   | 1       | +       |
+/// | D3     | 2   | 2       | -       |
+/// | D4     | 3   | 3       | ×       |
+/// | D5     | 4   | 4       | ÷       |
+/// | D6     | 5   | 5       | .       |
+/// | D7     | 6   | 6       | C       |
+/// | D8     | 7   | 7       | CE      |
+/// | D9     | 8   | 8       |         |
+/// | D10    | 9   | 9       |         |
+///
+/// Keys sharing a D line differ in their K line bits, KN at bit 10 and KO at bit 11.
+///
+/// ### Example
+/// ```
+/// use chips::tms0800::keypad::{CalcKey, Keypad, TiLayout};
+///
+/// assert_eq!(TiLayout.keycode(CalcKey::Digit(7)), 0b01_0010000000);
+/// assert_eq!(TiLayout.keycode(CalcKey::Equals), 0b10_0000000001);
+/// assert_ne!(TiLayout.keycode(CalcKey::Equals), TiLayout.keycode(CalcKey::Digit(0)));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TiLayout;
+
+impl Keypad for TiLayout {
+  /// Panics on a digit past 9
+  fn keycode(&self, key: CalcKey) -> u16 {
+    //KN is K line 0, KO is K line 1
+    let (d_line, k_line) = match key {
+      CalcKey::Digit(digit) => {
+        assert!(digit < 10, "Not a calculator digit: {}", digit);
+        (digit, 0)
+      },
+      CalcKey::Equals => (0, 1),
+      CalcKey::Add => (1, 1),
+      CalcKey::Subtract => (2, 1),
+      CalcKey::Multiply => (3, 1),
+      CalcKey::Divide => (4, 1),
+      CalcKey::Decimal => (5, 1),
+      CalcKey::Clear => (6, 1),
+      CalcKey::ClearEntry => (7, 1),
+    };
+    (1 << (K_SHIFT + k_line)) | (1 << d_line)
+  }
+}
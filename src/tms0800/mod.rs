@@ -2,9 +2,11 @@
 
 pub mod alu;
 pub mod control;
+pub mod keypad;
 
 use arbitrary_int::{u2,u4,u5,u11};
-use crate::shifter;
+use crate::display::{DisplayFrame, HasDisplay};
+use crate::{shifter, SegmentDecoder};
 
 /// WordSelect, Mapped from the "mask".
 type WordSelect = shifter::Shifter16<11>;
@@ -46,4 +48,17 @@ impl TMS0800 {
     //Run CU in all cases, including for the alu.
     self.carry = self.control.run_cycle(word_select, opcode, u2::new(class), instruction, self.carry);
   }
-}
\ No newline at end of file
+}
+
+impl HasDisplay for TMS0800 {
+  /// The LED display is always driven from A.
+  #[inline]
+  fn display_on(&self) -> bool {
+    true
+  }
+
+  /// The 11 digits of A, with the sign, blanking and decimal point from B. See `ALU::segment_output`.
+  fn pixels(&self) -> DisplayFrame {
+    DisplayFrame::Segments(self.alu.segment_output(&SegmentDecoder::new_s74x47()).to_vec())
+  }
+}
@@ -0,0 +1,33 @@
+//! Small bit twiddling helpers shared by the CPU cores
+
+use arbitrary_int::u4;
+
+/// Pack two nibbles into a byte, such as two BCD digits.
+///
+/// ### Example
+/// ```
+/// use arbitrary_int::u4;
+/// use chips::util::pack_bcd;
+///
+/// assert_eq!(pack_bcd(u4::new(4), u4::new(2)), 0x42);
+/// assert_eq!(pack_bcd(u4::new(0xF), u4::new(0)), 0xF0);
+/// ```
+#[inline]
+pub fn pack_bcd(high: u4, low: u4) -> u8 {
+  (high.value() << 4) | low.value()
+}
+
+/// Swap the high and low nibbles of a byte.
+///
+/// ### Example
+/// ```
+/// use chips::util::swap_nibbles;
+///
+/// assert_eq!(swap_nibbles(0x12), 0x21);
+/// assert_eq!(swap_nibbles(0xF0), 0x0F);
+/// assert_eq!(swap_nibbles(swap_nibbles(0xA5)), 0xA5);
+/// ```
+#[inline]
+pub fn swap_nibbles(byte: u8) -> u8 {
+  byte.rotate_left(4)
+}
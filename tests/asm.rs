@@ -0,0 +1,58 @@
+//! Assembling 8080 programs
+
+use chips::asm::{assemble, AsmError};
+use chips::cpu::i8080;
+use chips::disasm::{disassemble_all, Arch};
+
+#[test]
+fn mvi_and_jump_to_label() {
+  let program = assemble("
+    MVI A, 5
+    JMP label
+    NOP
+  label:
+    HLT
+  ").unwrap();
+  assert_eq!(program, [0x3E, 0x05, 0xC3, 0x06, 0x00, 0x00, 0x76]);
+
+  let listing: Vec<_> = disassemble_all(&program, Arch::I8080).map(|(_, text)| text).collect();
+  assert_eq!(listing, ["MVI A, 0x05", "JMP 0x0006", "NOP", "HLT"]);
+}
+
+#[test]
+fn number_formats() {
+  assert_eq!(assemble("MVI B, 42").unwrap(), [0x06, 42]);
+  assert_eq!(assemble("mvi b, 0x2a").unwrap(), [0x06, 0x2A]);
+  assert_eq!(assemble("MVI B, 2AH").unwrap(), [0x06, 0x2A]);
+  assert_eq!(assemble("LXI SP, 0FFFFH").unwrap(), [0x31, 0xFF, 0xFF]);
+  assert_eq!(assemble("DB 1, 2, 0x03").unwrap(), [1, 2, 3]);
+}
+
+#[test]
+fn errors() {
+  assert_eq!(assemble("NOP\nFOO A"), Err(AsmError::UnknownMnemonic { line: 2 }));
+  assert_eq!(assemble("MVI A, 256"), Err(AsmError::InvalidOperand { line: 1 }));
+  assert_eq!(assemble("MOV A"), Err(AsmError::InvalidOperand { line: 1 }));
+  assert_eq!(assemble("MOV M, M"), Err(AsmError::InvalidOperand { line: 1 }));
+  assert_eq!(assemble("JMP nowhere"), Err(AsmError::UndefinedLabel { line: 1 }));
+  assert_eq!(assemble("a: NOP\na: NOP"), Err(AsmError::DuplicateLabel { line: 2 }));
+  assert_eq!(assemble("é"), Err(AsmError::UnknownMnemonic { line: 1 }));
+  assert_eq!(assemble("éx A"), Err(AsmError::UnknownMnemonic { line: 1 }));
+
+  //256 lines of 256 bytes fill the whole address space
+  let fill = format!("DB {}\n", vec!["0"; 256].join(",")).repeat(256);
+  assert_eq!(assemble(&format!("{}end: NOP", fill)), Err(AsmError::AddressOverflow { line: 257 }));
+  let last = format!("{}\nlast: NOP", fill.strip_suffix(",0\n").unwrap()); //Drop the last byte
+  assert_eq!(assemble(&last).unwrap().len(), 0x1_0000);
+}
+
+#[test]
+fn disassembler_round_trip() {
+  //Undocumented opcodes disassemble as their documented twins, so they can't come back the same
+  let undocumented = [0x08, 0x10, 0x18, 0x20, 0x28, 0x30, 0x38, 0xCB, 0xD9, 0xDD, 0xED, 0xFD];
+  for opcode in (0..=0xFF_u8).filter(|opcode| !undocumented.contains(opcode)) {
+    let bytes = [opcode, 0x34, 0x12];
+    let (text, length) = i8080::disassemble(&bytes, 0);
+    assert_eq!(assemble(&text).unwrap(), bytes[..length], "{}", text);
+  }
+}
@@ -0,0 +1,48 @@
+//! Counting chips
+
+use arbitrary_int::u4;
+use chips::Counter;
+use chips::counter::BcdDigitCounter;
+
+#[test]
+fn bcd_digit_wraps_up() {
+  let mut digit = BcdDigitCounter::new();
+  for expected in 1..=9 {
+    assert!(!digit.up());
+    assert_eq!(digit.read(), u4::new(expected));
+  }
+  //9 -> 0 carries
+  assert!(digit.up());
+  assert_eq!(digit.read(), u4::new(0));
+}
+
+#[test]
+fn bcd_digit_wraps_down() {
+  let mut digit = BcdDigitCounter::new();
+  //0 -> 9 borrows
+  assert!(digit.down());
+  assert_eq!(digit.read(), u4::new(9));
+  for expected in (0..=8).rev() {
+    assert!(!digit.down());
+    assert_eq!(digit.read(), u4::new(expected));
+  }
+  digit.up();
+  digit.clear();
+  assert_eq!(digit.read(), u4::new(0));
+}
+
+#[test]
+fn counter_with_max() {
+  let mut counter: Counter<u8> = Counter::new_with_max(5);
+  let counts: Vec<u8> = (0..8).map(|_| counter.increment()).collect();
+  assert_eq!(counts, [1, 2, 3, 4, 5, 0, 1, 2]);
+
+  counter.clear();
+  let counts: Vec<u8> = (0..3).map(|_| counter.decrement()).collect();
+  assert_eq!(counts, [5, 4, 3]);
+
+  //Without a max, the whole type is used
+  let mut counter: Counter<u8> = Counter::new();
+  assert_eq!(counter.decrement(), 0xFF);
+  assert_eq!(counter.increment(), 0);
+}
@@ -0,0 +1,126 @@
+//! Generic CPU helpers
+
+use chips::cpu::{self, CPU};
+
+/// Memory which can only hand out a single byte at a time
+struct ByteMemory {
+  memory: Vec<u8>,
+}
+
+impl cpu::MemoryIO<u16> for ByteMemory {
+  fn read_mem<T: chips::ReadArr>(&self, address: u16) -> T {
+    T::read(&[self.memory[address as usize]])
+  }
+  fn write_mem<T: chips::WriteArr>(&mut self, address: u16, value: T) {
+    T::write(&mut self.memory[address as usize..=address as usize], value);
+  }
+}
+
+#[test]
+fn next_code_word_byte_memory() {
+  let io = ByteMemory { memory: vec![0x01, 0x34, 0x12] };
+  let mut cpu: CPU<u16> = CPU::new();
+  assert_eq!(cpu.next_code_byte(&io), 0x01);
+  assert_eq!(cpu.next_code_word(&io), 0x1234);
+  assert_eq!(cpu.pc, 3);
+}
+
+#[test]
+fn chip_names() {
+  use chips::cpu::{Chip, I8080, NullIo};
+  use chips::fairchild_f8::cpu3850;
+
+  fn name_of<IO>(chip: &impl Chip<IO>) -> &'static str {
+    chip.name()
+  }
+  assert_eq!(name_of::<NullIo>(&I8080::new()), "Intel 8080");
+  assert_eq!(name_of::<cpu3850::NullIo>(&cpu3850::CPU::new()), "Fairchild F3850");
+  assert_eq!(name_of::<()>(&chips::mcs4::Board::new(vec![0; 256], 1)), "Intel 4004");
+}
+
+#[test]
+fn chip_reset() {
+  use chips::cpu::{Chip, I8080, NullIo};
+
+  let mut io = NullIo::new(&[]);
+  let mut cpu = I8080::new();
+  cpu::run_for(&mut cpu, &mut io, 5);
+  assert_eq!(cpu.state().pc, 5);
+  Chip::<NullIo>::reset(&mut cpu);
+  assert_eq!(cpu.state().pc, 0);
+
+  let mut board = chips::mcs4::Board::new(vec![0; 256], 1);
+  cpu::run_for(&mut board, &mut (), 5);
+  assert_eq!(board.cpu.get_pc(), 5);
+  Chip::<()>::reset(&mut board);
+  assert_eq!(board.cpu.get_pc(), 0);
+}
+
+#[test]
+fn run_until_pc_every_core() {
+  use chips::fairchild_f8::cpu3850;
+
+  //F8 keeps its PC in the memory chips
+  let program = [0x29, 0x00, 0x10]; //JMP 0x0010
+  let mut io = cpu3850::NullIo::new(&program);
+  let mut f8 = cpu3850::CPU::new();
+  assert!(cpu::run_until_pc(&mut f8, &mut io, 0x0010, 100));
+  assert_eq!(io.pc0(), 0x0010);
+
+  let mut board = chips::mcs4::Board::new(vec![0; 256], 1); //NOPs
+  assert!(cpu::run_until_pc(&mut board, &mut (), 0x020, 1000));
+  assert!(!cpu::run_until_pc(&mut board, &mut (), 0x010, 100));
+}
+
+#[test]
+fn step_every_core() {
+  use chips::cpu::I8080;
+  use chips::fairchild_f8::cpu3850;
+
+  let program = [0x3E, 0x05, 0x3D, 0xC2, 0x02, 0x00, 0x76]; //MVI A, 5; DCR A; JNZ 0x0002; HLT
+  let mut io = cpu::NullIo::new(&program);
+  let mut i8080 = I8080::new();
+  for _ in 0..8 {
+    let step = i8080.step(&mut io);
+    assert_eq!(step.opcode, program[step.pc_before as usize]);
+  }
+
+  let program = [0x20, 0x42, 0x90, 0xFD]; //LI 0x42; BR back to the LI
+  let mut io = cpu3850::NullIo::new(&program);
+  let mut f8 = cpu3850::CPU::new();
+  for _ in 0..6 {
+    let step = f8.step(&mut io);
+    assert_eq!(step.opcode, program[step.pc_before as usize]);
+  }
+  assert_eq!(f8.step(&mut io).pc_before, 0);
+
+  let program = [0x20, 0x12, 0x60, 0x40, 0x00]; //FIM P0, 0x12; INC R0; JUN 0x000
+  let mut board = chips::mcs4::Board::from_rom_file(&program, 1).unwrap();
+  let steps: Vec<_> = (0..4).map(|_| board.step()).collect();
+  assert_eq!(steps[0], cpu::StepInfo { pc_before: 0, opcode: 0x20, cycles: 16 });
+  assert_eq!(steps[1], cpu::StepInfo { pc_before: 2, opcode: 0x60, cycles: 8 });
+  assert_eq!(steps[3].pc_before, 0);
+
+  //With 1 ROM, chip index 1 wraps back around to the only chip
+  let mut board = chips::mcs4::Board::from_rom_file(&[0xD7], 1).unwrap(); //LDM 7
+  board.cpu.set_pc(0x100);
+  assert_eq!(board.step(), cpu::StepInfo { pc_before: 0x100, opcode: 0xD7, cycles: 8 });
+  assert_eq!(board.acc().value(), 7);
+}
+
+#[test]
+fn wait_states() {
+  let io = ByteMemory { memory: vec![0x3E, 0x05, 0x00] };
+  let mut cpu: CPU<u16> = CPU::new();
+  //Each byte of a 7 cycle MVI held up by 2 wait cycles, reading the 250ns MK4015 at 10 MHz
+  let mut total = 7;
+  let mut bytes = vec![];
+  for _ in 0..2 {
+    let (byte, wait) = cpu.next_code_byte_wait::<chips::ram::MK4015Timing>(&io, 100);
+    bytes.push(byte);
+    total += wait;
+  }
+  assert_eq!(bytes, [0x3E, 0x05]);
+  assert_eq!(total, 11);
+  assert_eq!(cpu.pc, 2);
+}
@@ -0,0 +1,28 @@
+//! Segment decoder lookups
+
+use arbitrary_int::u4;
+use chips::SegmentDecoder;
+
+/// What `encode` would do without its cache
+fn linear_encode(decoder: &SegmentDecoder, segments: u8) -> Option<u4> {
+  (0..16).map(u4::new).find(|&value| decoder.decode(value) == segments)
+}
+
+#[test]
+fn encode_matches_linear_scan() {
+  let mut custom = [0b100_0000; 16];
+  custom[3] = 0b011_1111;
+  let decoders = [
+    SegmentDecoder::new_s74x47(),
+    SegmentDecoder::new_s74x247(),
+    SegmentDecoder::from_table(custom),
+  ];
+  //Run every lookup many times over, as a display redrawing every frame would
+  for _ in 0..100 {
+    for decoder in &decoders {
+      for segments in 0..=0xFF {
+        assert_eq!(decoder.encode(segments), linear_encode(decoder, segments), "segments {segments:08b}");
+      }
+    }
+  }
+}
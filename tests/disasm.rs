@@ -0,0 +1,69 @@
+//! Disassembling programs for each CPU core
+
+use chips::disasm::{disassemble_all, Arch};
+
+#[test]
+fn i8080_listing() {
+  let program = [
+    0x31, 0x00, 0x20, //LXI SP, 0x2000
+    0x3E, 0x05,       //MVI A, 0x05
+    0x3D,             //DCR A
+    0xC2, 0x05, 0x00, //JNZ 0x0005
+    0xCD, 0x00, 0x01, //CALL 0x0100
+    0x76,             //HLT
+  ];
+  let listing: Vec<_> = disassemble_all(&program, Arch::I8080).collect();
+  assert_eq!(listing.len(), 6);
+  assert_eq!(listing[3], (6, "JNZ 0x0005".into()));
+  assert_eq!(listing[5], (12, "HLT".into()));
+}
+
+#[test]
+fn mixed_program() {
+  //The same bytes mean different things to each core
+  let program = [0x20, 0x12, 0x2B, 0x40, 0x00, 0x1A];
+
+  let i4004: Vec<_> = disassemble_all(&program, Arch::I4004).map(|(_, text)| text).collect();
+  //The JCN at the end is missing its second word
+  assert_eq!(i4004, ["FIM P0, 0x12", "SRC P5", "JUN 0x000", "DB 0x1A"]);
+
+  let f3850: Vec<_> = disassemble_all(&program, Arch::F3850).map(|(_, text)| text).collect();
+  assert_eq!(f3850, ["LI 0x12", "NOP", "LR A, R0", "LR A, KU", "DI"]);
+
+  let i8080: Vec<_> = disassemble_all(&program, Arch::I8080).map(|(_, text)| text).collect();
+  assert_eq!(i8080, ["NOP", "STAX D", "DCX H", "MOV B, B", "NOP", "LDAX D"]);
+}
+
+#[test]
+fn every_opcode() {
+  for arch in [Arch::I8080, Arch::I4004, Arch::F3850] {
+    for opcode in 0..=0xFF {
+      let (text, length) = arch.disassemble(&[opcode, 0x00, 0x00], 0);
+      assert!(!text.is_empty());
+      assert!((1..=3).contains(&length), "{:?} {:02X}", arch, opcode);
+    }
+  }
+}
+
+#[test]
+fn i4004_labels() {
+  use chips::mcs4::cpu4004::disassemble_with_labels;
+
+  let program = [
+    0xD3,       //LDM 0x3
+    0xB0,       //XCH R0
+    0x61,       //INC R1
+    0x70, 0x02, //ISZ R0, back to the INC
+    0x50, 0x00, //JMS 0x000
+    0x43, 0x00, //JUN 0x300, past the end of the program
+  ];
+  let listing = disassemble_with_labels(&program);
+  assert_eq!(listing, [
+    (0, "L_000: LDM 0x3".into()),
+    (1, "XCH R0".into()),
+    (2, "L_002: INC R1".into()),
+    (3, "ISZ R0, L_002".into()),
+    (5, "JMS L_000".into()),
+    (7, "JUN 0x300".into()),
+  ]);
+}
@@ -0,0 +1,303 @@
+//! Fairchild F8 CPU tests, running against a flat 64K memory
+
+use chips::fairchild_f8::cpu3850;
+
+struct IO {
+  memory: Vec<u8>,
+  pc0: u16,
+  pc1: u16,
+  dc0: u16,
+  dc1: u16,
+}
+
+impl IO {
+  fn new(program: &[u8]) -> Self {
+    let mut memory = program.to_vec();
+    memory.resize(0x10000, 0x2B); //NOP
+    IO { memory, pc0: 0, pc1: 0, dc0: 0, dc1: 0 }
+  }
+}
+
+impl cpu3850::IO for IO {
+  fn output(&mut self, _port: u8, _value: u8) {}
+  fn input(&self, _port: u8) -> u8 { 0 }
+  fn read_external_port(&self, _port: u8) -> u8 { 0 }
+
+  fn next_code(&mut self) -> u8 {
+    let byte = self.memory[self.pc0 as usize];
+    self.pc0 = self.pc0.wrapping_add(1);
+    byte
+  }
+  fn peak_code(&self) -> i8 {
+    self.memory[self.pc0 as usize] as i8
+  }
+  fn next_data(&mut self) -> u8 {
+    let byte = self.memory[self.dc0 as usize];
+    self.dc0 = self.dc0.wrapping_add(1);
+    byte
+  }
+  fn write_data(&mut self, data: u8) {
+    self.memory[self.dc0 as usize] = data;
+    self.dc0 = self.dc0.wrapping_add(1);
+  }
+
+  fn jump(&mut self, upper: u8, lower: u8, push_pc: bool) {
+    if push_pc {
+      self.pc1 = self.pc0;
+    }
+    self.pc0 = u16::from_be_bytes([upper, lower]);
+  }
+  fn jump_relative(&mut self, relative_addr: i8) {
+    self.pc0 = self.pc0.wrapping_add(relative_addr as u16);
+  }
+  fn ret_pc(&mut self) {
+    self.pc0 = self.pc1;
+  }
+
+  fn add_dc0(&mut self, a: i8) {
+    self.dc0 = self.dc0.wrapping_add(a as u16);
+  }
+  fn get_dc0(&self) -> (u8, u8) {
+    let [upper, lower] = self.dc0.to_be_bytes();
+    (upper, lower)
+  }
+  fn set_dc0(&mut self, upper: u8, lower: u8) {
+    self.dc0 = u16::from_be_bytes([upper, lower]);
+  }
+  fn swap_dc(&mut self) {
+    core::mem::swap(&mut self.dc0, &mut self.dc1);
+  }
+
+  fn get_pc0(&self) -> (u8, u8) {
+    let [upper, lower] = self.pc0.to_be_bytes();
+    (upper, lower)
+  }
+  fn get_pc1(&self) -> (u8, u8) {
+    let [upper, lower] = self.pc1.to_be_bytes();
+    (upper, lower)
+  }
+  fn set_pc1(&mut self, upper: u8, lower: u8) {
+    self.pc1 = u16::from_be_bytes([upper, lower]);
+  }
+}
+
+fn trace(cpu: &mut cpu3850::CPU, io: &mut IO) -> Vec<cpu3850::Insn> {
+  let mut insns = Vec::new();
+  cpu.run_cycle_traced(io, &mut |insn| insns.push(insn));
+  insns
+}
+
+#[test]
+fn trace_instructions() {
+  let mut io = IO::new(&[
+    0x20, 0x42, //LI 0x42
+    0x70,       //LIS 0
+    0x81, 0x10, //BP +0x10 - not taken, as W starts out clear
+    0x90, 0x05, //BR +5 - taken
+  ]);
+  let mut cpu = cpu3850::CPU::new();
+
+  let insns = trace(&mut cpu, &mut io);
+  assert_eq!(insns.len(), 1);
+  let li = insns[0];
+  assert_eq!(li.opcode, 0x20);
+  assert_eq!(li.mnemonic, "LI");
+  assert_eq!(li.operands(), &[0x42]);
+  assert_eq!(li.cycles, 10);
+
+  let lis = trace(&mut cpu, &mut io)[0];
+  assert_eq!(lis.mnemonic, "LIS");
+  assert!(lis.operands().is_empty());
+
+  let bp = trace(&mut cpu, &mut io)[0];
+  assert_eq!((bp.opcode, bp.mnemonic), (0x81, "BT"));
+  assert_eq!(bp.operands(), &[0x10]);
+  assert_eq!(bp.cycles, 12);
+
+  //A taken branch only peaks at its displacement, which is still reported
+  let br = trace(&mut cpu, &mut io)[0];
+  assert_eq!(br.mnemonic, "BR");
+  assert_eq!(br.operands(), &[0x05]);
+  assert_eq!(br.cycles, 14);
+  assert_eq!(io.pc0, 6 + 5); //Relative to the displacement byte
+}
+
+#[test]
+fn trace_skips_reset() {
+  let mut io = IO::new(&[]);
+  let mut cpu = cpu3850::CPU::new();
+  cpu.reset = true;
+  assert!(trace(&mut cpu, &mut io).is_empty());
+  assert_eq!(trace(&mut cpu, &mut io)[0].mnemonic, "NOP");
+}
+
+#[test]
+fn advance_isar() {
+  for isar in 0..0b1000000_u8 {
+    let upper = isar & 0b111000;
+    let lower = isar & 0b111;
+    for op in 0..16 {
+      let expected = match op {
+        13 => upper | ((lower + 1) & 0b111),
+        14 => upper | (lower.wrapping_sub(1) & 0b111),
+        _ => isar,
+      };
+      assert_eq!(cpu3850::advance_isar(isar, op), expected, "isar {isar:o}, op {op}");
+    }
+  }
+  //The wrap cases never carry into, or borrow from, the upper octal digit
+  assert_eq!(cpu3850::advance_isar(0o77, 13), 0o70);
+  assert_eq!(cpu3850::advance_isar(0o07, 13), 0o00);
+  assert_eq!(cpu3850::advance_isar(0o10, 14), 0o17);
+  assert_eq!(cpu3850::advance_isar(0o00, 14), 0o07);
+}
+
+#[test]
+fn flags_round_trip() {
+  //(W bit, branch opcode testing it, whether the branch is taken with only that bit set)
+  let flags = [
+    (0, Some(0x81), true),  //Sign - BP, branch if positive
+    (1, Some(0x82), true),  //Carry - BC
+    (2, Some(0x84), true),  //Zero - BZ
+    (3, Some(0x98), false), //Overflow - BNO, branch if no overflow
+    (4, None, false),       //ICB
+  ];
+  for (bit, branch, taken) in flags {
+    let mut io = IO::new(&[
+      0x1D,       //LR W, J
+      0x1E,       //LR J, W
+      branch.unwrap_or(0x2B), 0x10,
+    ]);
+    let mut cpu = cpu3850::CPU::new();
+    cpu.regs[9] = 1 << bit;
+    cpu.run_cycle(&mut io);
+    cpu.regs[9] = 0;
+    cpu.run_cycle(&mut io);
+    assert_eq!(cpu.regs[9], 1 << bit, "W bit {bit}");
+    if branch.is_some() {
+      cpu.run_cycle(&mut io);
+      assert_eq!(io.pc0, if taken { 3 + 0x10 } else { 4 }, "W bit {bit}");
+    }
+  }
+}
+
+#[test]
+fn sign_flag_is_positive() {
+  let mut io = IO::new(&[
+    0x20, 0x01, //LI 0x01
+    0x18,       //COM - 0xFE, negative
+    0x1E,       //LR J, W
+  ]);
+  let mut cpu = cpu3850::CPU::new();
+  for _ in 0..3 {
+    cpu.run_cycle(&mut io);
+  }
+  assert_eq!(cpu.regs[9] & 1, 0);
+
+  let mut io = IO::new(&[
+    0x20, 0xFE, //LI 0xFE
+    0x18,       //COM - 0x01, positive
+    0x1E,       //LR J, W
+  ]);
+  for _ in 0..3 {
+    cpu.run_cycle(&mut io);
+  }
+  assert_eq!(cpu.regs[9] & 1, 1);
+}
+
+#[test]
+fn set_isar() {
+  let mut io = IO::new(&[
+    0x20, 0x42, //LI 0x42
+    0x5D,       //LR (IS)+, A
+    0x20, 0x43, //LI 0x43
+    0x5C,       //LR (IS), A
+  ]);
+  let mut cpu = cpu3850::CPU::new();
+  cpu.set_isar(0xFF);
+  assert_eq!(cpu.isar(), 0o77);
+  cpu.set_isar(0o25);
+  for _ in 0..4 {
+    cpu.run_cycle(&mut io);
+  }
+  assert_eq!(cpu.regs[0o25], 0x42);
+  assert_eq!(cpu.regs[0o26], 0x43);
+  assert_eq!(cpu.isar(), 0o26);
+}
+
+#[test]
+fn save_load_cpu() {
+  let mut io = IO::new(&[
+    0x20, 0x81, //LI 0x81
+    0x55,       //LR R5, A
+    0x6A,       //LISL 2
+    0x5C,       //LR (IS), A
+    0xB1,       //OUTS 1
+    0x1B,       //EI
+    0x24, 0x7F, //AI 0x7F
+  ]);
+  let mut cpu = cpu3850::CPU::new();
+  for _ in 0..7 {
+    cpu.run_cycle(&mut io);
+  }
+  let state = cpu.save_cpu();
+  assert_eq!(state.acc, 0x00);
+  assert_eq!(state.isar, 0o02);
+  assert!(state.carry && state.zero && state.interrupt);
+  assert_eq!((state.regs[5], state.regs[2]), (0x81, 0x81));
+  assert_eq!(state.ports[1], 0x81);
+
+  let mut copy = cpu3850::CPU::new();
+  copy.load_cpu(state);
+  assert_eq!(copy.save_cpu(), state);
+  assert_eq!((copy.acc(), copy.isar(), copy.flags()), (cpu.acc(), cpu.isar(), cpu.flags()));
+}
+
+#[test]
+fn named_registers() {
+  let mut cpu = cpu3850::CPU::new();
+  for (index, reg) in cpu.regs.iter_mut().enumerate() {
+    *reg = index as u8;
+  }
+  assert_eq!(cpu.j(), 0x09);
+  assert_eq!(cpu.hl(), 0x0A0B);
+  assert_eq!(cpu.k(), 0x0C0D);
+  assert_eq!(cpu.q(), 0x0E0F);
+
+  //LR K, P saves the return address, upper byte into KU
+  let mut io = IO::new(&[0x08]); //LR K, P
+  io.pc1 = 0x1234;
+  cpu.run_cycle(&mut io);
+  assert_eq!(cpu.k(), 0x1234);
+}
+
+#[test]
+fn invalid_opcode_nop() {
+  use chips::cpu::InvalidPolicy;
+  use std::sync::atomic::{AtomicU8, Ordering};
+
+  let mut io = IO::new(&[
+    0x2D,       //Unused
+    0x20, 0x42, //LI 0x42
+    0x2F,       //Unused
+  ]);
+  let mut cpu = cpu3850::CPU::new();
+  cpu.on_invalid = InvalidPolicy::Nop;
+  assert_eq!(cpu.run_cycle(&mut io), 4);
+  assert_eq!(io.pc0, 1);
+  cpu.run_cycle(&mut io);
+  assert_eq!(cpu.acc(), 0x42);
+
+  static SEEN: AtomicU8 = AtomicU8::new(0);
+  cpu.on_invalid = InvalidPolicy::Callback(|opcode| SEEN.store(opcode, Ordering::Relaxed));
+  cpu.run_cycle(&mut io);
+  assert_eq!(SEEN.load(Ordering::Relaxed), 0x2F);
+  assert_eq!(io.pc0, 4);
+}
+
+#[test]
+#[should_panic(expected = "Unknown opcode: 0x2E")]
+fn invalid_opcode_panics() {
+  let mut io = IO::new(&[0x2E]);
+  cpu3850::CPU::new().run_cycle(&mut io);
+}
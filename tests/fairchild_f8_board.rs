@@ -0,0 +1,281 @@
+//! Building a Fairchild F8 board, and its memory chips
+
+use arbitrary_int::u6;
+use chips::fairchild_f8::{dmi3852, psu3851, Board, BoardError, SoundTone};
+use chips::display::{DisplayFrame, HasDisplay};
+
+#[test]
+fn try_new() {
+  let board = Board::try_new(Some(vec![0; 0x800]), Some(vec![0; 0x400])).unwrap();
+  assert_eq!(board.roms.len(), 3);
+  assert!(Board::try_new(None, None).is_ok());
+
+  assert_eq!(Board::try_new(Some(vec![0; 0x500]), None).err(), Some(BoardError::RomNotAligned { length: 0x500 }));
+  assert_eq!(Board::try_new(Some(vec![0; 0x400]), Some(vec![0; 10])).err(), Some(BoardError::RomNotAligned { length: 10 }));
+  assert_eq!(Board::try_new(Some(vec![0; 0x400 * 64]), None).err(), Some(BoardError::TooManyRoms { count: 64 }));
+}
+
+#[test]
+fn custom_rams() {
+  let mut rom = vec![
+    0x2A, 0x88, 0x01, //DCI 0x8801
+    0x16,             //LM
+    0x27, 0x49,       //OUT 0x49
+  ];
+  rom.resize(0x400, 0x2B); //NOP
+  let mut board = Board::new(Some(rom), None).with_rams(&[
+    (u6::new(0x20), u6::new(0x10)),
+    (u6::new(0x21), u6::new(0x11)),
+    (u6::new(0x22), u6::new(0x12)),
+  ]);
+  assert_eq!(board.rams.len(), 3);
+  board.rams[2].ram.write(1, 0x5A_u8);
+
+  for _ in 0..3 {
+    board.run_cycle();
+  }
+  //Only the third chip has port 0x49
+  assert_eq!(board.read_port(0x49), 0x5A);
+  assert_eq!(board.rams[2].read_port(0x49), 0x5A);
+  assert_eq!(board.rams[0].read_port(0x49), 0);
+  assert_eq!(board.rams[1].read_port(0x49), 0);
+}
+
+#[test]
+fn maze_hardwire_with_rams() {
+  let mut rom = vec![
+    0x20, 0x09, //LI 0x09 - Write a 1 to hardwired address 0
+    0x27, 0x24, //OUT 0x24
+  ];
+  rom.resize(0x400, 0x2B); //NOP
+
+  //The RAM on ports 0x24 and 0x25 is the second chip
+  let mut board = Board::new(Some(rom.clone()), None).with_rams(&[
+    (u6::new(0xB), u6::new(0xC)),
+    (u6::new(0xA), u6::new(0b1001)),
+  ]);
+  board.run_cycle();
+  board.run_cycle();
+  assert!(board.rams[1].ram.read_bit(0));
+  assert!(!board.rams[0].ram.read_bit(0));
+
+  //No RAM on port 0x24 leaves the hardwire out
+  let mut board = Board::new(Some(rom), None).with_rams(&[
+    (u6::new(0xB), u6::new(0xC)),
+  ]);
+  board.run_cycle();
+  board.run_cycle();
+  assert!(!board.rams[0].ram.read_bit(0));
+}
+
+#[test]
+fn cpu_state() {
+  let mut rom = vec![
+    0x20, 0x42, //LI 0x42
+    0x6B,       //LISL 3
+    0x18,       //COM
+  ];
+  rom.resize(0x400, 0x2B); //NOP
+  let mut board = Board::new(Some(rom), None);
+  board.run_cycle();
+  assert_eq!(board.cpu.acc(), 0x42);
+  assert_eq!(board.cpu_state().acc, 0x42);
+
+  board.run_cycle();
+  board.run_cycle();
+  let state = board.cpu_state();
+  assert_eq!((state.acc, state.isar), (0xBD, 3));
+  //0xBD is negative, and the other flags are cleared by COM
+  assert_eq!(board.cpu.flags(), (false, false, false, false, false));
+  assert!(!state.sign && !state.zero);
+}
+
+#[test]
+fn counters_wrap() {
+  let mut data = [0; 0x400];
+  data[0x3FF] = 0xA5;
+  let mut psu = psu3851::F3851::new(data, u6::new(0x3F), u6::new(1));
+  psu.pc0 = 0xFFFF;
+  psu.dc0 = 0xFFFF;
+  assert_eq!(psu.next_code(), 0xA5);
+  assert_eq!(psu.next_data(), 0xA5);
+  assert_eq!((psu.pc0, psu.dc0), (0, 0));
+
+  let mut dmi = dmi3852::F3852::new(u6::new(0x3F), u6::new(1));
+  dmi.pc0 = 0xFFFF;
+  dmi.dc0 = 0xFFFF;
+  dmi.next_code();
+  dmi.write_data(0x5A);
+  assert_eq!((dmi.pc0, dmi.dc0), (0, 0));
+  dmi.dc0 = 0xFFFF;
+  dmi.next_data();
+  assert_eq!(dmi.dc0, 0);
+}
+
+#[test]
+fn owns_address() {
+  let psu = psu3851::F3851::new([0; 0x400], u6::new(2), u6::new(1));
+  assert!(psu.owns_code(0x0800) && psu.owns_data(0x0800));
+  assert!(psu.owns_code(0x0BFF) && psu.owns_data(0x0BFF));
+  assert!(!psu.owns_code(0x07FF) && !psu.owns_data(0x07FF));
+  assert!(!psu.owns_code(0x0C00) && !psu.owns_data(0x0C00));
+
+  let dmi = dmi3852::F3852::new(u6::new(0x3F), u6::new(1));
+  assert!(dmi.owns_code(0xFC00) && dmi.owns_data(0xFFFF));
+  assert!(!dmi.owns_code(0xFBFF) && !dmi.owns_data(0x0000));
+}
+
+#[test]
+fn display() {
+  let mut board = Board::new(None, None);
+  assert!(board.display_on());
+  //Pixel (1, 0) is color 1, pixel (0, 63) is color 3
+  board.vram[0].write_bit(1, true);
+  board.vram[1].write_bit(63 * 128 - 4096, true);
+  board.vram[3].write_bit(63 * 128 - 4096, true);
+  let DisplayFrame::Pixels { width, height, colors } = board.pixels() else { panic!("Channel F displays pixels") };
+  assert_eq!((width, height), (128, 64));
+  assert_eq!(colors[1], 1);
+  assert_eq!(colors[63 * 128], 3);
+  assert_eq!(colors.iter().filter(|&&color| color != 0).count(), 2);
+}
+
+#[test]
+fn run_frame() {
+  let mut rom = vec![
+    0x20, 0x20, //LI 0x20
+    0xB0,       //OUTS 0 - Enable writing pixels. Port 1 being 0 selects color 3, ports 4 and 5 select pixel (0, 0)
+  ];
+  rom.resize(0x400, 0x2B); //NOP
+  let mut board = Board::new(Some(rom), None);
+
+  let frame = board.run_frame();
+  assert!(frame.vram_changed);
+  assert!(frame.cycles >= Board::CYCLES_PER_FRAME);
+  let DisplayFrame::Pixels { colors, .. } = board.pixels() else { panic!("Channel F displays pixels") };
+  assert_eq!(colors[0], 3);
+
+  //The same pixel keeps getting written with the same color
+  assert!(!board.run_frame().vram_changed);
+}
+
+#[test]
+fn video_divisor() {
+  let mut board = Board::new(Some(vec![0x2B; 0x400]), None);
+  for _ in 0..1000 {
+    board.run_cycle();
+  }
+  assert_eq!(board.video_cycles(), 1000);
+
+  let mut board = Board::new(Some(vec![0x2B; 0x400]), None).with_video_divisor(3);
+  for _ in 0..1000 {
+    board.run_cycle();
+  }
+  //Runs on the first cycle, then every 3rd
+  assert_eq!(board.video_cycles(), 334);
+}
+
+#[test]
+fn read_pixel() {
+  let mut board = Board::new(None, None);
+  //Top half, color 2
+  board.vram[2].write_bit(10 * 128 + 5, true);
+  //Bottom half, color 1 and color 3
+  board.vram[1].write_bit(40 * 128 + 5 - 4096, true);
+  board.vram[1].write_bit(63 * 128 + 127 - 4096, true);
+  board.vram[3].write_bit(63 * 128 + 127 - 4096, true);
+
+  assert_eq!(board.read_pixel(5, 10), 2);
+  assert_eq!(board.read_pixel(5, 40), 1);
+  assert_eq!(board.read_pixel(127, 63), 3);
+  assert_eq!(board.read_pixel(5, 11), 0);
+  assert_eq!(board.read_pixel(6, 40), 0);
+}
+
+#[test]
+#[should_panic(expected = "off the 128x64 screen")]
+fn read_pixel_off_screen() {
+  //Would otherwise wrap around to pixel (0, 1)
+  Board::new(None, None).read_pixel(128, 0);
+}
+
+#[test]
+fn run_cycle_with_video() {
+  let mut board = Board::new(None, None);
+  board.ports[0] = 0b100000;    //Enable writing pixels
+  board.ports[1] = 0b0100_0000; //Inverted, selects color 2
+  board.ports[4] = 10;
+  board.ports[5] = 20;
+
+  let mut pixels = Vec::new();
+  board.run_cycle_with_video(|x, y, color| pixels.push((x, y, color)));
+  assert_eq!(pixels, [(10, 20, 2)]);
+  assert_eq!(board.read_pixel(10, 20), 2);
+
+  //Nothing is reported while writing is disabled
+  board.ports[0] = 0;
+  pixels.clear();
+  for _ in 0..16 {
+    board.run_cycle_with_video(|x, y, color| pixels.push((x, y, color)));
+  }
+  assert!(pixels.is_empty());
+}
+
+#[test]
+fn palette() {
+  let board = Board::new(None, None);
+  assert_eq!(board.palette_rgb(0), (0x10, 0x10, 0x10));
+  assert_eq!(board.palette_rgb(1), (0x4B, 0x3F, 0xF3));
+  assert_eq!(board.palette_rgb(2), (0xFF, 0x31, 0x53));
+  assert_eq!(board.palette_rgb(3), (0x02, 0xCC, 0x5D));
+
+  let gray = [(0, 0, 0), (0x55, 0x55, 0x55), (0xAA, 0xAA, 0xAA), (0xFF, 0xFF, 0xFF)];
+  let board = board.with_palette(gray);
+  for color in 0..4 {
+    assert_eq!(board.palette_rgb(color), gray[color as usize]);
+  }
+}
+
+#[test]
+fn sound() {
+  let mut rom = vec![
+    0x20, 0x40, //LI 0x40
+    0xB5,       //OUTS 5 - 1 kHz
+    0x20, 0xC5, //LI 0xC5
+    0xB5,       //OUTS 5 - 120 Hz, while also picking pixel row 5
+  ];
+  rom.resize(0x400, 0x2B); //NOP
+  let mut board = Board::new(Some(rom), None);
+  assert_eq!(board.sound_state(), SoundTone::Silence);
+  board.run_cycle();
+  board.run_cycle();
+  assert_eq!(board.sound_state(), SoundTone::High);
+  board.run_cycle();
+  board.run_cycle();
+  assert_eq!(board.sound_state(), SoundTone::Low);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "More than one memory chip answers at 0x0000")]
+fn overlapping_pages() {
+  let mut board = Board::new(Some(vec![0x2B; 0x400]), None);
+  //A second ROM wired to the same page as the first
+  board.roms.push(psu3851::F3851::new([0x2B; 0x400], u6::new(0), u6::new(2)));
+  board.run_cycle();
+}
+
+#[test]
+fn try_channel_f() {
+  let board = Board::try_channel_f(vec![0; Board::BIOS_SIZE], None).unwrap();
+  assert_eq!(board.roms.len(), 2);
+  let board = Board::try_channel_f(vec![0; Board::BIOS_SIZE], Some(vec![0; 0x800])).unwrap();
+  assert_eq!(board.roms.len(), 4);
+
+  //Truncated and overlong BIOS dumps
+  assert_eq!(Board::try_channel_f(vec![0; 0x400], None).err(), Some(BoardError::BiosSize { length: 0x400 }));
+  assert_eq!(Board::try_channel_f(vec![0; 0x801], None).err(), Some(BoardError::BiosSize { length: 0x801 }));
+  //Cartridges are checked too
+  assert_eq!(Board::try_channel_f(vec![0; 0x800], Some(vec![])).err(), Some(BoardError::EmptyCartridge));
+  assert_eq!(Board::try_channel_f(vec![0; 0x800], Some(vec![0; 0x7FF])).err(), Some(BoardError::RomNotAligned { length: 0x7FF }));
+}
@@ -0,0 +1,71 @@
+//! HP Classic calculator chips
+
+use arbitrary_int::u10;
+use chips::display::{DisplayFrame, HasDisplay};
+use chips::hp_classic::{Board, Register};
+use chips::rom;
+
+#[test]
+fn last_rom_word() {
+  let mut rom = vec![0; 320];
+  //Word 255 is bits 2550..=2559: the top 2 bits of byte 318, and all of byte 319
+  rom[318] = 0b1100_0000;
+  rom[319] = 0b1010_1010;
+  let mut board: Board<0> = Board::new(rom);
+  assert_eq!(board.roms[0].read(255).0, u10::new(0b10_1010_1011));
+  assert_eq!(board.roms[0].read(254).0, u10::new(0));
+}
+
+#[test]
+fn packed_round_trip() {
+  let words: Vec<u16> = (0..256).map(|index| (index * 0x25 + 7) & 0x3FF).collect();
+  let mut board: Board<0> = Board::new(rom::from_words(&words, 10));
+  for (index, &word) in words.iter().enumerate() {
+    assert_eq!(board.roms[0].read(index as u8).0, u10::new(word));
+  }
+}
+
+#[test]
+fn display_toggle() {
+  let words = [
+    0x028, //Display Toggle
+    0x028, //Display Toggle
+    0x028, //Display Toggle
+    0x228, //Display off
+  ];
+  let mut board: Board<0> = Board::new(rom::from_words(&words, 10));
+  assert!(!board.display_on());
+  board.run_cycle();
+  assert!(board.display_on());
+  board.run_cycle();
+  assert!(!board.display_on());
+  board.run_cycle();
+  assert!(board.display_on());
+  board.run_cycle();
+  assert!(!board.display_on());
+}
+
+#[test]
+fn display_digits() {
+  let mut board: Board<0> = Board::new(rom::from_words(&[0], 10));
+  board.anr.a = Register::new(0x01_2345_6789_0123);
+  board.anr.b = Register::new(0x90_0000_0000_0099);
+  let DisplayFrame::Digits(digits) = board.pixels() else { panic!("HP displays digits") };
+  let digits: Vec<u8> = digits.iter().map(|digit| digit.value()).collect();
+  assert_eq!(digits, [0xF, 1, 2, 3, 4, 5, 6, 7, 8, 9, 0, 1, 0xF, 0xF]);
+}
+
+#[test]
+fn cnt_state() {
+  let mut cnt = chips::hp_classic::cnt::CnT::new();
+  cnt.run_cycle(u10::new(0x53 << 2), true); //P = 5
+  cnt.run_cycle(u10::new(0b1111 << 2), true); //P++
+  cnt.run_cycle(u10::new(0x21 << 2), true); //S2 = true
+  let state = cnt.state();
+  assert_eq!(state.pointer.value(), 6);
+  assert!(state.status.read_bit(2));
+  assert_eq!(state.status.data, 0b100);
+  assert!(state.carry);
+  assert_eq!(state.next_address, 3);
+  assert_eq!(state.saved_address, 0);
+}
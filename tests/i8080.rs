@@ -0,0 +1,308 @@
+//! Small focused tests of the 8080 core
+
+use arbitrary_int::u3;
+use core::cell::Cell;
+use chips::cpu;
+
+struct IO {
+  memory: Vec<u8>,
+  /// Addresses written, in order
+  writes: Vec<u16>,
+  /// Reads of any size
+  reads: Cell<usize>,
+}
+
+impl IO {
+  fn new(program: &[u8]) -> Self {
+    let mut memory = program.to_vec();
+    memory.resize(0x1_0000, 0);
+    Self {
+      memory,
+      writes: Vec::new(),
+      reads: Cell::new(0),
+    }
+  }
+}
+
+impl cpu::i8080::IO for IO {
+  fn output(&mut self, _port: u8, _value: u8) {}
+  fn input(&mut self, _port: u8) -> u8 {
+    0
+  }
+}
+
+impl cpu::MemoryIO<u16> for IO {
+  fn read_mem<T: chips::ReadArr>(&self, address: u16) -> T {
+    self.reads.set(self.reads.get() + 1);
+    T::read(&self.memory[address as usize..])
+  }
+  fn write_mem<T: chips::WriteArr>(&mut self, address: u16, value: T) {
+    self.writes.push(address);
+    T::write(&mut self.memory[address as usize..], value);
+  }
+}
+
+#[test]
+fn state_display() {
+  let mut io = IO::new(&[0x3E, 0x42]); //MVI A, 0x42
+  let mut cpu = cpu::I8080::new();
+  cpu.run_cycle(&mut io);
+  let state = cpu.state();
+  assert_eq!(state.a, 0x42);
+  assert_eq!(state.pc, 2);
+  assert!(format!("{}", state).contains("A: 0x42"));
+}
+
+/// Flags of a real 8080 after adding `byte` (or its complement, when subtracting) to the accumulator
+fn reference_flags(acc: u8, byte: u8, subtract: bool) -> cpu::i8080::I8080State {
+  //The 8080 subtracts by adding the one's complement with the carry in set, then inverts the carry out.
+  let operand = if subtract { !byte } else { byte };
+  let sum = acc as u16 + operand as u16 + subtract as u16;
+  let result = sum as u8;
+  cpu::i8080::I8080State {
+    a: result,
+    sign: result & 0x80 != 0,
+    zero: result == 0,
+    aux: (acc ^ operand ^ result) & 0x10 != 0,
+    parity: result.count_ones().is_multiple_of(2),
+    carry: (sum > 0xFF) != subtract,
+    ..Default::default()
+  }
+}
+
+fn arithmetic_flags(opcode: u8, subtract: bool) {
+  let mut io = IO::new(&[
+    0x3E, 0, //MVI A, acc
+    0x06, 0, //MVI B, byte
+    opcode,  //ADD B or SUB B
+  ]);
+  for acc in 0..=0xFF {
+    for byte in 0..=0xFF {
+      io.memory[1] = acc;
+      io.memory[3] = byte;
+      let mut cpu = cpu::I8080::new();
+      for _ in 0..3 {
+        cpu.run_cycle(&mut io);
+      }
+      let state = cpu.state();
+      let expected = reference_flags(acc, byte, subtract);
+      assert_eq!(
+        (state.a, state.sign, state.zero, state.aux, state.parity, state.carry),
+        (expected.a, expected.sign, expected.zero, expected.aux, expected.parity, expected.carry),
+        "0x{:02X} {} 0x{:02X}", acc, if subtract { '-' } else { '+' }, byte
+      );
+    }
+  }
+}
+
+#[test]
+fn add_flags() {
+  arithmetic_flags(0x80, false);
+}
+
+#[test]
+fn sub_flags() {
+  arithmetic_flags(0x90, true);
+}
+
+#[test]
+fn reset() {
+  let mut io = IO::new(&[
+    0x3E, 0x42, //MVI A, 0x42
+    0x31, 0x00, 0x20, //LXI SP, 0x2000
+    0x37, //STC
+  ]);
+  let mut cpu = cpu::I8080::new();
+  for _ in 0..3 {
+    cpu.run_cycle(&mut io);
+  }
+  assert_eq!(cpu.state().sp, 0x2000);
+  cpu.reset();
+  let state = cpu.state();
+  assert_eq!((state.pc, state.sp), (0, 0xFFFF));
+  assert_eq!(state, cpu::I8080::new().state());
+}
+
+#[test]
+fn bdos_output() {
+  let mut ram: chips::RAM<0x1_0000> = chips::RAM::new();
+  cpu::i8080::load_com(&mut ram, &[
+    0x11, 0x10, 0x01, //LXI D, 0x110
+    0x0E, 0x09,       //MVI C, 9
+    0xCD, 0x05, 0x00, //CALL 5
+    0x1E, b'!',       //MVI E, '!'
+    0x0E, 0x02,       //MVI C, 2
+    0xCD, 0x05, 0x00, //CALL 5
+    0x00,             //NOP
+    b'H', b'I', b'$', //0x110
+  ]);
+  let mut printed = Vec::new();
+  let mut bdos = cpu::i8080::Bdos::new(IO::new(&ram.data), |byte| printed.push(byte));
+  let mut cpu = cpu::I8080::new();
+  //JMP, 6 instructions, the RET stub for each call, and the NOP
+  for _ in 0..10 {
+    bdos.run_cycle(&mut cpu);
+  }
+  assert_eq!(cpu.state().pc, 0x110);
+  assert_eq!(printed, b"HI!");
+}
+
+#[test]
+fn bdos_unterminated_string() {
+  let mut ram: chips::RAM<0x1_0000> = chips::RAM::new();
+  cpu::i8080::load_com(&mut ram, &[
+    0x0E, 0x09,       //MVI C, 9
+    0xCD, 0x05, 0x00, //CALL 5
+  ]);
+  assert!(!ram.data.contains(&b'$'));
+  let mut printed = Vec::new();
+  let mut bdos = cpu::i8080::Bdos::new(IO::new(&ram.data), |byte| printed.push(byte));
+  let mut cpu = cpu::I8080::new();
+  //JMP, MVI, CALL, and the RET stub
+  for _ in 0..4 {
+    bdos.run_cycle(&mut cpu);
+  }
+  assert_eq!(cpu.state().pc, 0x105);
+  assert_eq!(printed.len(), 0x1_0000);
+}
+
+#[test]
+fn hlt_runs_as_mov_m_m() {
+  let mut io = IO::new(&[
+    0x21, 0x34, 0x12, //LXI H, 0x1234
+    0x76,             //HLT
+  ]);
+  io.memory[0x1234] = 0x56;
+  let mut cpu = cpu::I8080::new();
+  cpu.run_cycle(&mut io);
+  cpu.run_cycle(&mut io);
+  assert_eq!(io.writes, [0x1234]);
+  assert_eq!(io.memory[0x1234], 0x56);
+  assert_eq!(cpu.state().pc, 4);
+}
+
+#[test]
+fn step_fetches_once() {
+  let mut io = IO::new(&[
+    0x00,             //NOP
+    0x3E, 0x05,       //MVI A, 5
+    0x21, 0x34, 0x12, //LXI H, 0x1234
+  ]);
+  let mut cpu = cpu::I8080::new();
+  cpu.step(&mut io);
+  assert_eq!(io.reads.get(), 1);
+  assert_eq!(cpu.step(&mut io).opcode, 0x3E);
+  assert_eq!(io.reads.get(), 3);
+  assert_eq!(cpu.step(&mut io).opcode, 0x21);
+  assert_eq!(io.reads.get(), 6);
+}
+
+#[test]
+fn custom_entry() {
+  let mut ram: chips::RAM<0x1_0000> = chips::RAM::new();
+  cpu::i8080::load_com(&mut ram, &[
+    0xC5, //PUSH B
+  ]);
+  let mut io = IO::new(&ram.data);
+  let mut cpu = cpu::I8080::new().with_entry(0x100).with_stack(0xF000);
+  assert_eq!((cpu.state().pc, cpu.state().sp), (0x100, 0xF000));
+  cpu.run_cycle(&mut io);
+  assert_eq!((cpu.state().pc, cpu.state().sp), (0x101, 0xEFFE));
+}
+
+#[test]
+fn trace_lda() {
+  let mut memory = vec![
+    0x3A, 0x34, 0x12, //LDA 0x1234
+  ];
+  memory.resize(0x1234, 0);
+  memory.push(0x42);
+  let mut accesses = Vec::new();
+  let mut io = cpu::TracingMemory::new(IO::new(&memory), |access, address, value| accesses.push((access, address, value)));
+  let mut cpu = cpu::I8080::new();
+  cpu.run_cycle(&mut io);
+  assert_eq!(io.inner.memory[0x1234], 0x42);
+  drop(io);
+  assert_eq!(cpu.state().a, 0x42);
+  assert_eq!(accesses, [
+    (cpu::Access::Read, 0x0000, 0x3A),
+    (cpu::Access::Read, 0x0001, 0x34),
+    (cpu::Access::Read, 0x0002, 0x12),
+    (cpu::Access::Read, 0x1234, 0x42),
+  ]);
+}
+
+#[test]
+fn interrupts() {
+  let mut io = IO::new(&[
+    0xFB, //EI
+    0xF3, //DI
+    0xFB, //EI
+  ]);
+  let mut cpu = cpu::I8080::new().with_stack(0x2000);
+  assert!(!cpu.interrupts_enabled());
+  cpu.run_cycle(&mut io);
+  assert!(cpu.interrupts_enabled());
+  cpu.run_cycle(&mut io);
+  assert!(!cpu.interrupts_enabled());
+  assert!(!cpu.interrupt(&mut io, u3::new(7)));
+  assert_eq!(cpu.state().pc, 2);
+
+  cpu.run_cycle(&mut io);
+  assert!(cpu.interrupt(&mut io, u3::new(7)));
+  assert!(!cpu.interrupts_enabled());
+  assert_eq!(cpu.state().pc, 0x38);
+  assert_eq!(io.memory[0x1FFE..0x2000], [0x03, 0x00]);
+}
+
+#[test]
+fn conditional_cycles() {
+  let mut cpu = cpu::I8080::new().with_stack(0x1000);
+  //Zero flag starts cleared
+  let mut io = IO::new(&[
+    0xCC, 0x00, 0x01, //CZ 0x0100, not taken
+    0xC4, 0x00, 0x01, //CNZ 0x0100, taken
+    0xCA, 0x00, 0x00, //JZ 0x0000, not taken
+    0xC2, 0x00, 0x00, //JNZ 0x0000, taken
+  ]);
+  io.memory[0x100] = 0xC8; //RZ, not taken
+  io.memory[0x101] = 0xC0; //RNZ, taken
+
+  let not_taken_call = cpu.run_cycle(&mut io);
+  let taken_call = cpu.run_cycle(&mut io);
+  assert_eq!((not_taken_call, taken_call), (11, 17));
+  assert_eq!(cpu.state().pc, 0x100);
+
+  let not_taken_return = cpu.run_cycle(&mut io);
+  let taken_return = cpu.run_cycle(&mut io);
+  assert_eq!((not_taken_return, taken_return), (5, 11));
+  assert_eq!(cpu.state().pc, 6);
+
+  //Conditional jumps cost the same either way
+  assert_eq!(cpu.run_cycle(&mut io), 10);
+  assert_eq!(cpu.run_cycle(&mut io), 10);
+  assert_eq!(cpu.state().pc, 0);
+}
+
+#[test]
+fn decode() {
+  use arbitrary_int::u2;
+  use cpu::i8080::{decode, AluOp, Instruction};
+
+  assert_eq!(decode(&[0x00]), (Instruction::Nop, 1));
+  assert_eq!(decode(&[0x21, 0x00, 0x20]), (Instruction::Lxi(u2::new(2), 0x2000), 3));
+  assert_eq!(decode(&[0x0A]), (Instruction::Ldax(u2::new(0)), 1));
+  assert_eq!(decode(&[0x78]), (Instruction::Mov(u3::new(7), u3::new(0)), 1));
+  assert_eq!(decode(&[0x76]), (Instruction::Hlt, 1));
+  assert_eq!(decode(&[0x96]), (Instruction::Alu(AluOp::Sub, u3::new(6)), 1));
+  assert_eq!(decode(&[0xCC, 0x00, 0x01]), (Instruction::Ccond(u3::new(1), 0x0100), 3));
+  assert_eq!(decode(&[0xF1]), (Instruction::Pop(u2::new(3)), 1));
+  assert_eq!(decode(&[0xDB, 0x10]), (Instruction::In(0x10), 2));
+  assert_eq!(decode(&[0xEF]), (Instruction::Rst(u3::new(5)), 1));
+  //Undocumented opcodes decode as what they run as
+  assert_eq!(decode(&[0x08]), (Instruction::Nop, 1));
+  assert_eq!(decode(&[0xD9]), (Instruction::Ret, 1));
+  assert_eq!(decode(&[0xFD, 0x34, 0x12]), (Instruction::Call(0x1234), 3));
+
+  assert_eq!(Instruction::Pop(u2::new(3)).to_string(), "POP PSW");
+}
@@ -0,0 +1,37 @@
+//! Small indexable registers
+
+use arbitrary_int::u4;
+use chips::{Indexer16, Indexer64};
+
+#[test]
+fn nibble_range() {
+  let mut indexer = Indexer64::new();
+  assert_eq!(indexer.try_write_nibble(15, u4::new(0xA)), Some(()));
+  assert_eq!(indexer.try_read_nibble(15), Some(u4::new(0xA)));
+  assert_eq!(indexer.try_write_nibble(16, u4::new(0xB)), None);
+  assert_eq!(indexer.try_read_nibble(16), None);
+  assert_eq!(indexer.data, 0xA000_0000_0000_0000);
+
+  let mut indexer = Indexer16::new();
+  assert_eq!(indexer.try_write_nibble(3, u4::new(0x5)), Some(()));
+  assert_eq!(indexer.try_read_nibble(3), Some(u4::new(0x5)));
+  assert_eq!(indexer.try_write_nibble(4, u4::new(0x6)), None);
+  assert_eq!(indexer.try_read_nibble(4), None);
+  assert_eq!(indexer.data, 0x5000);
+}
+
+#[test]
+fn bytes_round_trip() {
+  let mut indexer = Indexer64::new();
+  for index in 0..16 {
+    indexer.write_nibble(index, u4::new(15 - index));
+  }
+  let bytes = indexer.to_bytes();
+  assert_eq!(bytes[0], 0xEF); //Nibble 0 in the low half
+  assert_eq!(Indexer64::from_bytes(bytes), indexer);
+
+  let mut indexer = Indexer16::new();
+  indexer.write_nibble(3, u4::new(0x9));
+  assert_eq!(indexer.to_bytes(), [0x00, 0x90]);
+  assert_eq!(Indexer16::from_bytes([0x00, 0x90]), indexer);
+}
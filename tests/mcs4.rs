@@ -1,31 +1,11 @@
 //! Testing using the official MCS4 Evaluation Kit with 4001-0009
+#![cfg(feature = "helpers")]
 
-use arbitrary_int::{u2,u4};
-
-use log::warn;
-use chips::{rom,ram,cpu,cpu::i4004};
-
-use std::time;
-
-fn convert_ram_index(command_control: u4, designated_index: i4004::DesignatedIndex) -> usize {
-  let bank = match command_control.value() {
-    0b000 => 0,
-    0b001 => 1,
-    0b010 => 2,
-    0b100 => 3,
-    _ => { warn!("Invalid command control register: {}", command_control);
-      0
-    },
-  };
-  //bank takes upper 2 bits. designated index takes lower 2 bits.
-  (designated_index.chip_index().value() | (bank << 2)) as usize
-}
+use chips::mcs4;
 
 #[test]
 fn test() {
-//  TermLogger::init(LevelFilter::Trace, Config::default(), TerminalMode::Mixed, ColorChoice::Auto).unwrap();
-
-  let mut binary = vec![0xE2, 0xCF, 0x2A, 0x41, 0x50, 0xDE, 0x50, 0xE5, 0x30, 0xFE, 0x50, 0xEE, 0x50, 0xE5, 0x50, 0xEE,
+  let binary = vec![0xE2, 0xCF, 0x2A, 0x41, 0x50, 0xDE, 0x50, 0xE5, 0x30, 0xFE, 0x50, 0xEE, 0x50, 0xE5, 0x50, 0xEE,
                         0x50, 0xE5, 0x2A, 0x42, 0x5F, 0xFF, 0x57, 0x1A, 0x48, 0x24, 0x5F, 0xFF, 0x53, 0x20, 0x4C, 0x18,
                         0x5F, 0xFF, 0x4F, 0xFF, 0x22, 0xCB, 0xF0, 0x2B, 0xE1, 0x21, 0xE0, 0xF2, 0x71, 0x29, 0xE4, 0xF2,
                         0xE5, 0xF2, 0xE6, 0xF2, 0xE7, 0x60, 0x72, 0x29, 0xFA, 0x50, 0xF7, 0x73, 0x39, 0x25, 0xFA, 0xF5,
@@ -41,102 +21,34 @@ fn test() {
                         0xF0, 0x2B, 0xE4, 0x19, 0xD3, 0x40, 0x20, 0xF2, 0xE4, 0xD2, 0x21, 0xE1, 0x40, 0x20, 0x2B, 0xAB,
                         0xF1, 0xE1, 0xF5, 0xBB, 0xC0, 0x21, 0x23, 0x25, 0x27, 0x29, 0x2B, 0x2D, 0x2F, 0xC0, 0x32, 0x34,
                         0x36, 0x38, 0x3A, 0x3C, 0x3E, 0x30, 0xC0, 0xA4, 0xF5, 0xFD, 0xB4, 0xEA, 0xC0, 0x00, 0xFF, 0x00];
-    
-  binary.resize(0x100, 0xFF);
 
-  let mut i4001s = [rom::I4001::new(binary.try_into().unwrap())];
-  let mut i4002s = [ram::I4002::new(), ram::I4002::new()];
-  let mut i4004 = cpu::I4004::new();
-
-  let mut next_time = time::Instant::now();
-  let mut cycles = 0;
-  while cycles < 500 {
-    if time::Instant::now() >= next_time {
-      cycles += 1;
-      //Make Rust happy by borrowing things one at a time, then releasing them when done.
-      {
-        let mut i4004_io = I4004IO {
-          i4001s: &mut i4001s,
-          i4002s: &mut i4002s,
-        };
-        i4004.run_cycle(&mut i4004_io);
-      }
-      
-      //This is wired in the test board
-      i4004.set_test_flag(i4002s[0].read_ports().value() & 1 == 1);
-
-      if i4004.get_pc() & 0xFF == 0x50 {
-        return; //Success
-      }
-      next_time = time::Instant::now() + time::Duration::from_nanos(1 * 10_800); //10.8 microsecond delay. (8 clock ticket at 740 khz)
-    }
-  }
-  panic!("Failed to run all parts of test code successfully.");
+  //The test code reaches 0x50 in any ROM chip once every part of it passed
+  let outcome = mcs4::run_mcs4(&binary, 2, 1000, |pc| pc & 0xFF == 0x50);
+  assert!(outcome.success, "Failed to run all parts of test code successfully. Stopped at {:03X}", outcome.pc);
+  assert_eq!(outcome.pc & 0xFF, 0x50);
 }
 
-struct I4004IO<'a> {
-  i4001s: &'a mut [rom::I4001; 1],
-  i4002s: &'a mut [ram::I4002; 2],
+#[test]
+fn timeout() {
+  //JUN 0x000, forever
+  let outcome = mcs4::run_mcs4(&[0x40, 0x00], 1, 100, |pc| pc == 0x050);
+  assert!(!outcome.success);
+  assert_eq!(outcome.pc, 0x000);
+  assert_eq!(outcome.cycles, 100);
 }
-impl i4004::IO for I4004IO<'_> {
-  fn read_rom_byte(&self, address: i4004::ROMAddress) -> u8 {
-    let high_addr = address.chip_index().value() as usize;
-    let low_addr = address.offset();
-    let i4001 = &self.i4001s[high_addr % self.i4001s.len()];  //Wrap around
-    i4001.read(low_addr)
-  }
-  
-  fn read_rom_ports(&self, designated_index: i4004::DesignatedIndex) -> u4 {
-    let high_addr = (designated_index.chip_index() << 2 | designated_index.reg_index()).value() as usize;
-    let i4001 = &self.i4001s[high_addr % self.i4001s.len()];  //Wrap around
-    i4001.read_ports()
-  }
-  fn write_rom_ports(&mut self, designated_index: i4004::DesignatedIndex, value: u4) {
-    let high_addr = (designated_index.chip_index() << 2 | designated_index.reg_index()).value() as usize;
-    let i4001 = &mut self.i4001s[high_addr % self.i4001s.len()];  //Wrap around
-    i4001.write_ports(value);
-  }
-  
-  fn read_ram_character(&self, command_control: u4, designated_index: i4004::DesignatedIndex) -> u4 {
-    let high_addr = convert_ram_index(command_control, designated_index);
-    if let Some(i4002) = self.i4002s.get(high_addr) {
-      i4002.read_character(designated_index.reg_index(), designated_index.char_index())
-    } else {
-      warn!("Write to nonexisting ram {}.", high_addr);
-      u4::new(0)
-    }
-  }
-  fn write_ram_character(&mut self, command_control: u4, designated_index: i4004::DesignatedIndex, value: u4) {
-    let high_addr = convert_ram_index(command_control, designated_index);
-    if let Some(i4002) = self.i4002s.get_mut(high_addr) {
-      i4002.write_character(designated_index.reg_index(), designated_index.char_index(), value);
-    } else {
-      warn!("Write to nonexisting ram {}.", high_addr);
-    }
-  }
-  fn read_ram_status(&self, command_control: u4, designated_index: i4004::DesignatedIndex, status_index: u2) -> u4 {
-    let high_addr = convert_ram_index(command_control, designated_index);
-    if let Some(i4002) = self.i4002s.get(high_addr) {
-      i4002.read_status(designated_index.reg_index(), status_index)
-    } else {
-      warn!("Write to nonexisting ram {}.", high_addr);
-      u4::new(0)
-    }
-  }
-  fn write_ram_status(&mut self, command_control: u4, designated_index: i4004::DesignatedIndex, status_index: u2, value: u4) {
-    let high_addr = convert_ram_index(command_control, designated_index);
-    if let Some(i4002) = self.i4002s.get_mut(high_addr) {
-      i4002.write_status(designated_index.reg_index(), status_index, value);
-    } else {
-      warn!("Write to nonexisting ram {}.", high_addr);
-    }
-  }
-  fn write_ram_ports(&mut self, command_control: u4, designated_index: i4004::DesignatedIndex, value: u4) {
-    let high_addr = convert_ram_index(command_control, designated_index);
-    if let Some(i4002) = self.i4002s.get_mut(high_addr) {
-      i4002.write_ports(value);
-    } else {
-      warn!("Write to nonexisting ram {}.", high_addr);
-    }
-  }
+
+#[test]
+fn custom_success() {
+  let mut binary = vec![0; 0x200];
+  binary[0x000] = 0x40; //JUN 0x050
+  binary[0x001] = 0x50;
+  binary[0x050] = 0x41; //JUN 0x150
+  binary[0x051] = 0x50;
+  binary[0x150] = 0x41; //JUN 0x150, forever
+  binary[0x151] = 0x50;
+  //Passing through 0x050 is not a success when the program wants 0x150
+  let outcome = mcs4::run_mcs4(&binary, 1, 100, |pc| pc == 0x150);
+  assert!(outcome.success);
+  assert_eq!(outcome.pc, 0x150);
+  assert_eq!(outcome.cycles, 4);
 }
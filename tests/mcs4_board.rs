@@ -0,0 +1,354 @@
+//! Building and debugging an MCS-4 board
+
+use arbitrary_int::{u2, u4};
+use chips::mcs4::{Addr12, Address, Board, BoardError, Byte};
+use chips::{Indexer16, Indexer64, Snapshot};
+
+#[test]
+fn from_rom_file() {
+  let board = Board::from_rom_file(&[0xD5; 0x180], 1).unwrap();
+  assert_eq!(board.roms.len(), 2);
+  assert_eq!(board.rams.len(), 1);
+  //The second chip is only half filled, the rest is padding
+  assert_eq!(board.roms[1].get_opcode(Address::new_with_raw_value(0x17F)), 0xD5);
+  assert_eq!(board.roms[1].get_opcode(Address::new_with_raw_value(0x180)), 0);
+
+  assert_eq!(Board::from_rom_file(&[], 1).err(), Some(BoardError::EmptyRom));
+  assert_eq!(Board::from_rom_file(&[0; 0x1001], 1).err(), Some(BoardError::RomTooLarge { length: 0x1001 }));
+}
+
+#[test]
+fn try_new() {
+  assert!(Board::try_new(vec![0; 0x200], 4).is_ok());
+  assert_eq!(Board::try_new(vec![], 1).err(), Some(BoardError::EmptyRom));
+  assert_eq!(Board::try_new(vec![0; 0x180], 1).err(), Some(BoardError::RomNotAligned { length: 0x180 }));
+  assert_eq!(Board::try_new(vec![0; 0x1100], 1).err(), Some(BoardError::RomTooLarge { length: 0x1100 }));
+  assert_eq!(Board::try_new(vec![0; 0x100], 5).err(), Some(BoardError::TooManyRams { count: 5 }));
+}
+
+#[test]
+fn peek_poke() {
+  let mut board = Board::from_rom_file(&[
+    0x20, 0x05, //FIM P0, 0x05
+    0x21,       //SRC P0
+    0xE9,       //RDM
+    0x20, 0x06, //FIM P0, 0x06
+    0x21,       //SRC P0
+    0xE0,       //WRM
+  ], 1).unwrap();
+  assert_eq!(board.peek_rom(Address::new_with_raw_value(0x003)), 0xE9);
+
+  board.poke_ram(0, u2::new(0), u4::new(5), u4::new(0xA));
+  assert_eq!(board.peek_ram(0, u2::new(0), u4::new(5)), u4::new(0xA));
+  for _ in 0..8 {
+    board.run_cycle();
+  }
+  //The guest read the poked character, and copied it over
+  assert_eq!(board.peek_ram(0, u2::new(0), u4::new(6)), u4::new(0xA));
+}
+
+#[test]
+fn reset() {
+  let rom = [
+    0xD5,       //LDM 5
+    0xFA,       //STC
+    0x50, 0x10, //JMS 0x010
+  ];
+  let mut board = Board::from_rom_file(&rom, 1).unwrap();
+  for _ in 0..4 {
+    board.run_cycle();
+  }
+  let fresh = Board::from_rom_file(&rom, 1).unwrap();
+  assert_ne!(board.cpu.save(), fresh.cpu.save());
+  board.cpu.reset();
+  //PC, accumulator, carry and the stack are all back to power on
+  assert_eq!(board.cpu.save(), fresh.cpu.save());
+}
+
+#[test]
+fn run_for() {
+  let mut board = Board::from_rom_file(&[
+    0x20, 0x12, //FIM P0, 0x12
+    0xD5,       //LDM 5
+  ], 1).unwrap();
+  //The FIM takes two instruction cycles
+  assert_eq!(chips::cpu::run_for(&mut board, &mut (), 2), 3 * 8);
+}
+
+/// Runs `setup` (which is `instructions` long), then DAA, and returns the resulting accumulator and carry
+fn decimal_adjust(setup: &[u8], instructions: usize) -> (u4, u4) {
+  let mut rom = setup.to_vec();
+  rom.extend_from_slice(&[
+    0xFB,       //DAA
+    0x20, 0x00, //FIM P0, 0x00
+    0x21,       //SRC P0
+    0xE0,       //WRM
+    0xF7,       //TCC
+    0x20, 0x01, //FIM P0, 0x01
+    0x21,       //SRC P0
+    0xE0,       //WRM
+  ]);
+  let mut board = Board::from_rom_file(&rom, 1).unwrap();
+  chips::cpu::run_for(&mut board, &mut (), instructions + 8);
+  (board.peek_ram(0, u2::new(0), u4::new(0)), board.peek_ram(0, u2::new(0), u4::new(1)))
+}
+
+#[test]
+fn daa() {
+  //(a, b, accumulator after ADD and DAA, carry)
+  let sums = [
+    (3, 4, 7, 0),
+    (0, 0, 0, 0),
+    (9, 0, 9, 0),
+    (5, 5, 0, 1),
+    (8, 7, 5, 1),
+    //ADD already carried, DAA keeps the carry and corrects the low digit
+    (9, 9, 8, 1),
+  ];
+  for (a, b, acc, carry) in sums {
+    let setup = [
+      0xD0 | a, //LDM a
+      0xB2,     //XCH R2
+      0xD0 | b, //LDM b
+      0xF1,     //CLC
+      0x82,     //ADD R2
+    ];
+    assert_eq!(decimal_adjust(&setup, 5), (u4::new(acc), u4::new(carry)), "{a} + {b}");
+  }
+
+  //IAC from 9 needs adjusting
+  assert_eq!(decimal_adjust(&[0xD9, 0xF2], 2), (u4::new(0), u4::new(1)));
+  //IAC wrapping around sets carry, so DAA adjusts even a small accumulator
+  assert_eq!(decimal_adjust(&[0xDF, 0xF2], 2), (u4::new(6), u4::new(1)));
+  //A stale carry is not cleared, even when no adjustment is needed
+  assert_eq!(decimal_adjust(&[0xD2, 0xFA], 2), (u4::new(8), u4::new(1)));
+}
+
+#[test]
+fn test_line() {
+  let mut rom = vec![0; 0x20];
+  rom[0x00..0x09].copy_from_slice(&[
+    0x11, 0x10, //JCN T, 0x10
+    0x20, 0x00, //FIM P0, 0x00
+    0x21,       //SRC P0
+    0xD1,       //LDM 1
+    0xE0,       //WRM
+    0x40, 0x00, //JUN 0x000
+  ]);
+  rom[0x10..0x17].copy_from_slice(&[
+    0x20, 0x00, //FIM P0, 0x00
+    0x21,       //SRC P0
+    0xD2,       //LDM 2
+    0xE0,       //WRM
+    0x40, 0x00, //JUN 0x000
+  ]);
+  let mut board = Board::from_rom_file(&rom, 1).unwrap();
+
+  assert!(!board.test_line());
+  chips::cpu::run_for(&mut board, &mut (), 6);
+  assert_eq!(board.peek_ram(0, u2::new(0), u4::new(0)), u4::new(1));
+
+  board.signal_test(true);
+  assert!(board.test_line());
+  chips::cpu::run_for(&mut board, &mut (), 5);
+  assert_eq!(board.peek_ram(0, u2::new(0), u4::new(0)), u4::new(2));
+}
+
+#[test]
+fn dump_ram() {
+  let mut board = Board::from_rom_file(&[
+    0x20, 0x13, //FIM P0, 0x13 - register 1, character 3
+    0x21,       //SRC P0
+    0xD9,       //LDM 9
+    0xE0,       //WRM
+    0xD5,       //LDM 5
+    0xE4,       //WR0
+  ], 1).unwrap();
+  chips::cpu::run_for(&mut board, &mut (), 6);
+
+  let dump = board.dump_ram(0);
+  let mut characters = Indexer64::new();
+  characters.write_nibble(3, u4::new(9));
+  let mut status = Indexer16::new();
+  status.write_nibble(0, u4::new(5));
+  assert!(dump[1].0 == characters);
+  assert_eq!(dump[1].1, status);
+  for reg in [0, 2, 3] {
+    assert!(dump[reg].0 == Indexer64::new());
+    assert_eq!(dump[reg].1, Indexer16::new());
+  }
+}
+
+#[test]
+fn rom_input_lines() {
+  let mut board = Board::from_rom_file(&[
+    0x20, 0x00, //FIM P0, 0x00
+    0x21,       //SRC P0
+    0xDF,       //LDM 0xF
+    0xE2,       //WRR
+    0xEA,       //RDR
+    0xE0,       //WRM
+  ], 1).unwrap();
+  board.roms[0].configure_ports(u4::new(0b0011));
+  board.roms[0].set_input_line(u2::new(0), true);
+  board.roms[0].set_input_line(u2::new(1), false);
+  chips::cpu::run_for(&mut board, &mut (), 6);
+
+  //The output lines hold the written data, the input lines read what was driven
+  assert_eq!(board.roms[0].ports, u4::new(0xF));
+  assert_eq!(board.roms[0].read_ports(), u4::new(0b1101));
+  assert_eq!(board.peek_ram(0, u2::new(0), u4::new(0)), u4::new(0b1101));
+}
+
+#[test]
+fn manual_phases() {
+  let mut board = Board::from_rom_file(&[
+    0x41, 0x23, //JUN 0x123
+  ], 1).unwrap();
+  for _ in 0..2 {
+    let address = board.cpu.set_address_phase();
+    let opcode = Byte::new_with_raw_value(board.peek_rom(address));
+    board.cpu.fetch_phase(opcode);
+    board.cpu.execute_phase(u4::new(0));
+  }
+  assert_eq!(board.cpu.set_address_phase().raw_value(), 0x123);
+}
+
+#[test]
+fn set_pc() {
+  let mut rom = vec![0; 0x200];
+  rom[0x105] = 0x40; //JUN 0x020
+  rom[0x106] = 0x20;
+  let mut board = Board::from_rom_file(&rom, 1).unwrap();
+  board.cpu.set_pc(0x105);
+  assert_eq!(board.cpu.get_pc(), 0x105);
+  chips::cpu::run_for(&mut board, &mut (), 1);
+  assert_eq!(board.cpu.get_pc(), 0x020);
+
+  //Addresses past 12 bits wrap around
+  board.cpu.set_pc(0x1105);
+  assert_eq!(board.cpu.get_pc(), 0x105);
+}
+
+#[test]
+fn addr12_fields() {
+  for raw in 0..=0xFFF {
+    let address = Addr12::new_with_raw_value(raw);
+    //Same layout as the ROM chip index, high and low addresses
+    assert_eq!(address.chip_index().value() as u16, raw >> 8);
+    assert_eq!(address.high().value() as u16, (raw >> 4) & 0xF);
+    assert_eq!(address.low().value() as u16, raw & 0xF);
+    //The byte inside of the ROM chip is the high and low addresses together
+    assert_eq!(address.offset(), (address.high().value() << 4) | address.low().value());
+    assert_eq!(Addr12::from_parts(address.chip_index(), address.offset()).raw_value(), raw);
+    assert_eq!(Addr12::wrap(raw | 0xF000).raw_value(), raw);
+  }
+  //The older name is the same type
+  let address: Address = Addr12::wrap(0x17F);
+  assert_eq!(address.offset(), 0x7F);
+}
+
+#[test]
+fn address_wraps() {
+  let address = Addr12::new_with_raw_value(0xFFF).next();
+  assert_eq!(address.raw_value(), 0x000);
+  assert_eq!(address.chip_index(), u4::new(0));
+
+  //NOP at the very last address
+  let mut board = Board::from_rom_file(&[0; 0x100], 1).unwrap();
+  board.cpu.set_pc(0xFFF);
+  chips::cpu::run_for(&mut board, &mut (), 1);
+  assert_eq!(board.cpu.get_pc(), 0x000);
+}
+
+#[test]
+fn registers() {
+  let mut board = Board::from_rom_file(&[
+    0x20, 0x12, //FIM P0, 0x12
+    0x2E, 0xEF, //FIM P7, 0xEF
+  ], 1).unwrap();
+  for _ in 0..4 {
+    board.run_cycle();
+  }
+  let mut expected = [u4::new(0); 16];
+  expected[0] = u4::new(1);
+  expected[1] = u4::new(2);
+  expected[14] = u4::new(0xE);
+  expected[15] = u4::new(0xF);
+  assert_eq!(board.cpu.registers(), expected);
+}
+
+#[test]
+fn acc_and_carry() {
+  let mut board = Board::from_rom_file(&[
+    0xD7, //LDM 7
+    0xFA, //STC
+  ], 1).unwrap();
+  assert_eq!(board.acc(), u4::new(0));
+  chips::cpu::run_for(&mut board, &mut (), 1);
+  assert_eq!(board.acc(), u4::new(7));
+  assert_eq!(board.cpu.acc(), u4::new(7));
+  assert!(!board.carry());
+  chips::cpu::run_for(&mut board, &mut (), 1);
+  assert!(board.carry());
+  assert!(!board.test());
+  board.signal_test(true);
+  assert!(board.test());
+}
+
+#[test]
+fn run_instructions() {
+  let mut board = Board::from_rom_file(&[
+    0x20, 0x12, //FIM P0, 0x12
+    0x00,       //NOP
+    0x00,       //NOP
+  ], 1).unwrap();
+  assert_eq!(board.run_instructions(0), 0);
+  assert_eq!(board.cpu.get_pc(), 0);
+  assert_eq!(board.run_instructions(2), 3 * 8);
+  assert_eq!(board.cpu.get_pc(), 3);
+  board.run_instructions(1);
+  assert_eq!(board.cpu.get_pc(), 4);
+}
+
+/// Runs `program` followed by XCH RE, TCC, XCH RF, to get the accumulator and carry out into registers
+fn acc_carry(program: &[u8]) -> (u8, u8) {
+  let mut rom = program.to_vec();
+  rom.extend_from_slice(&[0xBE, 0xF7, 0xBF]);
+  let mut board = Board::from_rom_file(&rom, 1).unwrap();
+  chips::cpu::run_for(&mut board, &mut (), rom.len()); //All one word instructions
+  let registers = board.cpu.registers();
+  (registers[14].value(), registers[15].value())
+}
+
+#[test]
+fn carry_boundaries() {
+  //ADD R0, with R0 loaded by LDM n, XCH R0
+  assert_eq!(acc_carry(&[0xD1, 0xB0, 0xDF, 0x80]), (0x0, 1)); //0xF + 1
+  assert_eq!(acc_carry(&[0xD8, 0xB0, 0xD8, 0x80]), (0x0, 1)); //0x8 + 0x8
+  assert_eq!(acc_carry(&[0xD8, 0xB0, 0xD7, 0x80]), (0xF, 0)); //0x7 + 0x8
+  assert_eq!(acc_carry(&[0xD8, 0xB0, 0xFA, 0xD7, 0x80]), (0x0, 1)); //0x7 + 0x8 + carry
+
+  //SUB R0. Carry in set is a borrow, but carry out set means no borrow, so multi digit code runs CMC in between
+  assert_eq!(acc_carry(&[0xD5, 0xB0, 0xD5, 0x90]), (0x0, 1)); //5 - 5
+  assert_eq!(acc_carry(&[0xD5, 0xB0, 0xD4, 0x90]), (0xF, 0)); //4 - 5
+  assert_eq!(acc_carry(&[0xD5, 0xB0, 0xFA, 0xD5, 0x90]), (0xF, 0)); //5 - 5 - borrow
+
+  //IAC and DAC agree with ADD and SUB of 1
+  assert_eq!(acc_carry(&[0xDF, 0xF2]), (0x0, 1));
+  assert_eq!(acc_carry(&[0xDE, 0xF2]), (0xF, 0));
+  assert_eq!(acc_carry(&[0xD0, 0xF8]), (0xF, 0));
+  assert_eq!(acc_carry(&[0xD1, 0xF8]), (0x0, 1));
+}
+
+#[test]
+fn invalid_opcode_nop() {
+  let mut board = Board::from_rom_file(&[0xFF, 0xD5], 1).unwrap();
+  board.cpu.on_invalid = chips::cpu::InvalidPolicy::Nop;
+  board.run_cycle();
+  assert_eq!(board.cpu.get_pc(), 1);
+  //Kept through a reset
+  board.cpu.reset();
+  board.run_cycle();
+  assert_eq!(board.cpu.get_pc(), 1);
+}
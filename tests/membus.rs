@@ -0,0 +1,30 @@
+//! A CPU reading and writing through the memory mapped bus
+
+use chips::{cpu::MemoryIO, membus::Bus, RAM, ROM};
+
+#[test]
+fn rom_and_ram() {
+  let mut bus = Bus::new();
+  bus.map(0x0000..0x0100, ROM::new([0xAA; 0x100]));
+  bus.map(0x2000..0x2400, RAM::<0x400>::new());
+
+  assert_eq!(bus.read_mem::<u8>(0x00FF), 0xAA);
+  //Writes to ROM are ignored
+  bus.write_mem(0x0010, 0x1234_u16);
+  assert_eq!(bus.read_mem::<u16>(0x0010), 0xAAAA);
+
+  bus.write_mem(0x23FF, 0x5678_u16);
+  assert_eq!(bus.read_mem::<u8>(0x23FF), 0x78);
+  //The high byte fell past the end of the RAM, onto unmapped memory
+  assert_eq!(bus.read_mem::<u16>(0x23FF), 0xFF78);
+
+  bus.write_mem(0x2000, 0x9ABC_u16);
+  assert_eq!(bus.read_mem::<u16>(0x2000), 0x9ABC);
+}
+
+#[test]
+#[should_panic(expected = "longer than the 256 byte chip")]
+fn range_past_chip() {
+  let mut bus = Bus::new();
+  bus.map(0x0000..0x0200, RAM::<0x100>::new());
+}
@@ -0,0 +1,34 @@
+//! Bit level access to RAM chips
+
+use chips::RAM;
+
+#[test]
+fn bit_span_across_bytes() {
+  let mut ram: RAM<4> = RAM::new();
+  ram.data = [0xFF; 4];
+  //Bits 6 through 17 span three bytes
+  ram.write_bits(6, 12, 0b1010_0101_1100);
+  assert_eq!(ram.data, [0b0011_1111, 0b1001_0111, 0b1111_1110, 0xFF]);
+  assert_eq!(ram.read_bits(6, 12), 0b1010_0101_1100);
+
+  //Matches reading one bit at a time
+  let one_by_one = (0..12).fold(0, |value, bit| value | ((ram.read_bit(6 + bit) as u64) << bit));
+  assert_eq!(one_by_one, 0b1010_0101_1100);
+}
+
+#[test]
+fn full_width() {
+  let mut ram: RAM<9> = RAM::new();
+  ram.write_bits(3, 64, 0x0123_4567_89AB_CDEF);
+  assert_eq!(ram.read_bits(3, 64), 0x0123_4567_89AB_CDEF);
+  assert_eq!(ram.read_bits(0, 3), 0);
+  assert_eq!(ram.read_bits(0, 0), 0);
+}
+
+#[test]
+fn access_timing() {
+  use chips::ram::{AccessTiming, MK4015Timing, MK4027Timing};
+
+  assert_eq!((MK4015Timing::ACCESS_NS, MK4015Timing::CYCLE_NS), (250, 380));
+  assert_eq!((MK4027Timing::ACCESS_NS, MK4027Timing::CYCLE_NS), (200, 375));
+}
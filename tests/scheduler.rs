@@ -0,0 +1,24 @@
+//! Chips on one board, running at different clock rates
+
+use chips::scheduler::Scheduler;
+
+#[test]
+fn clock_ratio() {
+  //An MCS-4 style board: the CPU runs every 8 master ticks, while a peripheral runs 3 times slower.
+  let mut scheduler = Scheduler::new();
+  let fast = scheduler.add(8);
+  let slow = scheduler.add(24);
+  let mut fast_cycles = 0;
+  let mut slow_cycles = 0;
+
+  for _ in 0..240 {
+    scheduler.tick(|chip| match chip {
+      chip if chip == fast => fast_cycles += 1,
+      chip if chip == slow => slow_cycles += 1,
+      _ => unreachable!(),
+    });
+  }
+  assert_eq!(fast_cycles, 30);
+  assert_eq!(slow_cycles, 10);
+  assert_eq!(scheduler.elapsed(), 240);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "serde")]
+
+use chips::{RAM, ROM};
+use serde::de::value::{BytesDeserializer, Error};
+use serde::{Deserialize, Deserializer};
+use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, Token};
+
+/// The data of a deserialized RAM chip, as the chips themselves can't be compared
+#[derive(Debug, PartialEq)]
+struct RamData<const LENGTH: usize>([u8; LENGTH]);
+
+impl<'de, const LENGTH: usize> Deserialize<'de> for RamData<LENGTH> {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    RAM::<LENGTH>::deserialize(deserializer).map(|ram| Self(ram.data))
+  }
+}
+
+/// The data of a deserialized ROM chip
+#[derive(Debug, PartialEq)]
+struct RomData<const LENGTH: usize>([u8; LENGTH]);
+
+impl<'de, const LENGTH: usize> Deserialize<'de> for RomData<LENGTH> {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    ROM::<LENGTH>::deserialize(deserializer).map(|rom| Self(core::array::from_fn(|addr| rom.read::<u8>(addr))))
+  }
+}
+
+#[test]
+fn ram_round_trip() {
+  let mut ram: RAM<4> = RAM::new();
+  ram.write(1, 0xBEEF_u16);
+  ram.enable_profiling();
+  assert_ser_tokens(&ram, &[Token::Bytes(&[0x00, 0xEF, 0xBE, 0x00])]);
+  assert_de_tokens(&RamData(ram.data), &[Token::Bytes(&[0x00, 0xEF, 0xBE, 0x00])]);
+
+  //Profiling is left off
+  let mut copy = RAM::<4>::deserialize(BytesDeserializer::<Error>::new(&ram.data)).unwrap();
+  assert!(copy.access_counts().is_none());
+}
+
+#[test]
+fn rom_round_trip() {
+  let rom: ROM<3> = ROM::new([10, 20, 30]);
+  assert_ser_tokens(&rom, &[Token::Bytes(&[10, 20, 30])]);
+  assert_de_tokens(&RomData([10, 20, 30]), &[Token::Bytes(&[10, 20, 30])]);
+}
+
+#[test]
+fn sequence() {
+  //Formats without a bytes type hand over a sequence instead
+  assert_de_tokens(&RamData([5, 6]), &[Token::Seq { len: Some(2) }, Token::U8(5), Token::U8(6), Token::SeqEnd]);
+}
+
+#[test]
+fn wrong_length() {
+  assert_de_tokens_error::<RAM<4>>(&[Token::Bytes(&[1, 2, 3])], "invalid length 3, expected 4 bytes");
+  assert_de_tokens_error::<ROM<2>>(&[Token::Bytes(&[1, 2, 3])], "invalid length 3, expected 2 bytes");
+  assert_de_tokens_error::<RAM<2>>(&[Token::Seq { len: Some(3) }, Token::U8(5), Token::U8(6), Token::U8(7), Token::SeqEnd], "invalid length 3, expected 2 bytes");
+  assert_de_tokens_error::<RAM<3>>(&[Token::Seq { len: Some(2) }, Token::U8(5), Token::U8(6), Token::SeqEnd], "invalid length 2, expected 3 bytes");
+}
@@ -0,0 +1,102 @@
+//! Save and restore every chip which implements Snapshot
+
+use arbitrary_int::u4;
+use chips::{fairchild_f8, mcs4, shifter, Indexer16, Indexer64, RAM, Snapshot};
+use chips::snapshot::SnapshotError;
+
+fn round_trip<T: Snapshot>(chip: &T, fresh: &mut T) {
+  let saved = chip.save();
+  fresh.load(&saved).unwrap();
+  assert_eq!(fresh.save(), saved);
+}
+
+#[test]
+fn ram() {
+  let mut ram: RAM<0x10> = RAM::new();
+  ram.write(3, 0x1234_u16);
+  let mut restored = RAM::new();
+  round_trip(&ram, &mut restored);
+  assert_eq!(restored.read::<u16>(3), 0x1234);
+  assert_eq!(restored.load(&[0; 3]), Err(SnapshotError::WrongLength { expected: 0x10, found: 3 }));
+}
+
+#[test]
+fn indexers() {
+  let mut indexer64 = Indexer64::new();
+  indexer64.write_nibble(15, u4::new(0xA));
+  let mut restored64 = Indexer64::new();
+  round_trip(&indexer64, &mut restored64);
+  assert_eq!(restored64.read_nibble(15), u4::new(0xA));
+
+  let mut indexer16 = Indexer16::new();
+  indexer16.write_nibble(2, u4::new(0x5));
+  let mut restored16 = Indexer16::new();
+  round_trip(&indexer16, &mut restored16);
+  assert_eq!(restored16.read_nibble(2), u4::new(0x5));
+}
+
+#[test]
+fn shifters() {
+  let shifter64 = shifter::Shifter64::<56>::new(0x12_3456_789A_BCDE);
+  let mut restored64 = shifter::Shifter64::<56>::new(0);
+  round_trip(&shifter64, &mut restored64);
+  assert_eq!(restored64.read_parallel(), 0x12_3456_789A_BCDE);
+  //Bits beyond the shifter's width are rejected
+  assert_eq!(restored64.load(&u64::MAX.to_le_bytes()), Err(SnapshotError::InvalidData));
+
+  let shifter16 = shifter::Shifter16::<10>::new(0x3FF);
+  let mut restored16 = shifter::Shifter16::<10>::new(0);
+  round_trip(&shifter16, &mut restored16);
+  assert_eq!(restored16.read_parallel(), 0x3FF);
+  assert_eq!(restored16.load(&0x400_u16.to_le_bytes()), Err(SnapshotError::InvalidData));
+}
+
+#[test]
+fn cpu4004() {
+  let mut rom = vec![
+    0xD5,       //LDM 5
+    0xB3,       //XCH R3
+    0x20, 0x12, //FIM R0R1 0x12
+    0xFA,       //STC
+    0x40, 0x00, //JUN 0
+  ];
+  rom.resize(0x100, 0);
+  let mut board = mcs4::Board::new(rom.clone(), 1);
+  for _ in 0..5 {
+    board.run_cycle();
+  }
+  let mut restored = mcs4::Board::new(rom, 1);
+  round_trip(&board.cpu, &mut restored.cpu);
+
+  //Both boards continue on the same way
+  for _ in 0..3 {
+    board.run_cycle();
+    restored.run_cycle();
+  }
+  assert_eq!(board.cpu.save(), restored.cpu.save());
+}
+
+#[test]
+fn cpu3850() {
+  let mut rom = vec![
+    0x20, 0x42, //LI 0x42
+    0x50,       //LR R0, Acc
+    0x63,       //LISU 3
+    0x6A,       //LISL 2
+    0x5C,       //LR [ISAR], Acc
+    0x1E,       //LR J, W
+  ];
+  rom.resize(1024, 0x2B); //NOP
+  let mut board = fairchild_f8::Board::new(Some(rom), None);
+  for _ in 0..6 {
+    board.run_cycle();
+  }
+  let mut restored = fairchild_f8::Board::new(None, None);
+  round_trip(&board.cpu, &mut restored.cpu);
+  assert_eq!(restored.cpu.regs[0], 0x42);
+  assert_eq!(restored.cpu.regs[0o32], 0x42);
+
+  let mut bad_isar = board.cpu.save();
+  bad_isar[2] = 0x40;
+  assert_eq!(restored.cpu.load(&bad_isar), Err(SnapshotError::InvalidData));
+}
@@ -11,15 +11,10 @@ fn test() {
   let mut test_code = Vec::new();
   f.read_to_end(&mut test_code).unwrap();
 
-  //Reading a COM file. COM files start at memory address 0x100.
-  let mut memory = vec![0u8; 0x100];
-  memory[0] = 0xc3; memory[1] = 0; memory[2] = 0x1; //JMP 0x100
-  memory[5] = 0xc9; //RET
-  memory.append(&mut test_code);
-  memory.resize(0x1_0000, 0xFF);
-  let memory: [u8;0x1_0000] = memory.try_into().unwrap();
   let mut ram = ram::RAM::<0x1_0000>::new();
-  ram.set_total(memory);
+  //Unused memory reads as 0xFF. load_com clears the zero page again
+  ram.set_total([0xFF; 0x1_0000]);
+  cpu::i8080::load_com(&mut ram, &test_code);
   
   let mut cpu = cpu::I8080::new();
   
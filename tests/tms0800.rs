@@ -0,0 +1,95 @@
+//! Testing pieces of the TMS0800 calculator chip
+
+use arbitrary_int::{u2, u4, u5, u9, u11};
+use chips::shifter::Shifter16;
+use chips::display::{DisplayFrame, HasDisplay};
+use chips::tms0800::TMS0800;
+use chips::SegmentDecoder;
+use chips::tms0800::alu::{ALU, Opcode, Register};
+use chips::tms0800::control::ControlUnit;
+use chips::tms0800::keypad::{CalcKey, Keypad, TiLayout};
+
+#[test]
+fn press_equals() {
+  let code = TiLayout.keycode(CalcKey::Equals);
+  assert_eq!(code, 0b10_0000000001);
+
+  let mut control = ControlUnit::new();
+  control.press(&TiLayout, CalcKey::Equals);
+  assert_eq!(control.current_keypress, code);
+
+  //Jump on key match to 0x55. The scan starts at D10, so no match yet
+  let all_digits = Shifter16::<11>::new(0x7FF);
+  let key_match = u11::new(0x55);
+  control.run_cycle(all_digits, key_match, u2::new(2), u5::new(0), false);
+  assert_eq!(control.pc, u9::new(1));
+
+  //Walk the D scan bit down to D1, using NOPs
+  for _ in 0..8 {
+    control.run_cycle(all_digits, u11::new(0), u2::new(2), u5::new(16), false);
+  }
+  control.run_cycle(all_digits, key_match, u2::new(2), u5::new(0), false);
+  assert_eq!(control.pc, u9::new(0x55));
+
+  control.release();
+  assert_eq!(control.current_keypress, 0);
+}
+
+#[test]
+fn distinct_keycodes() {
+  let mut keys: Vec<CalcKey> = (0..10).map(CalcKey::Digit).collect();
+  keys.extend([CalcKey::Decimal, CalcKey::Add, CalcKey::Subtract, CalcKey::Multiply, CalcKey::Divide, CalcKey::Equals, CalcKey::Clear, CalcKey::ClearEntry]);
+  let mut codes: Vec<u16> = keys.iter().map(|&key| TiLayout.keycode(key)).collect();
+  codes.sort();
+  codes.dedup();
+  assert_eq!(codes.len(), keys.len());
+}
+
+#[test]
+fn segment_output() {
+  let mut alu = ALU::new([Opcode::WAIT; 32], [u4::new(0); 16]);
+  alu.a = Register::new(0x000_0000_0042);
+  alu.b = Register::new(0xFFF_FFFF_FF00);
+  let decoder = SegmentDecoder::new_s74x47();
+  let segments = alu.segment_output(&decoder);
+  assert_eq!(segments[..9], [0; 9]);
+  assert_eq!(segments[9..], [decoder.decode(u4::new(4)), decoder.decode(u4::new(2))]);
+
+  //Nothing blanked shows all the zeros, but never a zero for a positive sign
+  alu.b = Register::new(0);
+  let segments = alu.segment_output(&decoder);
+  assert_eq!(segments[0], 0);
+  assert!(segments[1..9].iter().all(|&segments| segments == decoder.decode(u4::new(0))));
+}
+
+#[test]
+fn negative_sign() {
+  let mut alu = ALU::new([Opcode::WAIT; 32], [u4::new(0); 16]);
+  //-42, with leading zeros blanked
+  alu.a = Register::new(0x900_0000_0042);
+  alu.b = Register::new(0xFFF_FFFF_FF00);
+  let decoder = SegmentDecoder::new_s74x47();
+  let segments = alu.segment_output(&decoder);
+  assert_eq!(segments[0], 0b100_0000);
+  assert_eq!(segments[1..9], [0; 8]);
+  assert_eq!(segments[9..], [decoder.decode(u4::new(4)), decoder.decode(u4::new(2))]);
+
+  //Positive leaves the sign column blank
+  alu.a = Register::new(0x000_0000_0042);
+  assert_eq!(alu.segment_output(&decoder)[0], 0);
+}
+
+#[test]
+fn display_frame() {
+  let mut calc = TMS0800::new([u11::new(0); 320], [Opcode::WAIT; 32], [u11::new(0); 16], [u4::new(0); 16]);
+  //-4.2, with leading zeros blanked
+  calc.alu.a = Register::new(0x900_0000_0042);
+  calc.alu.b = Register::new(0xFFF_FFFF_FF20);
+  let decoder = SegmentDecoder::new_s74x47();
+  let DisplayFrame::Segments(segments) = calc.pixels() else { panic!("TMS0800 displays segments") };
+  assert_eq!(segments, calc.alu.segment_output(&decoder));
+  //The blank sign position isn't a 9
+  assert_eq!(segments[0], 0b100_0000);
+  assert_eq!(segments[1..9], [0; 8]);
+  assert_eq!(segments[9..], [decoder.decode(u4::new(4)) | 0x80, decoder.decode(u4::new(2))]);
+}